@@ -1,10 +1,20 @@
 use anylist_rs::{AnyListClient, SavedTokens};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::ops::Deref;
+use std::sync::{Arc, OnceLock};
 
+use crate::credentials::{
+    lock_file, move_tokens, open_store, write_private, CredentialStore, FileLock, StoreKind,
+};
 use crate::error::CliError;
 
-fn get_or_create_config_dir() -> Result<PathBuf, CliError> {
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Profile chosen with `--profile` or `ANYLIST_PROFILE` for this invocation.
+static SELECTED_PROFILE: OnceLock<String> = OnceLock::new();
+
+pub fn get_or_create_config_dir() -> Result<PathBuf, CliError> {
     let config_dir = dirs::config_dir()
         .ok_or(CliError::ConfigDirNotFound)?
         .join("anylist_rs");
@@ -17,66 +27,214 @@ fn get_or_create_config_dir() -> Result<PathBuf, CliError> {
     Ok(config_dir)
 }
 
-pub fn read_tokens() -> Result<SavedTokens, CliError> {
-    let config_dir = get_or_create_config_dir()?;
-    let config_file = config_dir.join("config.json");
+pub fn config_file_path() -> Result<PathBuf, CliError> {
+    Ok(get_or_create_config_dir()?.join("config.json"))
+}
+
+/// Use `name` instead of the config file's current profile for this run.
+pub fn select_profile(name: &str) {
+    let _ = SELECTED_PROFILE.set(name.to_string());
+}
+
+/// Load the config file, moving a pre-profiles config (tokens at the top
+/// level) into the default profile.
+///
+/// The config looks like:
+///
+/// ```json
+/// { "current_profile": "default", "profiles": { "default": { "access_token": "..." } } }
+/// ```
+pub fn load_config() -> Result<serde_json::Value, CliError> {
+    let config_file = config_file_path()?;
 
     if !Path::new(&config_file).exists() {
-        return Err(CliError::ConfigFileNotFound);
+        return Ok(serde_json::json!({ "profiles": {} }));
     }
 
     let config_contents = fs::read_to_string(&config_file)?;
-    let config: serde_json::Value = serde_json::from_str(&config_contents)?;
-
-    let access_token = config["access_token"]
-        .as_str()
-        .ok_or_else(|| CliError::ConfigFileInvalid("access_token".to_string()))?
-        .to_string();
-
-    let refresh_token = config["refresh_token"]
-        .as_str()
-        .ok_or_else(|| CliError::ConfigFileInvalid("refresh_token".to_string()))?
-        .to_string();
-
-    let user_id = config["user_id"]
-        .as_str()
-        .ok_or_else(|| CliError::ConfigFileInvalid("user_id".to_string()))?
-        .to_string();
-
-    let is_premium_user = config["is_premium"]
-        .as_str()
-        .ok_or_else(|| CliError::ConfigFileInvalid("is_premium".to_string()))?
-        .parse::<bool>()
-        .map_err(|_| CliError::ConfigFileInvalid("is_premium (invalid boolean)".to_string()))?;
-
-    Ok(SavedTokens {
-        access_token,
-        refresh_token,
-        user_id,
-        is_premium_user,
-    })
-}
-
-pub fn save_credentials(client: AnyListClient) -> Result<(), CliError> {
-    let config_dir = get_or_create_config_dir()?;
-    let config_file = config_dir.join("config.json");
-
-    let mut config = if Path::new(&config_file).exists() {
-        let config_contents = fs::read_to_string(&config_file)?;
-        serde_json::from_str(&config_contents)?
-    } else {
-        serde_json::Value::Object(serde_json::Map::new())
+    let mut config: serde_json::Value = serde_json::from_str(&config_contents)?;
+
+    if config.get("profiles").is_none() {
+        // Re-read under the lock in case another invocation migrated first
+        let _lock = lock_config()?;
+        config = serde_json::from_str(&fs::read_to_string(&config_file)?)?;
+        if config.get("profiles").is_some() {
+            return Ok(config);
+        }
+
+        let mut profile = serde_json::Map::new();
+        if let Some(old) = config.as_object_mut() {
+            for key in ["access_token", "refresh_token", "user_id", "is_premium"] {
+                if let Some(value) = old.remove(key) {
+                    profile.insert(key.to_string(), value);
+                }
+            }
+        }
+        config["profiles"] = serde_json::json!({});
+        if !profile.is_empty() {
+            config["profiles"][DEFAULT_PROFILE] = serde_json::Value::Object(profile);
+            config["current_profile"] = serde_json::Value::String(DEFAULT_PROFILE.to_string());
+        }
+        write_config(&config)?;
+    }
+
+    Ok(config)
+}
+
+/// Hold an exclusive lock on the config until the returned guard is dropped,
+/// so concurrent invocations don't interleave their read-modify-write cycles.
+/// Nested calls within one invocation share the lock.
+pub fn lock_config() -> Result<FileLock, CliError> {
+    lock_file(&get_or_create_config_dir()?.join("config.lock"))
+}
+
+pub fn write_config(config: &serde_json::Value) -> Result<(), CliError> {
+    let _lock = lock_config()?;
+    let config_contents = serde_json::to_string(config)?;
+    write_private(&config_file_path()?, &config_contents)
+}
+
+/// The profile this invocation uses: `--profile`/`ANYLIST_PROFILE`, then the
+/// config's current profile, then "default".
+pub fn active_profile(config: &serde_json::Value) -> String {
+    SELECTED_PROFILE
+        .get()
+        .cloned()
+        .or_else(|| config["current_profile"].as_str().map(str::to_string))
+        .unwrap_or_else(|| DEFAULT_PROFILE.to_string())
+}
+
+/// Name of the profile this invocation uses.
+pub fn current_profile() -> Result<String, CliError> {
+    Ok(active_profile(&load_config()?))
+}
+
+/// The store holding a profile's tokens. Profiles saved before stores were
+/// configurable keep their tokens in the config file.
+pub fn profile_store(profile: &serde_json::Value) -> Result<Box<dyn CredentialStore>, CliError> {
+    let kind = match profile["credential_store"].as_str() {
+        None => StoreKind::File,
+        Some(name) => StoreKind::parse(name)
+            .ok_or_else(|| CliError::ConfigFileInvalid(format!("credential_store (unknown store '{}')", name)))?,
     };
+    Ok(open_store(kind, &config_file_path()?))
+}
+
+pub fn read_tokens() -> Result<SavedTokens, CliError> {
+    if !config_file_path()?.exists() {
+        return Err(CliError::ConfigFileNotFound);
+    }
+
+    let config = load_config()?;
+    let profile_name = active_profile(&config);
+    let profile = &config["profiles"][&profile_name];
+    if profile.is_null() {
+        return Err(CliError::ProfileNotFound(profile_name));
+    }
+
+    profile_store(profile)?
+        .load(&profile_name)?
+        .ok_or(CliError::NotLoggedIn(profile_name))
+}
+
+/// Save the client's tokens to the active profile. Returns the profile name.
+///
+/// `store` picks where the tokens go; without it, an existing profile keeps
+/// its current store and a new one uses the best store available.
+pub fn save_credentials(client: AnyListClient, store: Option<StoreKind>) -> Result<String, CliError> {
+    save_tokens(&client.export_tokens()?, store)
+}
+
+fn save_tokens(tokens: &SavedTokens, store: Option<StoreKind>) -> Result<String, CliError> {
+    let _lock = lock_config()?;
+    let config = load_config()?;
+    let profile_name = active_profile(&config);
+
+    let previous = config["profiles"]
+        .get(&profile_name)
+        .map(profile_store)
+        .transpose()?;
+    let kind = store
+        .or_else(|| previous.as_ref().map(|store| store.kind()))
+        .unwrap_or_else(StoreKind::best_available);
+
+    // Saved to the new store before the profile points there, and deleted
+    // from the old one only after that, so a failure loses no tokens
+    move_tokens(
+        open_store(kind, &config_file_path()?).as_ref(),
+        previous.as_deref(),
+        &profile_name,
+        tokens,
+        || {
+            // File-backed stores have just rewritten the config
+            let mut config = load_config()?;
+            let profile = &mut config["profiles"][&profile_name];
+            profile["credential_store"] = serde_json::Value::String(kind.as_str().to_string());
+            // Kept outside the store so `profile list` works without unlocking it
+            profile["user_id"] = serde_json::Value::String(tokens.user_id.to_string());
+            profile["is_premium"] = serde_json::Value::String(tokens.is_premium_user.to_string());
+
+            // The first account you log in to becomes the current one
+            if config["current_profile"].as_str().is_none() {
+                config["current_profile"] = serde_json::Value::String(profile_name.clone());
+            }
+            write_config(&config)
+        },
+    )?;
+
+    Ok(profile_name)
+}
 
-    let tokens = client.export_tokens()?;
+/// An authenticated client for the active profile.
+///
+/// The client refreshes expired access tokens on its own; when the session is
+/// dropped, any tokens that changed are written back so the next invocation
+/// doesn't have to refresh again (or fail once the old refresh token has been
+/// rotated out).
+pub struct Session {
+    client: Arc<AnyListClient>,
+    loaded: SavedTokens,
+}
+
+impl Session {
+    pub fn open() -> Result<Self, CliError> {
+        let loaded = read_tokens()?;
+        let client = AnyListClient::from_tokens(loaded.clone())?;
+        Ok(Session {
+            client: Arc::new(client),
+            loaded,
+        })
+    }
 
-    config["access_token"] = serde_json::Value::String(tokens.access_token.to_string());
-    config["refresh_token"] = serde_json::Value::String(tokens.refresh_token.to_string());
-    config["user_id"] = serde_json::Value::String(tokens.user_id.to_string());
-    config["is_premium"] = serde_json::Value::String(tokens.is_premium_user.to_string());
+    /// A handle for APIs that need shared ownership, e.g. realtime sync.
+    pub fn shared(&self) -> Arc<AnyListClient> {
+        Arc::clone(&self.client)
+    }
 
-    let config_contents = serde_json::to_string(&config)?;
-    fs::write(&config_file, config_contents)?;
+    fn persist_refreshed_tokens(&self) -> Result<(), CliError> {
+        let current = self.client.export_tokens()?;
+        if current.access_token == self.loaded.access_token
+            && current.refresh_token == self.loaded.refresh_token
+        {
+            return Ok(());
+        }
+        save_tokens(&current, None)?;
+        Ok(())
+    }
+}
 
-    Ok(())
+impl Deref for Session {
+    type Target = AnyListClient;
+
+    fn deref(&self) -> &AnyListClient {
+        &self.client
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        if let Err(err) = self.persist_refreshed_tokens() {
+            eprintln!("Warning: could not save refreshed tokens: {}", err);
+        }
+    }
 }
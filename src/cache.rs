@@ -0,0 +1,486 @@
+//! Local snapshot of AnyList data for `--offline` use.
+//!
+//! Online commands that read lists, recipes or meal plans save what they
+//! fetched to `cache/<profile>/snapshot.json` in the config directory. Item
+//! changes made with `--offline` are appended to `cache/<profile>/queue.json`
+//! and replayed by [`replay_queue`] at the start of the next online command.
+
+use anylist_rs::{AnyListClient, List, MealPlanEvent, Recipe};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::auth::{current_profile, get_or_create_config_dir, Session, DEFAULT_PROFILE};
+use crate::credentials::{lock_file, write_private, FileLock};
+use crate::error::CliError;
+use crate::item_parser::normalize_name;
+use crate::meal_calendar::MealCalendar;
+use crate::output::{ListItemRecord, ListRecord};
+use crate::quantity::combine_quantities;
+use crate::shopping::combine_details;
+
+fn cache_dir() -> Result<PathBuf, CliError> {
+    let root = get_or_create_config_dir()?.join("cache");
+    let profile = current_profile()?;
+    let dir = root.join(&profile);
+    if !dir.exists() {
+        fs::create_dir_all(&dir).map_err(CliError::ConfigDirCreationFailed)?;
+
+        // Caches from before profiles existed belong to the default account
+        if profile == DEFAULT_PROFILE {
+            for file in ["snapshot.json", "queue.json"] {
+                if root.join(file).exists() {
+                    fs::rename(root.join(file), dir.join(file))?;
+                }
+            }
+        }
+    }
+    Ok(dir)
+}
+
+/// Delete everything cached for a profile, e.g. when the profile is removed.
+pub fn forget_profile(profile: &str) -> Result<(), CliError> {
+    let dir = get_or_create_config_dir()?.join("cache").join(profile);
+    if dir.exists() {
+        fs::remove_dir_all(dir)?;
+    }
+    Ok(())
+}
+
+fn now() -> String {
+    Local::now().to_rfc3339()
+}
+
+// ============================================================================
+// Snapshot
+// ============================================================================
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Snapshot {
+    #[serde(default)]
+    pub lists: Vec<List>,
+    #[serde(default)]
+    pub lists_saved_at: Option<String>,
+    #[serde(default)]
+    pub recipes: Vec<Recipe>,
+    #[serde(default)]
+    pub recipes_saved_at: Option<String>,
+    #[serde(default)]
+    pub meal_plan_events: Vec<MealPlanEvent>,
+    #[serde(default)]
+    pub meal_plan_saved_at: Option<String>,
+    #[serde(default)]
+    pub meal_calendar: Option<MealCalendar>,
+}
+
+impl Snapshot {
+    fn path() -> Result<PathBuf, CliError> {
+        Ok(cache_dir()?.join("snapshot.json"))
+    }
+
+    pub fn load() -> Result<Self, CliError> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(Snapshot::default());
+        }
+        let contents = fs::read_to_string(&path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+
+    fn save(&self) -> Result<(), CliError> {
+        let contents = serde_json::to_string(self)?;
+        write_private(&Self::path()?, &contents)
+    }
+
+    pub fn list_by_name(&self, name: &str) -> Result<&List, CliError> {
+        self.lists
+            .iter()
+            .find(|list| list.name() == name)
+            .ok_or_else(|| CliError::NotCached(format!("list '{}'", name)))
+    }
+
+    /// Cached events between two `YYYY-MM-DD` dates, inclusive.
+    pub fn meal_plan_events_between(&self, start_date: &str, end_date: &str) -> Vec<MealPlanEvent> {
+        self.meal_plan_events
+            .iter()
+            .filter(|event| event.date() >= start_date && event.date() <= end_date)
+            .cloned()
+            .collect()
+    }
+}
+
+/// Update the snapshot, warning instead of failing: the cache is a convenience
+/// and should never break an online command.
+fn update_snapshot(update: impl FnOnce(&mut Snapshot)) {
+    let result = Snapshot::load().and_then(|mut snapshot| {
+        update(&mut snapshot);
+        snapshot.save()
+    });
+    if let Err(err) = result {
+        eprintln!("Warning: could not update offline cache: {}", err);
+    }
+}
+
+pub fn remember_lists(lists: &[List]) {
+    update_snapshot(|snapshot| {
+        snapshot.lists = lists.to_vec();
+        snapshot.lists_saved_at = Some(now());
+    });
+}
+
+pub fn remember_list(list: &List) {
+    update_snapshot(|snapshot| {
+        match snapshot.lists.iter_mut().find(|cached| cached.id() == list.id()) {
+            Some(cached) => *cached = list.clone(),
+            None => snapshot.lists.push(list.clone()),
+        }
+        snapshot.lists_saved_at = Some(now());
+    });
+}
+
+pub fn remember_recipes(recipes: &[Recipe]) {
+    update_snapshot(|snapshot| {
+        snapshot.recipes = recipes.to_vec();
+        snapshot.recipes_saved_at = Some(now());
+    });
+}
+
+/// Replace the cached events in a date range with freshly fetched ones.
+pub fn remember_meal_plan_events(start_date: &str, end_date: &str, events: &[MealPlanEvent]) {
+    update_snapshot(|snapshot| {
+        snapshot
+            .meal_plan_events
+            .retain(|event| event.date() < start_date || event.date() > end_date);
+        snapshot.meal_plan_events.extend(events.iter().cloned());
+        snapshot.meal_plan_events.sort_by(|a, b| a.date().cmp(b.date()));
+        snapshot.meal_plan_saved_at = Some(now());
+    });
+}
+
+pub fn remember_meal_calendar(calendar: &MealCalendar) {
+    update_snapshot(|snapshot| snapshot.meal_calendar = Some(calendar.clone()));
+}
+
+// ============================================================================
+// Mutation queue
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Mutation {
+    Add {
+        name: String,
+        quantity: Option<String>,
+        details: Option<String>,
+        category: Option<String>,
+    },
+    Check {
+        item_id: String,
+        item_name: String,
+    },
+    Uncheck {
+        item_id: String,
+        item_name: String,
+    },
+}
+
+impl Mutation {
+    fn describe(&self) -> String {
+        match self {
+            Mutation::Add { name, .. } => format!("add '{}'", name),
+            Mutation::Check { item_name, .. } => format!("check '{}'", item_name),
+            Mutation::Uncheck { item_name, .. } => format!("uncheck '{}'", item_name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedMutation {
+    pub list_id: String,
+    pub list_name: String,
+    pub queued_at: String,
+    #[serde(flatten)]
+    pub mutation: Mutation,
+}
+
+fn queue_path() -> Result<PathBuf, CliError> {
+    Ok(cache_dir()?.join("queue.json"))
+}
+
+/// Hold the queue against other invocations while reading and rewriting it.
+fn lock_queue() -> Result<FileLock, CliError> {
+    lock_file(&cache_dir()?.join("queue.lock"))
+}
+
+pub fn load_queue() -> Result<Vec<QueuedMutation>, CliError> {
+    let path = queue_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents = fs::read_to_string(&path)?;
+    Ok(serde_json::from_str(&contents)?)
+}
+
+fn save_queue(queue: &[QueuedMutation]) -> Result<(), CliError> {
+    let path = queue_path()?;
+    if queue.is_empty() {
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        return Ok(());
+    }
+    write_private(&path, &serde_json::to_string_pretty(queue)?)
+}
+
+/// Queue a change, folding an add into an earlier queued add of the same item
+/// on the same list: adding milk twice offline queues one milk with both
+/// quantities, like adding it twice online merges into one item.
+fn push_merged(queue: &mut Vec<QueuedMutation>, entry: QueuedMutation) {
+    if let Mutation::Add { name, quantity, details, category } = &entry.mutation {
+        let key = normalize_name(name);
+        let earlier = queue.iter_mut().find(|queued| {
+            queued.list_id == entry.list_id
+                && matches!(&queued.mutation, Mutation::Add { name, .. } if normalize_name(name) == key)
+        });
+        if let Some(QueuedMutation {
+            mutation:
+                Mutation::Add {
+                    quantity: queued_quantity,
+                    details: queued_details,
+                    category: queued_category,
+                    ..
+                },
+            ..
+        }) = earlier
+        {
+            *queued_quantity = combine_quantities(queued_quantity.as_deref(), quantity.as_deref());
+            *queued_details = combine_details(queued_details.as_deref(), details.as_deref());
+            if category.is_some() {
+                queued_category.clone_from(category);
+            }
+            return;
+        }
+    }
+    queue.push(entry);
+}
+
+pub fn enqueue(list: &List, mutation: Mutation) -> Result<(), CliError> {
+    let _lock = lock_queue()?;
+    let mut queue = load_queue()?;
+    push_merged(
+        &mut queue,
+        QueuedMutation {
+            list_id: list.id().to_string(),
+            list_name: list.name().to_string(),
+            queued_at: now(),
+            mutation,
+        },
+    );
+    save_queue(&queue)
+}
+
+/// Show queued changes in an offline view of a list.
+pub fn apply_queue(record: &mut ListRecord, queue: &[QueuedMutation]) {
+    for entry in queue.iter().filter(|entry| entry.list_id == record.id) {
+        match &entry.mutation {
+            Mutation::Add { name, quantity, details, category } => {
+                record.items.push(ListItemRecord {
+                    id: "pending".to_string(),
+                    list_id: record.id.clone(),
+                    name: name.clone(),
+                    quantity: quantity.clone(),
+                    details: details.clone(),
+                    category: category.clone(),
+                    checked: false,
+                });
+            }
+            Mutation::Check { item_id, .. } | Mutation::Uncheck { item_id, .. } => {
+                let checked = matches!(entry.mutation, Mutation::Check { .. });
+                if let Some(item) = record.items.iter_mut().find(|item| &item.id == item_id) {
+                    item.checked = checked;
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ReplayReport {
+    pub applied: Vec<String>,
+    pub conflicts: Vec<String>,
+    /// Entries left in the queue because the API could not be reached
+    pub deferred: usize,
+}
+
+/// Send queued offline changes to AnyList.
+///
+/// Changes that no longer make sense (the item was removed, was already
+/// checked on another device, ...) are dropped and reported as conflicts. If
+/// a request fails, that entry and everything after it stay queued so the
+/// order of changes is preserved.
+///
+/// The queue stays locked until the replay is done, so offline changes made
+/// meanwhile wait rather than being merged into entries already sent.
+pub async fn replay_queue(client: &AnyListClient) -> Result<ReplayReport, CliError> {
+    let _lock = lock_queue()?;
+    // Queues saved by older versions can hold the same add twice
+    let mut queue = Vec::new();
+    for entry in load_queue()? {
+        push_merged(&mut queue, entry);
+    }
+    let mut report = ReplayReport::default();
+    if queue.is_empty() {
+        return Ok(report);
+    }
+
+    let lists = client.get_lists().await?;
+
+    for (index, entry) in queue.iter().enumerate() {
+        let description = format!("{} in '{}'", entry.mutation.describe(), entry.list_name);
+        let Some(list) = lists.iter().find(|list| list.id() == entry.list_id) else {
+            report.conflicts.push(format!("{}: the list no longer exists", description));
+            continue;
+        };
+
+        let result = match &entry.mutation {
+            Mutation::Add { name, quantity, details, category } => {
+                let key = normalize_name(name);
+                let on_list = list
+                    .items()
+                    .iter()
+                    .any(|item| !item.is_checked() && normalize_name(item.name()) == key);
+                if on_list {
+                    report.conflicts.push(format!("{}: already on the list", description));
+                    continue;
+                }
+                client
+                    .add_item_with_details(
+                        list.id(),
+                        name,
+                        quantity.as_deref(),
+                        details.as_deref(),
+                        category.as_deref(),
+                    )
+                    .await
+                    .map(|_| ())
+            }
+            Mutation::Check { item_id, .. } | Mutation::Uncheck { item_id, .. } => {
+                let check = matches!(entry.mutation, Mutation::Check { .. });
+                match list.items().iter().find(|item| item.id() == item_id) {
+                    None => {
+                        report.conflicts.push(format!("{}: the item was removed", description));
+                        continue;
+                    }
+                    Some(item) if item.is_checked() == check => {
+                        let state = if check { "checked" } else { "unchecked" };
+                        report
+                            .conflicts
+                            .push(format!("{}: already {} on another device", description, state));
+                        continue;
+                    }
+                    Some(_) if check => client.cross_off_item(list.id(), item_id).await,
+                    Some(_) => client.uncheck_item(list.id(), item_id).await,
+                }
+            }
+        };
+
+        if let Err(err) = result {
+            save_queue(&queue[index..])?;
+            report.deferred = queue.len() - index;
+            eprintln!("Warning: could not sync queued changes: {}", err);
+            return Ok(report);
+        }
+        report.applied.push(description);
+    }
+
+    // The lock kept anything new out of the queue, so every entry in it was
+    // sent or dropped as a conflict
+    save_queue(&[])?;
+    if !report.applied.is_empty() {
+        remember_lists(&client.get_lists().await?);
+    }
+    Ok(report)
+}
+
+/// Replay the queue before an online command, printing a summary to stderr.
+///
+/// Failures here are reported but never stop the command the user asked for.
+pub async fn sync_queued_changes() {
+    if !matches!(load_queue(), Ok(queue) if !queue.is_empty()) {
+        return;
+    }
+    let client = match Session::open() {
+        Ok(client) => client,
+        Err(_) => return,
+    };
+
+    match replay_queue(&client).await {
+        Ok(report) => {
+            if !report.applied.is_empty() {
+                eprintln!("Synced {} queued change(s).", report.applied.len());
+            }
+            for conflict in &report.conflicts {
+                eprintln!("Conflict, skipped: {}", conflict);
+            }
+            if report.deferred > 0 {
+                eprintln!("{} change(s) still waiting to sync.", report.deferred);
+            }
+        }
+        Err(err) => eprintln!("Warning: could not sync queued changes: {}", err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn add(list_id: &str, name: &str, quantity: Option<&str>, details: Option<&str>) -> QueuedMutation {
+        QueuedMutation {
+            list_id: list_id.to_string(),
+            list_name: "Groceries".to_string(),
+            queued_at: now(),
+            mutation: Mutation::Add {
+                name: name.to_string(),
+                quantity: quantity.map(str::to_string),
+                details: details.map(str::to_string),
+                category: None,
+            },
+        }
+    }
+
+    #[test]
+    fn merges_queued_adds_of_the_same_item() {
+        let mut queue = Vec::new();
+        push_merged(&mut queue, add("list-1", "Milk", Some("1 l"), Some("oat")));
+        push_merged(&mut queue, add("list-1", "milk", Some("500 ml"), Some("cold")));
+
+        assert_eq!(queue.len(), 1);
+        let Mutation::Add { name, quantity, details, .. } = &queue[0].mutation else {
+            panic!("expected an add");
+        };
+        assert_eq!(name, "Milk");
+        assert_eq!(quantity.as_deref(), Some("1.5 l"));
+        assert_eq!(details.as_deref(), Some("oat; cold"));
+    }
+
+    #[test]
+    fn keeps_other_items_and_lists_apart() {
+        let mut queue = Vec::new();
+        push_merged(&mut queue, add("list-1", "Milk", None, None));
+        push_merged(&mut queue, add("list-2", "Milk", None, None));
+        push_merged(&mut queue, add("list-1", "Bread", None, None));
+        push_merged(
+            &mut queue,
+            QueuedMutation {
+                mutation: Mutation::Check {
+                    item_id: "item-1".to_string(),
+                    item_name: "Milk".to_string(),
+                },
+                ..add("list-1", "Milk", None, None)
+            },
+        );
+
+        assert_eq!(queue.len(), 4);
+    }
+}
@@ -1,8 +1,8 @@
-use anylist_rs::AnyListClient;
 use clap::{Arg, ArgMatches, Command};
 
-use crate::auth::read_tokens;
+use crate::auth::Session;
 use crate::error::CliError;
+use crate::output::{self, ActionRecord, CategoryRecord, OutputFormat};
 
 pub fn command() -> Command {
     Command::new("category")
@@ -40,9 +40,8 @@ pub fn command() -> Command {
         )
 }
 
-pub async fn exec_command(matches: &ArgMatches) -> Result<(), CliError> {
-    let tokens = read_tokens()?;
-    let client = AnyListClient::from_tokens(tokens)?;
+pub async fn exec_command(matches: &ArgMatches, format: OutputFormat) -> Result<(), CliError> {
+    let client = Session::open()?;
 
     match matches.subcommand() {
         Some(("add", sub_matches)) => {
@@ -52,13 +51,21 @@ pub async fn exec_command(matches: &ArgMatches) -> Result<(), CliError> {
 
             let list = client.get_list_by_name(list_name).await?;
             let category = client
-                .create_category(&list.id(), category_group_id, name)
+                .create_category(list.id(), category_group_id, name)
                 .await?;
 
-            println!(
-                "Created category '{}' in list '{}'",
-                category.name(), list.name()
-            );
+            output::render(
+                format,
+                &ActionRecord::with_record(
+                    "category.created",
+                    format!("Created category '{}' in list '{}'", category.name(), list.name()),
+                    CategoryRecord {
+                        id: category.id().to_string(),
+                        name: category.name().to_string(),
+                        category_group_id: category_group_id.to_string(),
+                    },
+                ),
+            )?;
         }
         Some(("rename", sub_matches)) => {
             let list_name = sub_matches.get_one::<String>("list").unwrap();
@@ -68,19 +75,31 @@ pub async fn exec_command(matches: &ArgMatches) -> Result<(), CliError> {
 
             let list = client.get_list_by_name(list_name).await?;
             client
-                .rename_category(&list.id(), category_group_id, category_id, name)
+                .rename_category(list.id(), category_group_id, category_id, name)
                 .await?;
 
-            println!("Renamed category to '{}' in list '{}'", name, list.name());
+            output::render(
+                format,
+                &ActionRecord::new(
+                    "category.renamed",
+                    format!("Renamed category to '{}' in list '{}'", name, list.name()),
+                ),
+            )?;
         }
         Some(("delete", sub_matches)) => {
             let list_name = sub_matches.get_one::<String>("list").unwrap();
             let category_id = sub_matches.get_one::<String>("category_id").unwrap();
 
             let list = client.get_list_by_name(list_name).await?;
-            client.delete_category(&list.id(), category_id).await?;
+            client.delete_category(list.id(), category_id).await?;
 
-            println!("Deleted category from list '{}'", list.name());
+            output::render(
+                format,
+                &ActionRecord::new(
+                    "category.deleted",
+                    format!("Deleted category from list '{}'", list.name()),
+                ),
+            )?;
         }
         _ => unreachable!("subcommand_required prevents this"),
     }
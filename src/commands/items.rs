@@ -1,8 +1,19 @@
-use anylist_rs::AnyListClient;
-use clap::{Arg, ArgMatches, Command};
+use anylist_rs::{AnyListClient, List, ListItem};
+use clap::parser::ValueSource;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use inquire::Confirm;
+use regex::{Regex, RegexBuilder};
+use std::fs;
+use std::io::{self, IsTerminal, Read};
 
-use crate::auth::read_tokens;
+use crate::auth::Session;
+use crate::cache::{self, Mutation, Snapshot};
 use crate::error::CliError;
+use crate::item_parser::parse_item;
+use crate::output::{self, ActionRecord, BatchItemResult, BatchRecord, ListItemRecord, OutputFormat};
+use crate::quantity::Quantity;
+use crate::resolve::resolve_to_change;
+use crate::shopping::{add_items, add_or_merge, AddOutcome, MergeMode, NewItem};
 
 pub fn command() -> Command {
     Command::new("item")
@@ -11,9 +22,41 @@ pub fn command() -> Command {
         .arg_required_else_help(true)
         .subcommand(
             Command::new("add")
-                .about("Add an item to a list")
+                .about("Add one or more items to a list")
+                .long_about(
+                    "Add one or more items to a list.\n\n\
+                     Each item may start with a count and quantity and contain '(details)'\n\
+                     and a '#Category', e.g. \"3x 500g Greek yogurt (plain) #Dairy\". Use --raw\n\
+                     to add the text exactly as written.\n\n\
+                     Separate several items with spaces or commas, read one per line from a\n\
+                     file with --from-file, or pass '-' to read them from stdin. --quantity,\n\
+                     --details and --category apply to every item that doesn't set its own.\n\n\
+                     An item already on the list (ignoring case and plurals) is merged into\n\
+                     the existing one, adding up quantities like \"1 l\" and \"500 ml\"; you are\n\
+                     asked first when running in a terminal. A checked-off match is unchecked\n\
+                     instead of added again.",
+                )
                 .arg(Arg::new("list").required(true).help("List name or ID"))
-                .arg(Arg::new("name").required(true).help("Item name"))
+                .arg(
+                    Arg::new("name")
+                        .num_args(1..)
+                        .required_unless_present("from_file")
+                        .value_name("ITEM")
+                        .help("Items to add ('-' reads them from stdin)"),
+                )
+                .args(MergeMode::args())
+                .arg(
+                    Arg::new("raw")
+                        .long("raw")
+                        .action(ArgAction::SetTrue)
+                        .help("Use each item text as the literal name, without parsing quantities or splitting on commas"),
+                )
+                .arg(
+                    Arg::new("from_file")
+                        .long("from-file")
+                        .value_name("FILE")
+                        .help("Read items from a file, one per line"),
+                )
                 .arg(
                     Arg::new("quantity")
                         .short('q')
@@ -37,7 +80,12 @@ pub fn command() -> Command {
             Command::new("update")
                 .about("Update an existing item")
                 .arg(Arg::new("list").required(true).help("List name or ID"))
-                .arg(Arg::new("item_id").required(true).help("Item ID"))
+                .arg(
+                    Arg::new("item")
+                        .required(true)
+                        .value_name("ITEM")
+                        .help("Item name or ID"),
+                )
                 .arg(Arg::new("name").required(true).help("New item name"))
                 .arg(
                     Arg::new("quantity")
@@ -56,100 +104,659 @@ pub fn command() -> Command {
                         .short('c')
                         .long("category")
                         .help("New category"),
-                ),
+                )
+                .arg(yes_arg()),
         )
-        .subcommand(
+        .subcommand(with_filters(
             Command::new("delete")
-                .about("Delete an item from a list")
+                .about("Delete an item, or every item matching filters, from a list")
                 .arg(Arg::new("list").required(true).help("List name or ID"))
-                .arg(Arg::new("item_id").required(true).help("Item ID")),
-        )
-        .subcommand(
+                .arg(item_arg())
+                .arg(yes_arg()),
+        ))
+        .subcommand(with_filters(
             Command::new("check")
-                .about("Check/cross off an item")
+                .about("Check/cross off an item, or every item matching filters")
                 .arg(Arg::new("list").required(true).help("List name or ID"))
-                .arg(Arg::new("item_id").required(true).help("Item ID")),
-        )
-        .subcommand(
+                .arg(item_arg())
+                .arg(yes_arg()),
+        ))
+        .subcommand(with_filters(
             Command::new("uncheck")
-                .about("Uncheck an item")
+                .about("Uncheck an item, or every item matching filters")
                 .arg(Arg::new("list").required(true).help("List name or ID"))
-                .arg(Arg::new("item_id").required(true).help("Item ID")),
+                .arg(item_arg())
+                .arg(yes_arg()),
+        ))
+}
+
+const FILTER_ARGS: [&str; 4] = ["category", "checked", "unchecked", "match"];
+
+/// The single-item argument of check/uncheck/delete, replaced by filters in bulk use.
+fn item_arg() -> Arg {
+    Arg::new("item")
+        .required_unless_present_any(FILTER_ARGS)
+        .conflicts_with_all(FILTER_ARGS)
+        .value_name("ITEM")
+        .help("Item name or ID")
+}
+
+/// Skips the question asked when the item name only partly matches an item,
+/// or before deleting several items.
+fn yes_arg() -> Arg {
+    Arg::new("yes")
+        .short('y')
+        .long("yes")
+        .action(ArgAction::SetTrue)
+        .help("Don't ask for confirmation, even when the item name only partly matches")
+}
+
+fn with_filters(command: Command) -> Command {
+    command
+        .arg(
+            Arg::new("category")
+                .long("category")
+                .value_name("CATEGORY")
+                .help("Only items in this category"),
+        )
+        .arg(
+            Arg::new("checked")
+                .long("checked")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("unchecked")
+                .help("Only checked-off items"),
         )
+        .arg(
+            Arg::new("unchecked")
+                .long("unchecked")
+                .action(ArgAction::SetTrue)
+                .help("Only items that aren't checked off"),
+        )
+        .arg(
+            Arg::new("match")
+                .long("match")
+                .value_name("REGEX")
+                .help("Only items whose name matches this regular expression"),
+        )
+        .arg(
+            Arg::new("dry_run")
+                .long("dry-run")
+                .action(ArgAction::SetTrue)
+                .help("Show the matching items without changing them"),
+        )
+}
+
+/// Which items a bulk check/uncheck/delete applies to.
+struct ItemFilter {
+    category: Option<String>,
+    checked: Option<bool>,
+    pattern: Option<Regex>,
+}
+
+impl ItemFilter {
+    /// The filters given on the command line, or `None` when a single item was named.
+    fn from_matches(matches: &ArgMatches) -> Result<Option<Self>, CliError> {
+        if !FILTER_ARGS.iter().any(|arg| matches.value_source(arg) == Some(ValueSource::CommandLine)) {
+            return Ok(None);
+        }
+        let pattern = matches
+            .get_one::<String>("match")
+            .map(|pattern| {
+                RegexBuilder::new(pattern)
+                    .case_insensitive(true)
+                    .build()
+                    .map_err(|err| CliError::InvalidInput(format!("--match: {}", err)))
+            })
+            .transpose()?;
+        let checked = if matches.get_flag("checked") {
+            Some(true)
+        } else if matches.get_flag("unchecked") {
+            Some(false)
+        } else {
+            None
+        };
+        Ok(Some(ItemFilter {
+            category: matches.get_one::<String>("category").cloned(),
+            checked,
+            pattern,
+        }))
+    }
+
+    fn matches(&self, item: &ListItem) -> bool {
+        self.category
+            .as_ref()
+            .is_none_or(|category| item.category().is_some_and(|c| c.eq_ignore_ascii_case(category)))
+            && self.checked.is_none_or(|checked| item.is_checked() == checked)
+            && self.pattern.as_ref().is_none_or(|pattern| pattern.is_match(item.name()))
+    }
+
+    /// Items the command would change: checking skips items already checked
+    /// off, unchecking skips items that aren't.
+    fn targets<'a>(&self, command: &str, items: &'a [ListItem]) -> Vec<&'a ListItem> {
+        items
+            .iter()
+            .filter(|item| self.matches(item))
+            .filter(|item| match command {
+                "check" => !item.is_checked(),
+                "uncheck" => item.is_checked(),
+                _ => true,
+            })
+            .collect()
+    }
+}
+
+/// Message and record listing the items a dry run would change.
+fn preview(command: &str, list: &List, targets: &[&ListItem]) -> ActionRecord<Vec<ListItemRecord>> {
+    let action = match command {
+        "check" => "item.check_planned",
+        "uncheck" => "item.uncheck_planned",
+        _ => "item.delete_planned",
+    };
+    let mut message = format!("Would {} {} item(s) in list '{}':", command, targets.len(), list.name());
+    for item in targets {
+        message.push_str(&format!("\n  {}", item.name()));
+        if let Some(quantity) = item.quantity() {
+            message.push_str(&format!(" ({})", quantity));
+        }
+    }
+    let records = targets.iter().map(|item| ListItemRecord::from(*item)).collect();
+    ActionRecord::with_record(action, message, records)
+}
+
+/// Queue an add/check/uncheck against the cached list, to be sent by the next
+/// online command.
+fn exec_offline(matches: &ArgMatches, format: OutputFormat) -> Result<(), CliError> {
+    let (command, sub_matches) = matches.subcommand().expect("subcommand_required");
+    let list_name = sub_matches.get_one::<String>("list").unwrap();
+
+    let snapshot = Snapshot::load()?;
+    let list = snapshot.list_by_name(list_name)?;
+
+    if matches!(command, "check" | "uncheck")
+        && let Some(filter) = ItemFilter::from_matches(sub_matches)?
+    {
+        let targets = filter.targets(command, list.items());
+        if sub_matches.get_flag("dry_run") {
+            return output::render(format, &preview(command, list, &targets));
+        }
+        for item in &targets {
+            let (item_id, item_name) = (item.id().to_string(), item.name().to_string());
+            let mutation = if command == "check" {
+                Mutation::Check { item_id, item_name }
+            } else {
+                Mutation::Uncheck { item_id, item_name }
+            };
+            cache::enqueue(list, mutation)?;
+        }
+        let message = format!("Queued {}ing {} item(s) in list '{}'", command, targets.len(), list.name());
+        return output::render(format, &ActionRecord::new("item.queued", message));
+    }
+
+    let (mutation, message) = match command {
+        "add" => {
+            let items = collect_new_items(sub_matches)?;
+            let names: Vec<String> = items.iter().map(|item| format!("'{}'", item.name)).collect();
+            for item in items {
+                cache::enqueue(
+                    list,
+                    Mutation::Add {
+                        name: item.name,
+                        quantity: item.quantity,
+                        details: item.details,
+                        category: item.category,
+                    },
+                )?;
+            }
+            let message = format!("Queued adding {} to list '{}'", names.join(", "), list.name());
+            return output::render(format, &ActionRecord::new("item.queued", message));
+        }
+        "check" | "uncheck" => {
+            let item_query = sub_matches.get_one::<String>("item").unwrap();
+            let check = command == "check";
+            let item = resolve_to_change(
+                item_query,
+                list.items(),
+                |item| item.is_checked() != check,
+                if check { "Check off" } else { "Uncheck" },
+                sub_matches.get_flag("yes"),
+                io::stdin().is_terminal(),
+            )?;
+            let (item_id, item_name) = (item.id().to_string(), item.name().to_string());
+            let message = format!("Queued {}ing '{}' in list '{}'", command, item_name, list.name());
+            let mutation = if check {
+                Mutation::Check { item_id, item_name }
+            } else {
+                Mutation::Uncheck { item_id, item_name }
+            };
+            (mutation, message)
+        }
+        _ => unreachable!("only add, check and uncheck are allowed offline"),
+    };
+
+    cache::enqueue(list, mutation)?;
+    output::render(format, &ActionRecord::new("item.queued", message))
 }
 
-pub async fn exec_command(matches: &ArgMatches) -> Result<(), CliError> {
-    let tokens = read_tokens()?;
-    let client = AnyListClient::from_tokens(tokens)?;
+pub async fn exec_command(matches: &ArgMatches, format: OutputFormat) -> Result<(), CliError> {
+    if matches.get_flag("offline") {
+        return exec_offline(matches, format);
+    }
+
+    let client = Session::open()?;
+
+    if let Some((command @ ("check" | "uncheck" | "delete"), sub_matches)) = matches.subcommand()
+        && let Some(filter) = ItemFilter::from_matches(sub_matches)?
+    {
+        return exec_bulk(&client, command, sub_matches, filter, format).await;
+    }
 
     match matches.subcommand() {
         Some(("add", sub_matches)) => {
             let list_name = sub_matches.get_one::<String>("list").unwrap();
-            let name = sub_matches.get_one::<String>("name").unwrap();
-            let quantity = sub_matches.get_one::<String>("quantity").map(|s| s.as_str());
-            let details = sub_matches.get_one::<String>("details").map(|s| s.as_str());
-            let category = sub_matches.get_one::<String>("category").map(|s| s.as_str());
+            let items = collect_new_items(sub_matches)?;
+
+            let merge = MergeMode::from_matches(sub_matches);
 
             let list = client.get_list_by_name(list_name).await?;
-            let item = client
-                .add_item_with_details(&list.id(), name, quantity, details, category)
-                .await?;
+            if items.len() > 1 {
+                return add_batch(&client, &list, items, merge, format).await;
+            }
 
-            println!("Added item '{}' to list '{}'", item.name(), list.name());
-            if let Some(q) = &item.quantity() {
-                println!("  Quantity: {}", q);
+            let mut on_list: Vec<ListItemRecord> = list.items().iter().map(ListItemRecord::from).collect();
+            let (outcome, item) = add_or_merge(&client, list.id(), &mut on_list, &items[0], merge).await?;
+
+            let (action, mut message) = match outcome {
+                AddOutcome::Added => (
+                    "item.added",
+                    format!("Added item '{}' to list '{}'", item.name, list.name()),
+                ),
+                AddOutcome::Merged => (
+                    "item.merged",
+                    format!("Merged '{}' into '{}' in list '{}'", items[0].name, item.name, list.name()),
+                ),
+                AddOutcome::Unchecked => (
+                    "item.unchecked",
+                    format!("'{}' was checked off; unchecked it in list '{}'", item.name, list.name()),
+                ),
+            };
+            if let Some(q) = &item.quantity {
+                message.push_str(&format!("\n  Quantity: {}", q));
             }
-            if !item.details().is_empty() {
-                println!("  Details: {}", item.details());
+            if let Some(d) = &item.details {
+                message.push_str(&format!("\n  Details: {}", d));
             }
-            if let Some(c) = &item.category() {
-                println!("  Category: {}", c);
+            if let Some(c) = &item.category {
+                message.push_str(&format!("\n  Category: {}", c));
             }
+            output::render(format, &ActionRecord::with_record(action, message, item))?;
         }
         Some(("update", sub_matches)) => {
             let list_name = sub_matches.get_one::<String>("list").unwrap();
-            let item_id = sub_matches.get_one::<String>("item_id").unwrap();
+            let item_query = sub_matches.get_one::<String>("item").unwrap();
             let name = sub_matches.get_one::<String>("name").unwrap();
             let quantity = sub_matches.get_one::<String>("quantity").map(|s| s.as_str());
             let details = sub_matches.get_one::<String>("details").map(|s| s.as_str());
             let category = sub_matches.get_one::<String>("category").map(|s| s.as_str());
 
             let list = client.get_list_by_name(list_name).await?;
+            let item = resolve_to_change(
+                item_query,
+                list.items(),
+                |_| true,
+                "Update",
+                sub_matches.get_flag("yes"),
+                io::stdin().is_terminal(),
+            )?;
             client
-                .update_item(&list.id(), item_id, name, quantity, details, category)
+                .update_item(list.id(), item.id(), name, quantity, details, category)
                 .await?;
 
-            println!("Updated item '{}' in list '{}'", name, list.name());
+            output::render(
+                format,
+                &ActionRecord::new(
+                    "item.updated",
+                    format!("Updated item '{}' in list '{}'", name, list.name()),
+                ),
+            )?;
         }
         Some(("delete", sub_matches)) => {
             let list_name = sub_matches.get_one::<String>("list").unwrap();
-            let item_id = sub_matches.get_one::<String>("item_id").unwrap();
+            let item_query = sub_matches.get_one::<String>("item").unwrap();
 
             let list = client.get_list_by_name(list_name).await?;
-            client.delete_item(&list.id(), item_id).await?;
+            let item = resolve_to_change(
+                item_query,
+                list.items(),
+                |_| true,
+                "Delete",
+                sub_matches.get_flag("yes"),
+                io::stdin().is_terminal(),
+            )?;
+            client.delete_item(list.id(), item.id()).await?;
 
-            println!("Deleted item from list '{}'", list.name());
+            output::render(
+                format,
+                &ActionRecord::with_record(
+                    "item.deleted",
+                    format!("Deleted item '{}' from list '{}'", item.name(), list.name()),
+                    ListItemRecord::from(item),
+                ),
+            )?;
         }
         Some(("check", sub_matches)) => {
             let list_name = sub_matches.get_one::<String>("list").unwrap();
-            let item_id = sub_matches.get_one::<String>("item_id").unwrap();
+            let item_query = sub_matches.get_one::<String>("item").unwrap();
 
             let list = client.get_list_by_name(list_name).await?;
-            client.cross_off_item(&list.id(), item_id).await?;
+            let item = resolve_to_change(
+                item_query,
+                list.items(),
+                |item| !item.is_checked(),
+                "Check off",
+                sub_matches.get_flag("yes"),
+                io::stdin().is_terminal(),
+            )?;
+            client.cross_off_item(list.id(), item.id()).await?;
 
-            println!("Checked off item in list '{}'", list.name());
+            output::render(
+                format,
+                &ActionRecord::with_record(
+                    "item.checked",
+                    format!("Checked off '{}' in list '{}'", item.name(), list.name()),
+                    ListItemRecord::from(item),
+                ),
+            )?;
         }
         Some(("uncheck", sub_matches)) => {
             let list_name = sub_matches.get_one::<String>("list").unwrap();
-            let item_id = sub_matches.get_one::<String>("item_id").unwrap();
+            let item_query = sub_matches.get_one::<String>("item").unwrap();
 
             let list = client.get_list_by_name(list_name).await?;
-            client.uncheck_item(&list.id(), item_id).await?;
+            let item = resolve_to_change(
+                item_query,
+                list.items(),
+                |item| item.is_checked(),
+                "Uncheck",
+                sub_matches.get_flag("yes"),
+                io::stdin().is_terminal(),
+            )?;
+            client.uncheck_item(list.id(), item.id()).await?;
 
-            println!("Unchecked item in list '{}'", list.name());
+            output::render(
+                format,
+                &ActionRecord::with_record(
+                    "item.unchecked",
+                    format!("Unchecked '{}' in list '{}'", item.name(), list.name()),
+                    ListItemRecord::from(item),
+                ),
+            )?;
         }
         _ => unreachable!("subcommand_required prevents this"),
     }
 
     Ok(())
 }
+
+/// Gather the items for `item add` from its arguments, `--from-file` and stdin,
+/// filling in `--quantity`/`--details`/`--category` where an item sets none.
+fn collect_new_items(matches: &ArgMatches) -> Result<Vec<NewItem>, CliError> {
+    let raw = matches.get_flag("raw");
+    let mut texts = Vec::new();
+    for arg in matches.get_many::<String>("name").into_iter().flatten() {
+        if arg == "-" {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input)?;
+            texts.extend(input.lines().map(str::to_string));
+        } else if raw {
+            texts.push(arg.clone());
+        } else {
+            texts.extend(arg.split(',').map(str::to_string));
+        }
+    }
+    if let Some(path) = matches.get_one::<String>("from_file") {
+        texts.extend(fs::read_to_string(path)?.lines().map(str::to_string));
+    }
+
+    // Normalise "2lbs" to "2 lbs", keeping fractions; anything else is kept as written
+    let quantity = matches.get_one::<String>("quantity").map(|text| match Quantity::parse(text) {
+        Some(quantity) if !raw => quantity.to_recipe_string(),
+        _ => text.clone(),
+    });
+    let details = matches.get_one::<String>("details");
+    let category = matches.get_one::<String>("category");
+
+    let items: Vec<NewItem> = texts
+        .iter()
+        .map(|text| text.trim())
+        .filter(|text| !text.is_empty())
+        .map(|text| {
+            let mut item = new_item(text, raw);
+            item.quantity = item.quantity.or_else(|| quantity.clone());
+            item.details = item.details.or_else(|| details.cloned());
+            item.category = item.category.or_else(|| category.cloned());
+            item
+        })
+        .collect();
+
+    if items.is_empty() {
+        return Err(CliError::InvalidInput("no items to add".to_string()));
+    }
+    Ok(items)
+}
+
+fn new_item(text: &str, raw: bool) -> NewItem {
+    if raw {
+        return NewItem {
+            name: text.to_string(),
+            quantity: None,
+            details: None,
+            category: None,
+        };
+    }
+    let parsed = parse_item(text);
+    NewItem {
+        quantity: parsed.quantity_text(),
+        name: parsed.name,
+        details: parsed.details,
+        category: parsed.category,
+    }
+}
+
+/// Add several items to a list, reporting each one. Fails after rendering the
+/// report if any item could not be added.
+async fn add_batch(
+    client: &AnyListClient,
+    list: &List,
+    items: Vec<NewItem>,
+    merge: MergeMode,
+    format: OutputFormat,
+) -> Result<(), CliError> {
+    let record = add_items(client, list, items, merge).await;
+    output::render(format, &record)?;
+    if record.failed > 0 {
+        return Err(CliError::PartialFailure(record.failed, record.results.len()));
+    }
+    Ok(())
+}
+
+/// Check, uncheck or delete every item matching the filters.
+async fn exec_bulk(
+    client: &AnyListClient,
+    command: &str,
+    matches: &ArgMatches,
+    filter: ItemFilter,
+    format: OutputFormat,
+) -> Result<(), CliError> {
+    let list_name = matches.get_one::<String>("list").unwrap();
+    let list = client.get_list_by_name(list_name).await?;
+    let targets = filter.targets(command, list.items());
+
+    if targets.is_empty() {
+        let message = format!("No items in list '{}' to {}", list.name(), command);
+        return output::render(format, &ActionRecord::new("item.none_matched", message));
+    }
+    if matches.get_flag("dry_run") {
+        return output::render(format, &preview(command, &list, &targets));
+    }
+
+    if command == "delete" {
+        if !matches.get_flag("yes") {
+            confirm_delete(&list, targets.len())?;
+        }
+        let ids: Vec<&str> = targets.iter().map(|item| item.id()).collect();
+        client.bulk_delete_items(list.id(), &ids).await?;
+        let records: Vec<ListItemRecord> = targets.iter().map(|item| ListItemRecord::from(*item)).collect();
+        let message = format!("Deleted {} item(s) from list '{}'", records.len(), list.name());
+        return output::render(format, &ActionRecord::with_record("item.deleted", message, records));
+    }
+
+    let mut results = Vec::new();
+    for item in targets {
+        let result = if command == "check" {
+            client.cross_off_item(list.id(), item.id()).await
+        } else {
+            client.uncheck_item(list.id(), item.id()).await
+        };
+        results.push(match result {
+            Ok(()) => {
+                let mut record = ListItemRecord::from(item);
+                record.checked = command == "check";
+                BatchItemResult::ok(item.name(), record)
+            }
+            Err(err) => BatchItemResult::failed(item.name(), CliError::from(err).to_string()),
+        });
+    }
+
+    let action = if command == "check" { "item.checked" } else { "item.unchecked" };
+    let record = BatchRecord::new(action, list.name(), results);
+    output::render(format, &record)?;
+    if record.failed > 0 {
+        return Err(CliError::PartialFailure(record.failed, record.results.len()));
+    }
+    Ok(())
+}
+
+/// Ask before deleting several items; without a terminal, `--yes` is required.
+fn confirm_delete(list: &List, count: usize) -> Result<(), CliError> {
+    if !io::stdin().is_terminal() {
+        return Err(CliError::InvalidInput(format!(
+            "refusing to delete {} item(s) without confirmation; pass --yes",
+            count
+        )));
+    }
+    let prompt = format!("Delete {} item(s) from list '{}'?", count, list.name());
+    if Confirm::new(&prompt).with_default(false).prompt()? {
+        Ok(())
+    } else {
+        Err(CliError::PromptCancelled)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Parse a full command line and return the `item` subcommand's matches,
+    /// panicking with clap's message if it doesn't parse.
+    fn parse(line: &[&str]) -> ArgMatches {
+        let matches = crate::cli()
+            .try_get_matches_from(["anylist", "item"].iter().chain(line))
+            .unwrap_or_else(|err| panic!("{:?} didn't parse: {}", line, err));
+        let (_, item) = matches.subcommand().expect("a subcommand");
+        let (_, sub_matches) = item.subcommand().expect("an item subcommand");
+        sub_matches.clone()
+    }
+
+    #[test]
+    fn keeps_fractions_in_quantities() {
+        let line = ["add", "Groceries", "1/2 cup sugar, flour", "-q", "1 1/2 cups"];
+        let items = collect_new_items(&parse(&line)).unwrap();
+        let quantities: Vec<_> = items
+            .iter()
+            .map(|item| (item.name.as_str(), item.quantity.as_deref()))
+            .collect();
+        assert_eq!(quantities, [("sugar", Some("1/2 cup")), ("flour", Some("1 1/2 cups"))]);
+
+        let items = collect_new_items(&parse(&["add", "Groceries", "sugar", "-q", "2lbs"])).unwrap();
+        assert_eq!(items[0].quantity.as_deref(), Some("2 lbs"));
+        let items = collect_new_items(&parse(&["add", "Groceries", "sugar", "-q", "1/2 cup", "--raw"])).unwrap();
+        assert_eq!(items[0].quantity.as_deref(), Some("1/2 cup"));
+    }
+
+    /// Items as (name, category, checked).
+    fn items(items: &[(&str, Option<&str>, bool)]) -> Vec<ListItem> {
+        items
+            .iter()
+            .enumerate()
+            .map(|(index, (name, category, checked))| {
+                serde_json::from_value(json!({
+                    "id": index.to_string(),
+                    "list_id": "list",
+                    "name": name,
+                    "details": "",
+                    "is_checked": checked,
+                    "quantity": null,
+                    "category": category,
+                    "user_id": null
+                }))
+                .unwrap()
+            })
+            .collect()
+    }
+
+    fn filter(line: &[&str]) -> Option<ItemFilter> {
+        ItemFilter::from_matches(&parse(line)).unwrap()
+    }
+
+    fn names<'a>(items: &[&'a ListItem]) -> Vec<&'a str> {
+        items.iter().map(|item| item.name()).collect()
+    }
+
+    #[test]
+    fn filters_items() {
+        let list = items(&[
+            ("Milk", Some("Dairy"), false),
+            ("Cheddar cheese", Some("dairy"), true),
+            ("Cream cheese", Some("Dairy"), false),
+            ("Bread", Some("Bakery"), true),
+            ("Cheese straws", None, false),
+        ]);
+
+        type Case = (&'static [&'static str], &'static [&'static str]);
+        let cases: &[Case] = &[
+            (&["delete", "Groceries", "--category", "DAIRY"], &["Milk", "Cheddar cheese", "Cream cheese"]),
+            (&["delete", "Groceries", "--checked"], &["Cheddar cheese", "Bread"]),
+            (&["delete", "Groceries", "--unchecked"], &["Milk", "Cream cheese", "Cheese straws"]),
+            (&["delete", "Groceries", "--match", "^CHE"], &["Cheddar cheese", "Cheese straws"]),
+            (&["delete", "Groceries", "--match", "cheese$", "--unchecked"], &["Cream cheese"]),
+            (&["delete", "Groceries", "--category", "Dairy", "--checked"], &["Cheddar cheese"]),
+            (&["delete", "Groceries", "--category", "Frozen"], &[]),
+        ];
+        for &(line, expected) in cases {
+            let filter = filter(line).unwrap_or_else(|| panic!("{:?} should filter", line));
+            assert_eq!(names(&filter.targets("delete", &list)), expected, "{:?}", line);
+        }
+
+        // Checking skips items already checked off, and unchecking the reverse
+        let dairy = filter(&["check", "Groceries", "--category", "dairy"]).unwrap();
+        assert_eq!(names(&dairy.targets("check", &list)), ["Milk", "Cream cheese"]);
+        assert_eq!(names(&dairy.targets("uncheck", &list)), ["Cheddar cheese"]);
+    }
+
+    #[test]
+    fn filters_only_when_asked() {
+        assert!(filter(&["check", "Groceries", "milk"]).is_none());
+        assert!(filter(&["delete", "Groceries", "milk", "--yes"]).is_none());
+        assert!(filter(&["uncheck", "Groceries", "--dry-run", "--checked"]).is_some());
+
+        let err = ItemFilter::from_matches(&parse(&["delete", "Groceries", "--match", "("])).err();
+        assert!(matches!(err, Some(CliError::InvalidInput(message)) if message.starts_with("--match")));
+        assert!(crate::cli().try_get_matches_from(["anylist", "item", "check", "Groceries"]).is_err());
+        assert!(
+            crate::cli()
+                .try_get_matches_from(["anylist", "item", "delete", "Groceries", "milk", "--checked"])
+                .is_err()
+        );
+    }
+}
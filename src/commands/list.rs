@@ -1,70 +1,17 @@
-use anylist_rs::{
-    lists::{List, ListItem},
-    AnyListClient,
-};
-use clap::{Arg, ArgMatches, Command};
+use anylist_rs::List;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use inquire::Confirm;
+use std::io::{self, IsTerminal};
 
-use crate::auth::read_tokens;
+use crate::auth::Session;
+use crate::cache::{self, Snapshot};
 use crate::error::CliError;
-
-fn display_list_items(list: &List) {
-    println!("\n{}", list.name());
-    println!("{}", "=".repeat(list.name().len()));
-    println!();
-
-    // Display unchecked items
-    let unchecked_items: Vec<&ListItem> = list.items().iter().filter(|item| !item.is_checked()).collect();
-    if !unchecked_items.is_empty() {
-        let mut sorted: Vec<&ListItem> = unchecked_items;
-        sorted.sort_by(|a, b| a.name().to_lowercase().cmp(&b.name().to_lowercase()));
-
-        for item in sorted {
-            print!("  [ ] \x1B[1m{}\x1B[0m ({})", item.name(), item.id());
-            if let Some(qty) = &item.quantity() {
-                print!(" ({})", qty);
-            }
-            if !item.details().is_empty() {
-                print!(" - {}", item.details());
-            }
-            if let Some(cat) = &item.category() {
-                print!(" [{}]", cat);
-            }
-            println!();
-        }
-    } else {
-        println!("  (no items)");
-    }
-
-    // Display checked items
-    let checked_items: Vec<&ListItem> = list.items().iter().filter(|item| item.is_checked()).collect();
-    if !checked_items.is_empty() {
-        println!("\nCompleted:");
-        for item in checked_items {
-            print!("  [✓] {}", item.name());
-            if let Some(qty) = &item.quantity() {
-                print!(" ({})", qty);
-            }
-            println!();
-        }
-    }
-    println!();
-}
-
-fn display_lists_names(lists: Vec<List>) {
-    if lists.is_empty() {
-        println!("No lists found.");
-        return;
-    }
-
-    println!("\nYour Lists:");
-    println!("{}", "=".repeat(11));
-    println!();
-    for list in lists {
-        let item_count = list.items().iter().filter(|item| !item.is_checked()).count();
-        println!("  • {} ({} items)", list.name(), item_count);
-    }
-    println!();
-}
+use crate::item_parser::normalize_name;
+use crate::output::{
+    self, ActionRecord, ListItemRecord, ListRecord, ListSummaries, ListSummaryRecord, MergedItemsRecord, OutputFormat,
+};
+use crate::quantity::combine_quantities;
+use crate::shopping::combine_details;
 
 pub fn command() -> Command {
     Command::new("list")
@@ -72,7 +19,7 @@ pub fn command() -> Command {
         .long_about(
             "View and manage your AnyList lists.\n\n\
              By default, this command shows all your lists with item counts.\n\
-             Use subcommands to view, create, rename, or delete lists.",
+             Use subcommands to view, create, rename, delete or deduplicate lists.",
         )
         .subcommand(
             Command::new("get")
@@ -84,6 +31,36 @@ pub fn command() -> Command {
                         .value_name("LIST_NAME"),
                 ),
         )
+        .subcommand(
+            Command::new("dedupe")
+                .about("Merge unchecked items that have the same name")
+                .long_about(
+                    "Merge unchecked items that have the same name, ignoring case and\n\
+                     plurals. The first item is kept with the quantities added up (\"1 l\" and\n\
+                     \"500 ml\" become \"1.5 l\") and the details combined, and the others\n\
+                     are deleted. The merges are shown and confirmed first; pass --yes to\n\
+                     skip the question (required when not running in a terminal).",
+                )
+                .arg(
+                    Arg::new("name")
+                        .help("Name of the list to clean up")
+                        .required(true)
+                        .value_name("LIST_NAME"),
+                )
+                .arg(
+                    Arg::new("dry_run")
+                        .long("dry-run")
+                        .action(ArgAction::SetTrue)
+                        .help("Show what would be merged without changing the list"),
+                )
+                .arg(
+                    Arg::new("yes")
+                        .short('y')
+                        .long("yes")
+                        .action(ArgAction::SetTrue)
+                        .help("Merge without asking for confirmation"),
+                ),
+        )
         .subcommand(
             Command::new("create")
                 .about("Create a new shopping list")
@@ -102,43 +79,228 @@ pub fn command() -> Command {
         )
 }
 
-pub async fn exec_command(matches: &ArgMatches) -> Result<(), CliError> {
-    let tokens = read_tokens()?;
-    let client = AnyListClient::from_tokens(tokens)?;
+/// Show lists from the local snapshot, including changes still waiting to sync.
+fn exec_offline(matches: &ArgMatches, format: OutputFormat) -> Result<(), CliError> {
+    let snapshot = Snapshot::load()?;
+    let saved_at = snapshot
+        .lists_saved_at
+        .clone()
+        .ok_or_else(|| CliError::NotCached("your lists".to_string()))?;
+    eprintln!("Offline: showing lists cached at {}", saved_at);
 
     match matches.subcommand() {
         Some(("get", sub_matches)) => {
             let list_name = sub_matches
                 .get_one::<String>("name")
                 .expect("required argument");
+            let list = snapshot.list_by_name(list_name)?;
+
+            let queue = cache::load_queue()?;
+            let mut record = ListRecord::from(list);
+            cache::apply_queue(&mut record, &queue);
+            output::render(format, &record)?;
+        }
+        _ => {
+            let summaries = snapshot.lists.iter().map(ListSummaryRecord::from).collect();
+            output::render(format, &ListSummaries(summaries))?;
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn exec_command(matches: &ArgMatches, format: OutputFormat) -> Result<(), CliError> {
+    if matches.get_flag("offline") {
+        return exec_offline(matches, format);
+    }
+
+    let client = Session::open()?;
+
+    match matches.subcommand() {
+        Some(("get", sub_matches)) => {
+            let list_name = sub_matches
+                .get_one::<String>("name")
+                .expect("required argument");
+            let list = client.get_list_by_name(list_name).await?;
+            cache::remember_list(&list);
+            output::render(format, &ListRecord::from(&list))?;
+        }
+        Some(("dedupe", sub_matches)) => {
+            let list_name = sub_matches
+                .get_one::<String>("name")
+                .expect("required argument");
+            let dry_run = sub_matches.get_flag("dry_run");
+
             let list = client.get_list_by_name(list_name).await?;
-            display_list_items(&list);
+            let groups = merge_duplicates(&list);
+            if groups.is_empty() {
+                return output::render(
+                    format,
+                    &ActionRecord::new("list.deduplicated", format!("No duplicates in list '{}'", list.name())),
+                );
+            }
+
+            let mut plan = String::new();
+            for group in &groups {
+                plan.push_str(&format!("\n  {} x{}", group.kept.name, group.removed.len() + 1));
+                if let Some(quantity) = &group.kept.quantity {
+                    plan.push_str(&format!(" -> {}", quantity));
+                }
+                if let Some(details) = &group.kept.details {
+                    plan.push_str(&format!(" ({})", details));
+                }
+            }
+
+            if dry_run {
+                let message = format!("Would merge duplicates in list '{}':{}", list.name(), plan);
+                return output::render(format, &ActionRecord::with_record("list.dedupe_planned", message, groups));
+            }
+            if !sub_matches.get_flag("yes") {
+                eprintln!("Duplicates in list '{}':{}", list.name(), plan);
+                if !io::stdin().is_terminal() {
+                    return Err(CliError::InvalidInput(format!(
+                        "refusing to merge {} group(s) of items without confirmation; pass --yes",
+                        groups.len()
+                    )));
+                }
+                if !Confirm::new("Merge them?").with_default(false).prompt()? {
+                    return Err(CliError::PromptCancelled);
+                }
+            }
+
+            for group in &groups {
+                let kept = &group.kept;
+                client
+                    .update_item(
+                        list.id(),
+                        &kept.id,
+                        &kept.name,
+                        kept.quantity.as_deref(),
+                        kept.details.as_deref(),
+                        kept.category.as_deref(),
+                    )
+                    .await?;
+                let removed: Vec<&str> = group.removed.iter().map(|item| item.id.as_str()).collect();
+                client.bulk_delete_items(list.id(), &removed).await?;
+            }
+
+            let message = format!("Merged duplicates in list '{}':{}", list.name(), plan);
+            output::render(format, &ActionRecord::with_record("list.deduplicated", message, groups))?;
         }
         Some(("create", sub_matches)) => {
             let name = sub_matches.get_one::<String>("name").unwrap();
             let list = client.create_list(name).await?;
-            println!("Created list: {} (ID: {})", list.name(), list.id());
+            output::render(
+                format,
+                &ActionRecord::with_record(
+                    "list.created",
+                    format!("Created list: {} (ID: {})", list.name(), list.id()),
+                    ListRecord::from(&list),
+                ),
+            )?;
         }
         Some(("rename", sub_matches)) => {
             let name = sub_matches.get_one::<String>("name").unwrap();
             let new_name = sub_matches.get_one::<String>("new_name").unwrap();
 
             let list = client.get_list_by_name(name).await?;
-            client.rename_list(&list.id(), new_name).await?;
-            println!("Renamed list '{}' to '{}'", name, new_name);
+            client.rename_list(list.id(), new_name).await?;
+            output::render(
+                format,
+                &ActionRecord::new("list.renamed", format!("Renamed list '{}' to '{}'", name, new_name)),
+            )?;
         }
         Some(("delete", sub_matches)) => {
             let name = sub_matches.get_one::<String>("name").unwrap();
 
             let list = client.get_list_by_name(name).await?;
-            client.delete_list(&list.id()).await?;
-            println!("Deleted list '{}'", name);
+            client.delete_list(list.id()).await?;
+            output::render(
+                format,
+                &ActionRecord::new("list.deleted", format!("Deleted list '{}'", name)),
+            )?;
         }
         _ => {
             let lists = client.get_lists().await?;
-            display_lists_names(lists);
+            cache::remember_lists(&lists);
+            let summaries = lists.iter().map(ListSummaryRecord::from).collect();
+            output::render(format, &ListSummaries(summaries))?;
         }
     }
 
     Ok(())
 }
+
+/// Group unchecked items by normalized name, folding each group with more
+/// than one item into its first item: quantities are added up and details
+/// combined as when adding an item that is already on the list.
+fn merge_duplicates(list: &List) -> Vec<MergedItemsRecord> {
+    let mut groups: Vec<(String, Vec<ListItemRecord>)> = Vec::new();
+    for item in list.items().iter().filter(|item| !item.is_checked()) {
+        let key = normalize_name(item.name());
+        match groups.iter_mut().find(|(group_key, _)| *group_key == key) {
+            Some((_, items)) => items.push(ListItemRecord::from(item)),
+            None => groups.push((key, vec![ListItemRecord::from(item)])),
+        }
+    }
+
+    groups
+        .into_iter()
+        .filter(|(_, items)| items.len() > 1)
+        .map(|(_, mut items)| {
+            let removed = items.split_off(1);
+            let mut kept = items.remove(0);
+            for item in &removed {
+                kept.quantity = combine_quantities(kept.quantity.as_deref(), item.quantity.as_deref());
+                kept.details = combine_details(kept.details.as_deref(), item.details.as_deref());
+                kept.category = kept.category.or_else(|| item.category.clone());
+            }
+            MergedItemsRecord { kept, removed }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn item(id: &str, name: &str, quantity: Option<&str>, details: &str, checked: bool) -> serde_json::Value {
+        json!({
+            "id": id,
+            "list_id": "list",
+            "name": name,
+            "details": details,
+            "is_checked": checked,
+            "quantity": quantity,
+            "category": null,
+            "user_id": null
+        })
+    }
+
+    #[test]
+    fn merges_quantities_and_details() {
+        let list: List = serde_json::from_value(json!({
+            "id": "list",
+            "name": "Groceries",
+            "items": [
+                item("1", "Milk", Some("1 l"), "semi-skimmed", false),
+                item("2", "Bread", None, "", false),
+                item("3", "milk", Some("500 ml"), "organic", false),
+                item("4", "Milks", None, "semi-skimmed", false),
+                item("5", "Bread", None, "", true),
+            ],
+            "shared_users": []
+        }))
+        .unwrap();
+
+        let groups = merge_duplicates(&list);
+        assert_eq!(groups.len(), 1, "checked-off items are left alone");
+        let group = &groups[0];
+        assert_eq!(group.kept.id, "1");
+        assert_eq!(group.kept.quantity.as_deref(), Some("1.5 l"));
+        assert_eq!(group.kept.details.as_deref(), Some("semi-skimmed; organic"));
+        let removed: Vec<&str> = group.removed.iter().map(|item| item.id.as_str()).collect();
+        assert_eq!(removed, vec!["3", "4"]);
+    }
+}
@@ -1,34 +1,168 @@
-use anylist_rs::AnyListClient;
-use clap::{ArgMatches, Command};
+use anylist_rs::{AnyListClient, SavedTokens};
+use clap::{Arg, ArgAction, ArgMatches, Command};
 use inquire::{Password, Text};
+use std::fs;
+use std::io::{self, IsTerminal, Read};
 
 use crate::auth::save_credentials;
+use crate::credentials::{EncryptedFileStore, SecretServiceStore, StoreKind, PASSPHRASE_ENV};
 use crate::error::CliError;
+use crate::output::{self, ActionRecord, OutputFormat};
 
 pub fn command() -> Command {
     Command::new("login")
         .about("Login to your AnyList account")
+        .long_about(
+            "Login to your AnyList account.\n\n\
+             Without options you are prompted for your email and password. For scripts\n\
+             and headless machines, pass --email (or ANYLIST_EMAIL) together with\n\
+             --password-stdin (or ANYLIST_PASSWORD), or import tokens saved elsewhere\n\
+             with --import-tokens.",
+        )
+        .arg(
+            Arg::new("credential_store")
+                .long("credential-store")
+                .value_name("STORE")
+                .value_parser(["auto", "file", "encrypted-file", "secret-service"])
+                .default_value("auto")
+                .help("Where to keep tokens: auto picks the system keyring (secret-service) if there is one, else the config file (file); encrypted-file protects the config file with a passphrase"),
+        )
+        .arg(
+            Arg::new("email")
+                .long("email")
+                .value_name("EMAIL")
+                .env("ANYLIST_EMAIL")
+                .help("Account email; skips the email prompt"),
+        )
+        .arg(
+            Arg::new("password_stdin")
+                .long("password-stdin")
+                .action(ArgAction::SetTrue)
+                .help("Read the password from the first line of stdin"),
+        )
+        .arg(
+            Arg::new("import_tokens")
+                .long("import-tokens")
+                .value_name("FILE")
+                .conflicts_with_all(["email", "password_stdin"])
+                .help("Save tokens from a JSON file ('-' for stdin) instead of logging in"),
+        )
 }
 
-pub async fn exec_command(_matches: &ArgMatches) -> Result<(), CliError> {
-    let email = Text::new("Email:")
-        .prompt()?;
+pub async fn exec_command(matches: &ArgMatches, format: OutputFormat) -> Result<(), CliError> {
+    let store = matches
+        .get_one::<String>("credential_store")
+        .and_then(|name| StoreKind::parse(name));
+    if store == Some(StoreKind::SecretService) && !SecretServiceStore::is_available() {
+        return Err(CliError::CredentialStoreError(
+            "the Secret Service is not available (is 'secret-tool' installed and a session bus running?)".to_string(),
+        ));
+    }
+    if store == Some(StoreKind::EncryptedFile) && !EncryptedFileStore::is_available() {
+        return Err(CliError::CredentialStoreError(format!(
+            "no passphrase to encrypt the tokens with; set {} or run in a terminal",
+            PASSPHRASE_ENV
+        )));
+    }
+
+    if let Some(path) = matches.get_one::<String>("import_tokens") {
+        let client = AnyListClient::from_tokens(import_tokens(path)?)?;
+        let profile = save_credentials(client, store)?;
+        return output::render(
+            format,
+            &ActionRecord::new(
+                "auth.tokens_imported",
+                format!("✓ Imported tokens into profile '{}'", profile),
+            ),
+        );
+    }
+
+    let email = match matches.get_one::<String>("email") {
+        Some(email) => email.clone(),
+        None => {
+            require_terminal("no email given; use --email or ANYLIST_EMAIL")?;
+            Text::new("Email:")
+                .prompt()?
+        }
+    };
 
-    let password = Password::new("Password:")
-        .with_display_mode(inquire::PasswordDisplayMode::Masked)
-        .without_confirmation()
-        .prompt()?;
+    let password = if matches.get_flag("password_stdin") {
+        read_password_line()?
+    } else if let Ok(password) = std::env::var("ANYLIST_PASSWORD") {
+        password
+    } else {
+        require_terminal("no password given; use --password-stdin or ANYLIST_PASSWORD")?;
+        Password::new("Password:")
+            .with_display_mode(inquire::PasswordDisplayMode::Masked)
+            .without_confirmation()
+            .prompt()?
+    };
 
     match AnyListClient::login(&email, &password).await {
         Ok(client) => {
-            save_credentials(client)?;
-            println!("✓ Successfully logged in!");
-            println!("\nYou can now use {} to manage your AnyList lists.", env!("CARGO_BIN_NAME"));
-            println!("Try: {} list", env!("CARGO_BIN_NAME"));
-            Ok(())
+            let profile = save_credentials(client, store)?;
+            output::render(
+                format,
+                &ActionRecord::new(
+                    "auth.logged_in",
+                    format!(
+                        "✓ Successfully logged in (profile '{profile}')!\n\nYou can now use {bin} to manage your AnyList lists.\nTry: {bin} list",
+                        bin = env!("CARGO_BIN_NAME")
+                    ),
+                ),
+            )
         }
         Err(err) => {
             Err(CliError::LoginFailed(format!("Authentication failed: {}", err)))
         }
     }
 }
+
+/// Prompting without a terminal would hang (cron, containers), so fail instead.
+fn require_terminal(message: &str) -> Result<(), CliError> {
+    if io::stdin().is_terminal() {
+        Ok(())
+    } else {
+        Err(CliError::LoginFailed(message.to_string()))
+    }
+}
+
+fn read_password_line() -> Result<String, CliError> {
+    let mut line = String::new();
+    io::stdin().read_line(&mut line)?;
+    let password = line.trim_end_matches(['\r', '\n']);
+    if password.is_empty() {
+        return Err(CliError::LoginFailed("no password on stdin".to_string()));
+    }
+    Ok(password.to_string())
+}
+
+/// Read tokens in the format produced by `export_tokens`:
+///
+/// ```json
+/// { "access_token": "...", "refresh_token": "...", "user_id": "...", "is_premium_user": false }
+/// ```
+fn import_tokens(path: &str) -> Result<SavedTokens, CliError> {
+    let contents = if path == "-" {
+        let mut contents = String::new();
+        io::stdin().read_to_string(&mut contents)?;
+        contents
+    } else {
+        fs::read_to_string(path)?
+    };
+
+    let tokens: SavedTokens = serde_json::from_str(&contents)
+        .map_err(|err| CliError::LoginFailed(format!("invalid token file: {}", err)))?;
+
+    for (field, value) in [
+        ("access_token", &tokens.access_token),
+        ("refresh_token", &tokens.refresh_token),
+        ("user_id", &tokens.user_id),
+    ] {
+        if value.trim().is_empty() {
+            return Err(CliError::LoginFailed(format!("invalid token file: {} is empty", field)));
+        }
+    }
+
+    Ok(tokens)
+}
@@ -0,0 +1,37 @@
+use clap::{ArgMatches, Command};
+
+use crate::auth::{active_profile, load_config, lock_config, profile_store};
+use crate::error::CliError;
+use crate::output::{self, ActionRecord, OutputFormat};
+
+pub fn command() -> Command {
+    Command::new("logout")
+        .about("Remove the saved tokens for the active profile")
+        .long_about(
+            "Remove the saved tokens for the active profile.\n\n\
+             The profile itself and its offline cache are kept, so queued changes sync\n\
+             after you log in again. AnyList has no endpoint for revoking tokens, so\n\
+             tokens copied elsewhere stay valid until they expire; change your password\n\
+             to invalidate them. Use 'anylist profile remove' to delete the profile too.",
+        )
+}
+
+pub async fn exec_command(_matches: &ArgMatches, format: OutputFormat) -> Result<(), CliError> {
+    let _lock = lock_config()?;
+    let config = load_config()?;
+    let profile_name = active_profile(&config);
+
+    let Some(profile) = config["profiles"].get(&profile_name) else {
+        return Err(CliError::ProfileNotFound(profile_name));
+    };
+    let store = profile_store(profile)?;
+    if store.load(&profile_name)?.is_none() {
+        return Err(CliError::NotLoggedIn(profile_name));
+    }
+    store.delete(&profile_name)?;
+
+    output::render(
+        format,
+        &ActionRecord::new("auth.logged_out", format!("Logged out of profile '{}'", profile_name)),
+    )
+}
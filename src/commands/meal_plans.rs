@@ -1,8 +1,200 @@
-use anylist_rs::AnyListClient;
-use clap::{Arg, ArgMatches, Command};
+use anylist_rs::{AnyListClient, MealPlanEvent, Recipe};
+use chrono::{Days, Months, NaiveDate};
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use inquire::Confirm;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::collections::HashSet;
+use std::io::{self, IsTerminal};
 
-use crate::auth::read_tokens;
+use crate::auth::Session;
+use crate::cache::{self, Snapshot};
+use crate::dates::{self, Interval};
 use crate::error::CliError;
+use crate::meal_calendar::{self, MealCalendar, MealLabel};
+use crate::meal_suggest::{suggest, MealConstraints};
+use crate::output::{
+    self, ActionRecord, MealCalendarsRecord, MealLabelsRecord, MealPlanEventRecord, MealPlanEventsRecord,
+    MealPlanGridRecord, OutputFormat, PlannedItemRecord, PlannedMealRecord,
+};
+use crate::recipe_search::RecipeQuery;
+use crate::resolve::resolve;
+use crate::shopping::{add_items, aggregate_ingredients, choose_items, MergeMode, NewItem};
+
+/// Most events `meal-plan repeat` adds at once, a year of daily meals.
+const MAX_REPEATS: usize = 366;
+
+/// An event to move or repeat. Events far from today are only found once
+/// a command such as `meal-plan list` has cached their dates.
+fn event_id_arg() -> Arg {
+    Arg::new("event_id").required(true).help(format!(
+        "Event ID; events more than {} years away must have been listed first",
+        EVENT_SEARCH_YEARS
+    ))
+}
+
+/// A date range: a span ("this week"), or a start and an end date. Two
+/// values at most, so flags after the dates are still parsed as flags.
+fn date_span_arg() -> Arg {
+    Arg::new("dates")
+        .value_name("DATES")
+        .num_args(1..=2)
+        .help("Start and end date, or a span such as 'this week', 2026-10 or 2026-W43")
+}
+
+/// The first and last day given by the date range arguments.
+fn date_range(matches: &ArgMatches) -> Result<(NaiveDate, NaiveDate), CliError> {
+    let words: Vec<String> = matches
+        .get_many::<String>("dates")
+        .expect("required or defaulted")
+        .cloned()
+        .collect();
+    dates::parse_span(&words, dates::today())
+}
+
+/// The date range arguments as API dates.
+fn date_span(matches: &ArgMatches) -> Result<(String, String), CliError> {
+    let (first, last) = date_range(matches)?;
+    Ok((dates::format_date(first), dates::format_date(last)))
+}
+
+/// A single-day argument as an API date.
+fn date_arg(matches: &ArgMatches, id: &str) -> Result<String, CliError> {
+    let text = matches.get_one::<String>(id).expect("required argument");
+    Ok(dates::format_date(dates::parse_date(text, dates::today())?))
+}
+
+fn recipe_arg() -> Arg {
+    Arg::new("recipe")
+        .short('r')
+        .long("recipe")
+        .visible_alias("recipe-id")
+        .value_name("RECIPE")
+}
+
+fn label_arg() -> Arg {
+    Arg::new("label")
+        .short('l')
+        .long("label")
+        .visible_alias("label-id")
+        .value_name("LABEL")
+}
+
+fn calendar_arg() -> Arg {
+    Arg::new("calendar")
+        .short('c')
+        .long("calendar")
+        .value_name("CALENDAR_ID")
+        .help("Calendar ID (defaults to your meal-planning calendar)")
+}
+
+fn dry_run_arg() -> Arg {
+    Arg::new("dry_run")
+        .long("dry-run")
+        .action(ArgAction::SetTrue)
+        .help("Show the events that would be created without creating them")
+}
+
+/// Fetch the meal-planning calendar, remembering it for `--offline`.
+async fn load_calendar(client: &AnyListClient) -> Result<Option<MealCalendar>, CliError> {
+    let calendar = meal_calendar::fetch(client).await?;
+    if let Some(calendar) = &calendar {
+        cache::remember_meal_calendar(calendar);
+    }
+    Ok(calendar)
+}
+
+/// `--calendar`, or the account's own calendar.
+fn calendar_id(matches: &ArgMatches, calendar: Option<&MealCalendar>) -> Result<String, CliError> {
+    match matches.get_one::<String>("calendar") {
+        Some(id) => Ok(id.clone()),
+        None => calendar.map(|calendar| calendar.id.clone()).ok_or(CliError::NoMealCalendar),
+    }
+}
+
+/// `--calendar`, or the account's own calendar, fetched only when needed.
+async fn target_calendar_id(matches: &ArgMatches, client: &AnyListClient) -> Result<String, CliError> {
+    match matches.get_one::<String>("calendar") {
+        Some(id) => Ok(id.clone()),
+        None => calendar_id(matches, load_calendar(client).await?.as_ref()),
+    }
+}
+
+/// How many years either side of today `find_event` looks for an event the
+/// cache hasn't seen.
+const EVENT_SEARCH_YEARS: u32 = 2;
+
+/// Look up an event by ID.
+async fn find_event(client: &AnyListClient, event_id: &str) -> Result<MealPlanEvent, CliError> {
+    // anylist_rs only looks events up by date. Try the day the cache last saw
+    // the event on, then a window around today.
+    let cached_date = Snapshot::load().ok().and_then(|snapshot| {
+        snapshot
+            .meal_plan_events
+            .iter()
+            .find(|event| event.id() == event_id)
+            .map(|event| event.date().to_string())
+    });
+    if let Some(date) = cached_date
+        && let Some(event) = client
+            .get_meal_plan_events(&date, &date)
+            .await?
+            .into_iter()
+            .find(|event| event.id() == event_id)
+    {
+        return Ok(event);
+    }
+
+    let today = dates::today();
+    let years = Months::new(12 * EVENT_SEARCH_YEARS);
+    let (start, end) = (
+        today.checked_sub_months(years).unwrap_or(NaiveDate::MIN),
+        today.checked_add_months(years).unwrap_or(NaiveDate::MAX),
+    );
+    let (start_date, end_date) = (&dates::format_date(start), &dates::format_date(end));
+    let events = client.get_meal_plan_events(start_date, end_date).await?;
+    cache::remember_meal_plan_events(start_date, end_date, &events);
+    events
+        .into_iter()
+        .find(|event| event.id() == event_id)
+        .ok_or_else(|| CliError::NoMatch("meal plan event", event_id.to_string()))
+}
+
+fn event_date(event: &MealPlanEvent) -> Result<NaiveDate, CliError> {
+    NaiveDate::parse_from_str(event.date(), "%Y-%m-%d").map_err(|_| CliError::InvalidDate(event.date().to_string()))
+}
+
+/// Warn when events have notes, which `anylist_rs` can't save.
+fn warn_about_notes<'a>(events: impl IntoIterator<Item = &'a MealPlanEvent>, action: &str) {
+    if events
+        .into_iter()
+        .any(|event| event.details().is_some_and(|details| !details.is_empty()))
+    {
+        eprintln!("Warning: event notes can't be {} and are left out", action);
+    }
+}
+
+/// The label `--label` names, by name or ID.
+fn chosen_label<'a>(
+    matches: &ArgMatches,
+    calendar: Option<&'a MealCalendar>,
+) -> Result<Option<&'a MealLabel>, CliError> {
+    let Some(query) = matches.get_one::<String>("label") else {
+        return Ok(None);
+    };
+    let labels = calendar.map(|calendar| calendar.labels.as_slice()).unwrap_or_default();
+    resolve(query, labels, io::stdin().is_terminal()).map(Some)
+}
+
+/// The recipe `--recipe` names, by name or ID.
+async fn chosen_recipe(matches: &ArgMatches, client: &AnyListClient) -> Result<Option<Recipe>, CliError> {
+    let Some(query) = matches.get_one::<String>("recipe") else {
+        return Ok(None);
+    };
+    let recipes = client.get_recipes().await?;
+    cache::remember_recipes(&recipes);
+    resolve(query, &recipes, io::stdin().is_terminal()).map(|recipe| Some(recipe.clone()))
+}
 
 pub fn command() -> Command {
     Command::new("meal-plan")
@@ -12,152 +204,971 @@ pub fn command() -> Command {
         .subcommand(
             Command::new("list")
                 .about("List meal plan events in a date range")
-                .arg(
-                    Arg::new("start_date")
-                        .required(true)
-                        .help("Start date (YYYY-MM-DD)"),
+                .long_about(
+                    "List meal plan events in a date range, this week by default.\n\n\
+                     Dates can be YYYY-MM-DD, today, tomorrow, a weekday (fri, next fri,\n\
+                     last fri) or an offset (+3d, -1w, +1m). A single span also works:\n\
+                     this week, next month, 2026-10 or 2026-W43. Two-word spans need no\n\
+                     quotes, but a start and an end with spaces do:\n\n\
+                     \x20   anylist meal-plan list next week\n\
+                     \x20   anylist meal-plan list today +13d\n\
+                     \x20   anylist meal-plan list \"next mon\" \"next fri\"\n\n\
+                     A range starting with a negative offset goes after --:\n\n\
+                     \x20   anylist meal-plan list -- -1w today",
+                )
+                .arg(date_span_arg().default_value("this week")),
+        )
+        .subcommand(
+            Command::new("week")
+                .about("Show a week of meals as a calendar grid")
+                .long_about(
+                    "Show a week of meals as a calendar grid, with days as columns and meal\n\
+                     labels (Breakfast, Lunch, Dinner, ...) as rows. Shows this week by\n\
+                     default, or the week containing any date given: next week, next fri,\n\
+                     2026-W43. Terminals too narrow for the grid get a list by day.",
                 )
                 .arg(
-                    Arg::new("end_date")
-                        .required(true)
-                        .help("End date (YYYY-MM-DD)"),
+                    date_span_arg()
+                        .default_value("this week")
+                        .help("A day in the week, or a week such as 'next week' or 2026-W43"),
+                ),
+        )
+        .subcommand(
+            Command::new("month")
+                .about("Show a month of meals as calendar grids, one per week")
+                .arg(
+                    date_span_arg()
+                        .default_value("this month")
+                        .help("A day in the month, or a month such as 'next month' or 2026-10"),
                 ),
         )
         .subcommand(
             Command::new("add")
                 .about("Add a meal plan event")
-                .arg(Arg::new("calendar_id").required(true).help("Calendar ID"))
-                .arg(Arg::new("date").required(true).help("Date (YYYY-MM-DD)"))
                 .arg(
-                    Arg::new("recipe_id")
-                        .short('r')
-                        .long("recipe-id")
-                        .help("Recipe ID"),
+                    Arg::new("date")
+                        .required(true)
+                        .allow_hyphen_values(true)
+                        .help("Date (YYYY-MM-DD, today, fri, +3d, ...)"),
                 )
+                .arg(recipe_arg().help("Recipe name or ID"))
                 .arg(
                     Arg::new("title")
                         .short('t')
                         .long("title")
                         .help("Event title (for non-recipe events)"),
                 )
-                .arg(
-                    Arg::new("label_id")
-                        .short('l')
-                        .long("label-id")
-                        .help("Meal label ID (Breakfast, Lunch, Dinner, etc.)"),
-                ),
+                .arg(label_arg().help("Meal label name or ID (breakfast, lunch, dinner, ...)"))
+                .arg(calendar_arg()),
         )
         .subcommand(
             Command::new("update")
                 .about("Update a meal plan event")
-                .arg(Arg::new("calendar_id").required(true).help("Calendar ID"))
                 .arg(Arg::new("event_id").required(true).help("Event ID"))
-                .arg(Arg::new("date").required(true).help("New date (YYYY-MM-DD)"))
                 .arg(
-                    Arg::new("recipe_id")
-                        .short('r')
-                        .long("recipe-id")
-                        .help("New recipe ID"),
+                    Arg::new("date")
+                        .required(true)
+                        .allow_hyphen_values(true)
+                        .help("New date (YYYY-MM-DD, today, fri, +3d, ...)"),
                 )
+                .arg(recipe_arg().help("New recipe name or ID"))
                 .arg(
                     Arg::new("title")
                         .short('t')
                         .long("title")
                         .help("New event title"),
                 )
+                .arg(label_arg().help("New meal label name or ID"))
+                .arg(calendar_arg()),
+        )
+        .subcommand(
+            Command::new("copy")
+                .about("Copy the meals in a date range to another date")
+                .long_about(
+                    "Copy every meal plan event in a date range so that the range starts on\n\
+                     --to, keeping each meal's place in it. For example, to have last week's\n\
+                     meals again this week:\n\n\
+                     \x20   anylist meal-plan copy last week --to mon",
+                )
+                .arg(
+                    date_span_arg()
+                        .required(true)
+                        .help("Days to copy, such as 'last week' or 2026-W42"),
+                )
                 .arg(
-                    Arg::new("label_id")
+                    Arg::new("to")
+                        .long("to")
+                        .required(true)
+                        .allow_hyphen_values(true)
+                        .value_name("DATE")
+                        .help("Where the first day of the range lands"),
+                )
+                .arg(calendar_arg())
+                .arg(dry_run_arg()),
+        )
+        .subcommand(
+            Command::new("move")
+                .about("Move a meal plan event to another day")
+                .long_about(
+                    "Move a meal plan event to another day.\n\n\
+                     The event's notes can't be kept, so moving an event with notes asks\n\
+                     first, or needs --force when not running in a terminal.",
+                )
+                .arg(event_id_arg())
+                .arg(
+                    Arg::new("date")
+                        .required(true)
+                        .allow_hyphen_values(true)
+                        .help("New date (YYYY-MM-DD, today, fri, +3d, ...)"),
+                )
+                .arg(
+                    Arg::new("force")
+                        .short('f')
+                        .long("force")
+                        .action(ArgAction::SetTrue)
+                        .help("Move the event even though its notes are lost"),
+                )
+                .arg(calendar_arg()),
+        )
+        .subcommand(
+            Command::new("repeat")
+                .about("Repeat a meal plan event, such as taco Tuesday every week")
+                .arg(event_id_arg())
+                .arg(
+                    Arg::new("every")
+                        .long("every")
+                        .required(true)
+                        .value_name("INTERVAL")
+                        .help("day, week, fortnight, month, or a count such as 2w or 10d"),
+                )
+                .arg(
+                    Arg::new("until")
+                        .long("until")
+                        .required(true)
+                        .allow_hyphen_values(true)
+                        .value_name("DATE")
+                        .help("Last day to repeat on"),
+                )
+                .arg(calendar_arg())
+                .arg(dry_run_arg()),
+        )
+        .subcommand(
+            Command::new("suggest")
+                .about("Plan meals from your recipes")
+                .long_about(
+                    "Pick a recipe for each day, preview the plan, and add it once you\n\
+                     confirm.\n\n\
+                     Recipes are chosen at random, better-rated ones more often, among those\n\
+                     that pass the filters and weren't planned in the last --no-repeat weeks.\n\
+                     Days that already have a meal (with --label, that meal) are left alone.\n\
+                     --seed makes the choice repeatable:\n\n\
+                     \x20   anylist meal-plan suggest --from mon --days 7 --label dinner \\\n\
+                     \x20       --weekday-max-time 45 --without meat --seed 7",
+                )
+                .arg(
+                    Arg::new("from")
+                        .long("from")
+                        .value_name("DATE")
+                        .allow_hyphen_values(true)
+                        .default_value("tomorrow")
+                        .help("First day to plan"),
+                )
+                .arg(
+                    Arg::new("days")
+                        .long("days")
+                        .value_name("N")
+                        .value_parser(value_parser!(u32).range(1..=62))
+                        .default_value("7")
+                        .help("Number of days to plan"),
+                )
+                .arg(label_arg().help("Meal label for the new events, such as dinner"))
+                .arg(
+                    Arg::new("ingredient")
+                        .short('i')
+                        .long("ingredient")
+                        .value_name("INGREDIENT")
+                        .action(ArgAction::Append)
+                        .help("Only recipes using this ingredient (repeatable)"),
+                )
+                .arg(
+                    Arg::new("without")
+                        .short('x')
+                        .long("without")
+                        .value_name("INGREDIENT")
+                        .action(ArgAction::Append)
+                        .help("Skip recipes using this ingredient or group (repeatable)"),
+                )
+                .arg(
+                    Arg::new("min_rating")
+                        .long("min-rating")
+                        .value_name("STARS")
+                        .value_parser(value_parser!(i32).range(1..=5))
+                        .help("Only recipes rated at least this many stars"),
+                )
+                .arg(
+                    Arg::new("max_time")
+                        .long("max-time")
+                        .value_name("MINUTES")
+                        .value_parser(value_parser!(i32).range(0..))
+                        .help("Only recipes whose prep and cook time add up to at most this"),
+                )
+                .arg(
+                    Arg::new("weekday_max_time")
+                        .long("weekday-max-time")
+                        .value_name("MINUTES")
+                        .value_parser(value_parser!(i32).range(0..))
+                        .help("Like --max-time, for Monday to Friday only"),
+                )
+                .arg(
+                    Arg::new("no_repeat")
+                        .long("no-repeat")
+                        .value_name("WEEKS")
+                        .value_parser(value_parser!(u32).range(0..=52))
+                        .default_value("2")
+                        .help("Skip recipes planned in this many weeks before the first day"),
+                )
+                .arg(
+                    Arg::new("seed")
+                        .long("seed")
+                        .value_name("N")
+                        .value_parser(value_parser!(u64))
+                        .help("Seed for the random choice, for a repeatable plan"),
+                )
+                .arg(
+                    Arg::new("yes")
+                        .short('y')
+                        .long("yes")
+                        .action(ArgAction::SetTrue)
+                        .help("Add the suggested meals without asking"),
+                )
+                .arg(calendar_arg())
+                .arg(dry_run_arg()),
+        )
+        .subcommand(
+            Command::new("calendars")
+                .about("List meal-planning calendars"),
+        )
+        .subcommand(
+            Command::new("labels")
+                .about("List meal labels (Breakfast, Lunch, Dinner, ...)"),
+        )
+        .subcommand(
+            Command::new("shop")
+                .about("Add the ingredients for planned meals to a shopping list")
+                .long_about(
+                    "Add the ingredients for every recipe planned in a date range to a\n\
+                     shopping list.\n\n\
+                     Ingredients used by several recipes are combined into one item with\n\
+                     the quantities added up (\"200 g\" and \"1 kg\" become \"1.2 kg\"), and\n\
+                     each item's details say which recipes it is for. The combined list is\n\
+                     shown first: in a terminal you can deselect what you already have.\n\
+                     Without a terminal, pass --yes to add them all.\n\
+                     Ingredients already on the list are merged into the existing items.",
+                )
+                .arg(date_span_arg().required(true))
+                .arg(
+                    Arg::new("list")
                         .short('l')
-                        .long("label-id")
-                        .help("New meal label ID"),
-                ),
+                        .long("list")
+                        .required(true)
+                        .value_name("LIST_NAME")
+                        .help("List to add the ingredients to"),
+                )
+                .arg(
+                    Arg::new("all")
+                        .short('y')
+                        .long("all")
+                        .visible_alias("yes")
+                        .action(ArgAction::SetTrue)
+                        .help("Add every ingredient without asking"),
+                )
+                .arg(
+                    Arg::new("dry_run")
+                        .long("dry-run")
+                        .action(ArgAction::SetTrue)
+                        .help("Show the combined ingredients without adding them"),
+                )
+                .args(MergeMode::args()),
         )
         .subcommand(
             Command::new("delete")
                 .about("Delete a meal plan event")
-                .arg(Arg::new("calendar_id").required(true).help("Calendar ID"))
-                .arg(Arg::new("event_id").required(true).help("Event ID")),
+                .arg(Arg::new("event_id").required(true).help("Event ID"))
+                .arg(calendar_arg()),
         )
 }
 
-pub async fn exec_command(matches: &ArgMatches) -> Result<(), CliError> {
-    let tokens = read_tokens()?;
-    let client = AnyListClient::from_tokens(tokens)?;
+/// The days `meal-plan week` or `month` shows: the week or month around
+/// the first date given.
+fn view_span(view: &str, matches: &ArgMatches) -> Result<(String, String), CliError> {
+    let (day, _) = date_range(matches)?;
+    let (first, last) = if view == "week" {
+        let monday = dates::week_start(day);
+        (monday, monday.checked_add_days(Days::new(6)))
+    } else {
+        let first = dates::month_start(day);
+        (first, dates::month_end(first))
+    };
+    let last = last.ok_or_else(|| CliError::InvalidDate(dates::format_date(day)))?;
+    Ok((dates::format_date(first), dates::format_date(last)))
+}
+
+fn render_events(
+    start_date: &str,
+    end_date: &str,
+    events: &[MealPlanEvent],
+    format: OutputFormat,
+) -> Result<(), CliError> {
+    output::render(
+        format,
+        &MealPlanEventsRecord {
+            start_date: start_date.to_string(),
+            end_date: end_date.to_string(),
+            events: events.iter().map(MealPlanEventRecord::from).collect(),
+        },
+    )
+}
+
+fn render_calendar(view: &str, calendar: Option<MealCalendar>, format: OutputFormat) -> Result<(), CliError> {
+    if view == "calendars" {
+        output::render(format, &MealCalendarsRecord::from(calendar.as_ref()))
+    } else {
+        let calendar = calendar.ok_or(CliError::NoMealCalendar)?;
+        output::render(format, &MealLabelsRecord::from(&calendar))
+    }
+}
+
+/// Show the meal plan from the local snapshot, without loading credentials.
+fn exec_offline(matches: &ArgMatches, format: OutputFormat) -> Result<(), CliError> {
+    let snapshot = Snapshot::load()?;
+    let cached_plan = || {
+        let saved_at = snapshot
+            .meal_plan_saved_at
+            .as_deref()
+            .ok_or_else(|| CliError::NotCached("your meal plan".to_string()))?;
+        eprintln!("Offline: showing meal plan cached at {}", saved_at);
+        Ok::<_, CliError>(())
+    };
+
+    match matches.subcommand() {
+        Some(("list", sub_matches)) => {
+            let (start_date, end_date) = date_span(sub_matches)?;
+            cached_plan()?;
+            let events = snapshot.meal_plan_events_between(&start_date, &end_date);
+            render_events(&start_date, &end_date, &events, format)?;
+        }
+        Some((view @ ("week" | "month"), sub_matches)) => {
+            let (start_date, end_date) = view_span(view, sub_matches)?;
+            cached_plan()?;
+            let events = snapshot.meal_plan_events_between(&start_date, &end_date);
+            output::render(
+                format,
+                &MealPlanGridRecord::new(
+                    &start_date,
+                    &end_date,
+                    &events,
+                    &snapshot.recipes,
+                    snapshot.meal_calendar.as_ref(),
+                ),
+            )?;
+        }
+        Some((view @ ("calendars" | "labels"), _)) => {
+            let calendar = snapshot
+                .meal_calendar
+                .clone()
+                .ok_or_else(|| CliError::NotCached("your meal-planning calendar".to_string()))?;
+            render_calendar(view, Some(calendar), format)?;
+        }
+        _ => unreachable!("only views are allowed offline"),
+    }
+
+    Ok(())
+}
+
+pub async fn exec_command(matches: &ArgMatches, format: OutputFormat) -> Result<(), CliError> {
+    if matches.get_flag("offline") {
+        return exec_offline(matches, format);
+    }
+
+    let client = Session::open()?;
 
     match matches.subcommand() {
         Some(("list", sub_matches)) => {
-            let start_date = sub_matches.get_one::<String>("start_date").unwrap();
-            let end_date = sub_matches.get_one::<String>("end_date").unwrap();
+            let (start_date, end_date) = date_span(sub_matches)?;
+            let (start_date, end_date) = (&start_date, &end_date);
 
             let events = client.get_meal_plan_events(start_date, end_date).await?;
+            cache::remember_meal_plan_events(start_date, end_date, &events);
+            render_events(start_date, end_date, &events, format)?;
+        }
+        Some((view @ ("week" | "month"), sub_matches)) => {
+            let (start_date, end_date) = view_span(view, sub_matches)?;
+            let (start_date, end_date) = (&start_date, &end_date);
 
-            if events.is_empty() {
-                println!("No meal plan events found in this date range.");
-            } else {
-                println!("Meal plan events ({} to {}):", start_date, end_date);
-                println!();
-                for event in events {
-                    println!("Date: {}", event.date());
-                    if let Some(title) = &event.title() {
-                        println!("  Title: {}", title);
-                    }
-                    if let Some(recipe_id) = &event.recipe_id() {
-                        println!("  Recipe ID: {}", recipe_id);
-                    }
-                    if let Some(label_id) = &event.label_id() {
-                        println!("  Label ID: {}", label_id);
-                    }
-                    if let Some(details) = &event.details() {
-                        println!("  Details: {}", details);
-                    }
-                    println!("  Event ID: {}", event.id());
-                    println!();
-                }
-            }
+            let events = client.get_meal_plan_events(start_date, end_date).await?;
+            cache::remember_meal_plan_events(start_date, end_date, &events);
+            let recipes = client.get_recipes().await?;
+            cache::remember_recipes(&recipes);
+            let calendar = load_calendar(&client).await?;
+
+            output::render(
+                format,
+                &MealPlanGridRecord::new(start_date, end_date, &events, &recipes, calendar.as_ref()),
+            )?;
         }
         Some(("add", sub_matches)) => {
-            let calendar_id = sub_matches.get_one::<String>("calendar_id").unwrap();
-            let date = sub_matches.get_one::<String>("date").unwrap();
-            let recipe_id = sub_matches.get_one::<String>("recipe_id").map(|s| s.as_str());
+            let date = &date_arg(sub_matches, "date")?;
             let title = sub_matches.get_one::<String>("title").map(|s| s.as_str());
-            let label_id = sub_matches.get_one::<String>("label_id").map(|s| s.as_str());
+            let calendar = load_calendar(&client).await?;
+            let calendar_id = calendar_id(sub_matches, calendar.as_ref())?;
+            let label = chosen_label(sub_matches, calendar.as_ref())?;
+            let recipe = chosen_recipe(sub_matches, &client).await?;
 
             let event = client
-                .create_meal_plan_event(calendar_id, date, recipe_id, title, label_id)
+                .create_meal_plan_event(
+                    &calendar_id,
+                    date,
+                    recipe.as_ref().map(|recipe| recipe.id()),
+                    title,
+                    label.map(|label| label.id.as_str()),
+                )
                 .await?;
 
-            println!("Created meal plan event for {}", event.date());
-            if let Some(t) = &event.title() {
-                println!("  Title: {}", t);
+            let mut message = format!("Created meal plan event for {}", event.date());
+            if let Some(t) = event.title() {
+                message.push_str(&format!("\n  Title: {}", t));
+            }
+            if let Some(recipe) = &recipe {
+                message.push_str(&format!("\n  Recipe: {}", recipe.name()));
             }
-            if let Some(r) = &event.recipe_id() {
-                println!("  Recipe ID: {}", r);
+            if let Some(label) = label {
+                message.push_str(&format!("\n  Label: {}", label.name));
             }
+            output::render(
+                format,
+                &ActionRecord::with_record(
+                    "meal_plan_event.created",
+                    message,
+                    MealPlanEventRecord::from(&event),
+                ),
+            )?;
         }
         Some(("update", sub_matches)) => {
-            let calendar_id = sub_matches.get_one::<String>("calendar_id").unwrap();
             let event_id = sub_matches.get_one::<String>("event_id").unwrap();
-            let date = sub_matches.get_one::<String>("date").unwrap();
-            let recipe_id = sub_matches.get_one::<String>("recipe_id").map(|s| s.as_str());
+            let date = &date_arg(sub_matches, "date")?;
             let title = sub_matches.get_one::<String>("title").map(|s| s.as_str());
-            let label_id = sub_matches.get_one::<String>("label_id").map(|s| s.as_str());
+            let calendar = load_calendar(&client).await?;
+            let calendar_id = calendar_id(sub_matches, calendar.as_ref())?;
+            let label = chosen_label(sub_matches, calendar.as_ref())?;
+            let recipe = chosen_recipe(sub_matches, &client).await?;
 
             client
-                .update_meal_plan_event(calendar_id, event_id, date, recipe_id, title, label_id)
+                .update_meal_plan_event(
+                    &calendar_id,
+                    event_id,
+                    date,
+                    recipe.as_ref().map(|recipe| recipe.id()),
+                    title,
+                    label.map(|label| label.id.as_str()),
+                )
                 .await?;
 
-            println!("Updated meal plan event for {}", date);
+            output::render(
+                format,
+                &ActionRecord::new(
+                    "meal_plan_event.updated",
+                    format!("Updated meal plan event for {}", date),
+                ),
+            )?;
         }
         Some(("delete", sub_matches)) => {
-            let calendar_id = sub_matches.get_one::<String>("calendar_id").unwrap();
             let event_id = sub_matches.get_one::<String>("event_id").unwrap();
+            let calendar_id = target_calendar_id(sub_matches, &client).await?;
+
+            client.delete_meal_plan_event(&calendar_id, event_id).await?;
 
-            client.delete_meal_plan_event(calendar_id, event_id).await?;
+            output::render(
+                format,
+                &ActionRecord::new("meal_plan_event.deleted", "Deleted meal plan event"),
+            )?;
+        }
+        Some(("copy", sub_matches)) => {
+            let (first, last) = date_range(sub_matches)?;
+            let to = dates::parse_date(sub_matches.get_one::<String>("to").unwrap(), dates::today())?;
+            let (start_date, end_date) = (&dates::format_date(first), &dates::format_date(last));
+
+            let events = client.get_meal_plan_events(start_date, end_date).await?;
+            cache::remember_meal_plan_events(start_date, end_date, &events);
+            if events.is_empty() {
+                let message = format!("No meal plan events from {} to {}", start_date, end_date);
+                return output::render(format, &ActionRecord::new("meal_plan_event.none_created", message));
+            }
 
-            println!("Deleted meal plan event");
+            let shift = to.signed_duration_since(first);
+            let mut copies = Vec::new();
+            for event in &events {
+                let date = event_date(event)?;
+                let date = date
+                    .checked_add_signed(shift)
+                    .ok_or_else(|| CliError::InvalidDate(to.to_string()))?;
+                copies.push((event, date));
+            }
+            copies.sort_by_key(|(_, date)| *date);
+            create_copies(&client, sub_matches, &copies, format).await?;
+        }
+        Some(("move", sub_matches)) => {
+            let event_id = sub_matches.get_one::<String>("event_id").unwrap();
+            let date = &date_arg(sub_matches, "date")?;
+            let event = find_event(&client, event_id).await?;
+            let calendar_id = target_calendar_id(sub_matches, &client).await?;
+
+            if event.details().is_some_and(|details| !details.is_empty()) && !sub_matches.get_flag("force") {
+                if !io::stdin().is_terminal() {
+                    return Err(CliError::InvalidInput(
+                        "the event has notes, which can't be moved with it; pass --force to move it anyway".to_string(),
+                    ));
+                }
+                let prompt = "The event's notes can't be moved with it and will be lost. Move it anyway?";
+                if !Confirm::new(prompt).with_default(false).prompt()? {
+                    return Err(CliError::PromptCancelled);
+                }
+            }
+            client
+                .update_meal_plan_event(
+                    &calendar_id,
+                    event_id,
+                    date,
+                    event.recipe_id(),
+                    event.title(),
+                    event.label_id(),
+                )
+                .await?;
+
+            output::render(
+                format,
+                &ActionRecord::new(
+                    "meal_plan_event.updated",
+                    format!("Moved meal plan event from {} to {}", event.date(), date),
+                ),
+            )?;
+        }
+        Some(("repeat", sub_matches)) => {
+            let event_id = sub_matches.get_one::<String>("event_id").unwrap();
+            let every_text = sub_matches.get_one::<String>("every").unwrap();
+            let every = Interval::parse(every_text).filter(|every| !every.is_empty()).ok_or_else(|| {
+                CliError::InvalidInput(format!(
+                    "'{}' is not an interval; use day, week, fortnight, month, or a count such as 2w",
+                    every_text
+                ))
+            })?;
+            let until = dates::parse_date(sub_matches.get_one::<String>("until").unwrap(), dates::today())?;
+            let event = find_event(&client, event_id).await?;
+            let start = event_date(&event)?;
+
+            let repeats: Vec<NaiveDate> = (1..)
+                .map_while(|times| every.forward(start, times))
+                .take_while(|date| *date <= until)
+                .take(MAX_REPEATS + 1)
+                .collect();
+            if repeats.is_empty() {
+                return Err(CliError::InvalidInput(format!(
+                    "--until {} is before the first repeat of the event on {}",
+                    dates::format_date(until),
+                    event.date()
+                )));
+            }
+            if repeats.len() > MAX_REPEATS {
+                return Err(CliError::InvalidInput(format!(
+                    "that would add more than {} events; choose a nearer --until",
+                    MAX_REPEATS
+                )));
+            }
+
+            let copies: Vec<(&MealPlanEvent, NaiveDate)> = repeats.into_iter().map(|date| (&event, date)).collect();
+            create_copies(&client, sub_matches, &copies, format).await?;
+        }
+        Some(("suggest", sub_matches)) => {
+            let from = dates::parse_date(sub_matches.get_one::<String>("from").unwrap(), dates::today())?;
+            let day_count = *sub_matches.get_one::<u32>("days").unwrap();
+            let out_of_range = || CliError::InvalidInput(format!("{} is too close to the end of the calendar", from));
+            let days: Vec<NaiveDate> = (0..day_count)
+                .map(|offset| from.checked_add_days(Days::new(offset.into())).ok_or_else(out_of_range))
+                .collect::<Result<_, _>>()?;
+            let last = *days.last().expect("--days is at least 1");
+            let no_repeat_weeks = *sub_matches.get_one::<u32>("no_repeat").unwrap();
+            let since = from.checked_sub_days(Days::new(7 * u64::from(no_repeat_weeks))).ok_or_else(|| {
+                CliError::InvalidInput("--no-repeat reaches before the start of the calendar".to_string())
+            })?;
+
+            let calendar = load_calendar(&client).await?;
+            let label = chosen_label(sub_matches, calendar.as_ref())?;
+            let recipes = client.get_recipes().await?;
+            cache::remember_recipes(&recipes);
+            let (since_date, end_date) = (&dates::format_date(since), &dates::format_date(last));
+            let events = client.get_meal_plan_events(since_date, end_date).await?;
+            cache::remember_meal_plan_events(since_date, end_date, &events);
+
+            // Days that already have this meal keep it
+            let planned_days: HashSet<&str> = events
+                .iter()
+                .filter(|event| label.is_none_or(|label| event.label_id() == Some(label.id.as_str())))
+                .map(|event| event.date())
+                .collect();
+            let open_days: Vec<NaiveDate> = days
+                .into_iter()
+                .filter(|day| !planned_days.contains(dates::format_date(*day).as_str()))
+                .collect();
+
+            let strings = |id: &str| -> Vec<String> {
+                sub_matches.get_many::<String>(id).unwrap_or_default().cloned().collect()
+            };
+            let constraints = MealConstraints {
+                query: RecipeQuery {
+                    include: strings("ingredient"),
+                    exclude: strings("without"),
+                    min_rating: sub_matches.get_one::<i32>("min_rating").copied(),
+                    max_minutes: sub_matches.get_one::<i32>("max_time").copied(),
+                    ..RecipeQuery::default()
+                },
+                weekday_max_minutes: sub_matches.get_one::<i32>("weekday_max_time").copied(),
+                recent: events
+                    .iter()
+                    .filter_map(|event| event.recipe_id())
+                    .map(str::to_string)
+                    .collect(),
+            };
+            let mut rng = match sub_matches.get_one::<u64>("seed") {
+                Some(&seed) => StdRng::seed_from_u64(seed),
+                None => StdRng::from_entropy(),
+            };
+
+            let mut new_events = Vec::new();
+            let mut unfilled = Vec::new();
+            for (date, recipe) in suggest(&recipes, &open_days, &constraints, &mut rng) {
+                let Some(recipe) = recipe else {
+                    unfilled.push(dates::format_date(date));
+                    continue;
+                };
+                new_events.push(NewEvent {
+                    meal: PlannedMealRecord {
+                        event_id: String::new(),
+                        date: dates::format_date(date),
+                        label: label.map(|label| label.name.clone()),
+                        name: recipe.name().to_string(),
+                        recipe_id: Some(recipe.id().to_string()),
+                    },
+                    title: None,
+                    label_id: label.map(|label| label.id.as_str()),
+                });
+            }
+            if !unfilled.is_empty() && !new_events.is_empty() {
+                eprintln!("Warning: no recipe left that fits {}", unfilled.join(", "));
+            }
+            if new_events.is_empty() {
+                let message = if open_days.is_empty() {
+                    format!("Every day from {} to {} is already planned", dates::format_date(from), end_date)
+                } else {
+                    "No recipes fit; try fewer filters or a shorter --no-repeat".to_string()
+                };
+                return output::render(format, &ActionRecord::new("meal_plan_event.none_created", message));
+            }
+
+            if sub_matches.get_flag("dry_run") {
+                return render_planned(&new_events, format);
+            }
+            if !sub_matches.get_flag("yes") {
+                if !io::stdin().is_terminal() {
+                    return Err(CliError::InvalidInput(
+                        "refusing to add the suggested meals without confirmation; pass --yes".to_string(),
+                    ));
+                }
+                eprintln!("{}", planned_message(&new_events));
+                if !Confirm::new("Add these meals?").with_default(true).prompt()? {
+                    return Err(CliError::PromptCancelled);
+                }
+            }
+
+            let calendar_id = calendar_id(sub_matches, calendar.as_ref())?;
+            create_events(&client, &calendar_id, &new_events, format).await?;
+        }
+        Some((view @ ("calendars" | "labels"), _)) => {
+            render_calendar(view, load_calendar(&client).await?, format)?;
+        }
+        Some(("shop", sub_matches)) => {
+            let (start_date, end_date) = date_span(sub_matches)?;
+            let (start_date, end_date) = (&start_date, &end_date);
+            let list_name = sub_matches.get_one::<String>("list").unwrap();
+
+            let events = client.get_meal_plan_events(start_date, end_date).await?;
+            cache::remember_meal_plan_events(start_date, end_date, &events);
+            let recipes = client.get_recipes().await?;
+            cache::remember_recipes(&recipes);
+
+            // A recipe planned twice is shopped for twice
+            let mut planned: Vec<&Recipe> = Vec::new();
+            for recipe_id in events.iter().filter_map(|event| event.recipe_id()) {
+                match recipes.iter().find(|recipe| recipe.id() == recipe_id) {
+                    Some(recipe) => planned.push(recipe),
+                    None => eprintln!("Warning: skipping planned recipe {} (not found)", recipe_id),
+                }
+            }
+
+            let items = aggregate_ingredients(planned.iter().copied());
+            if items.is_empty() {
+                let message = format!("No recipes planned from {} to {}", start_date, end_date);
+                return output::render(format, &ActionRecord::new("item.none_added", message));
+            }
+
+            let mut message = format!(
+                "Would add ingredients for {} meal(s) from {} to {} to list '{}':",
+                planned.len(),
+                start_date,
+                end_date,
+                list_name
+            );
+            for item in &items {
+                message.push_str(&format!("\n  {}", describe(item)));
+            }
+            if sub_matches.get_flag("dry_run") {
+                let records: Vec<PlannedItemRecord> = items
+                    .iter()
+                    .map(|item| PlannedItemRecord {
+                        name: item.name.clone(),
+                        quantity: item.quantity.clone(),
+                        details: item.details.clone(),
+                    })
+                    .collect();
+                return output::render(format, &ActionRecord::with_record("item.add_planned", message, records));
+            }
+            if !sub_matches.get_flag("all") && !io::stdin().is_terminal() {
+                eprintln!("{}", message);
+                return Err(CliError::InvalidInput(
+                    "refusing to add ingredients without confirmation; pass --yes, or --dry-run to only show them"
+                        .to_string(),
+                ));
+            }
+
+            let items = if sub_matches.get_flag("all") {
+                items
+            } else {
+                let prompt = format!(
+                    "Ingredients for {} meal(s) (deselect what you already have):",
+                    planned.len()
+                );
+                choose_items(items, &prompt)?
+            };
+            if items.is_empty() {
+                let message = format!("Nothing to add from {} to {}", start_date, end_date);
+                return output::render(format, &ActionRecord::new("item.none_added", message));
+            }
+
+            let list = client.get_list_by_name(list_name).await?;
+            let record = add_items(&client, &list, items, MergeMode::from_matches(sub_matches)).await;
+            output::render(format, &record)?;
+            if record.failed > 0 {
+                return Err(CliError::PartialFailure(record.failed, record.results.len()));
+            }
         }
         _ => unreachable!("subcommand_required prevents this"),
     }
 
     Ok(())
 }
+
+/// An event to create, described the way it will be listed.
+struct NewEvent<'a> {
+    meal: PlannedMealRecord,
+    title: Option<&'a str>,
+    label_id: Option<&'a str>,
+}
+
+/// Create `copies` of events on new dates, or with `--dry-run` show what
+/// would be created.
+async fn create_copies(
+    client: &AnyListClient,
+    matches: &ArgMatches,
+    copies: &[(&MealPlanEvent, NaiveDate)],
+    format: OutputFormat,
+) -> Result<(), CliError> {
+    let calendar = load_calendar(client).await?;
+    let recipes = client.get_recipes().await?;
+    cache::remember_recipes(&recipes);
+    let new_events: Vec<NewEvent> = copies
+        .iter()
+        .map(|(event, date)| NewEvent {
+            meal: PlannedMealRecord {
+                event_id: String::new(),
+                date: dates::format_date(*date),
+                ..PlannedMealRecord::new(event, &recipes, calendar.as_ref())
+            },
+            title: event.title(),
+            label_id: event.label_id(),
+        })
+        .collect();
+
+    if matches.get_flag("dry_run") {
+        return render_planned(&new_events, format);
+    }
+
+    warn_about_notes(copies.iter().map(|(event, _)| *event), "copied");
+    let calendar_id = calendar_id(matches, calendar.as_ref())?;
+    create_events(client, &calendar_id, &new_events, format).await
+}
+
+/// "Would create 3 meal plan event(s):" and the events.
+fn planned_message(new_events: &[NewEvent]) -> String {
+    let mut message = format!("Would create {} meal plan event(s):", new_events.len());
+    for NewEvent { meal, .. } in new_events {
+        message.push_str(&format!("\n  {}  {}", meal.date, describe_meal(meal)));
+    }
+    message
+}
+
+fn render_planned(new_events: &[NewEvent], format: OutputFormat) -> Result<(), CliError> {
+    let meals: Vec<PlannedMealRecord> = new_events.iter().map(|new_event| new_event.meal.clone()).collect();
+    output::render(
+        format,
+        &ActionRecord::with_record("meal_plan_event.create_planned", planned_message(new_events), meals),
+    )
+}
+
+/// Create the events, warning about the ones that fail.
+async fn create_events(
+    client: &AnyListClient,
+    calendar_id: &str,
+    new_events: &[NewEvent<'_>],
+    format: OutputFormat,
+) -> Result<(), CliError> {
+    let mut created = Vec::new();
+    let mut lines = String::new();
+    for NewEvent { meal, title, label_id } in new_events {
+        let result = client
+            .create_meal_plan_event(calendar_id, &meal.date, meal.recipe_id.as_deref(), *title, *label_id)
+            .await;
+        match result {
+            Ok(event) => {
+                lines.push_str(&format!("\n  {}  {}", meal.date, describe_meal(meal)));
+                created.push(MealPlanEventRecord::from(&event));
+            }
+            Err(err) => eprintln!("Warning: could not add {} on {}: {}", meal.name, meal.date, err),
+        }
+    }
+
+    let failed = new_events.len() - created.len();
+    let message = format!("Created {} meal plan event(s):{}", created.len(), lines);
+    output::render(
+        format,
+        &ActionRecord::with_record("meal_plan_event.created", message, created),
+    )?;
+    if failed > 0 {
+        return Err(CliError::PartialFailure(failed, new_events.len()));
+    }
+    Ok(())
+}
+
+/// "Dinner: Chili"
+fn describe_meal(meal: &PlannedMealRecord) -> String {
+    match &meal.label {
+        Some(label) => format!("{}: {}", label, meal.name),
+        None => meal.name.clone(),
+    }
+}
+
+/// "flour (1.2 kg) - for Bread, Pancakes"
+fn describe(item: &NewItem) -> String {
+    let mut line = item.name.clone();
+    if let Some(quantity) = &item.quantity {
+        line.push_str(&format!(" ({})", quantity));
+    }
+    if let Some(details) = &item.details {
+        line.push_str(&format!(" - {}", details));
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parse a full command line and return the `meal-plan` subcommand's
+    /// name and matches, panicking with clap's message if it doesn't parse.
+    fn parse(line: &[&str]) -> (String, ArgMatches) {
+        let matches = crate::cli()
+            .try_get_matches_from(["anylist"].iter().chain(line))
+            .unwrap_or_else(|err| panic!("{:?} didn't parse: {}", line, err));
+        let (_, meal_plan) = matches.subcommand().expect("a subcommand");
+        let (name, sub_matches) = meal_plan.subcommand().expect("a meal-plan subcommand");
+        (name.to_string(), sub_matches.clone())
+    }
+
+    fn dates_of(matches: &ArgMatches) -> Vec<&str> {
+        matches.get_many::<String>("dates").unwrap().map(String::as_str).collect()
+    }
+
+    #[test]
+    fn dates_stop_before_flags() {
+        let (_, list) = parse(&["meal-plan", "list", "this", "week", "-o", "json"]);
+        assert_eq!(dates_of(&list), ["this", "week"]);
+
+        let (_, list) = parse(&["meal-plan", "list", "this", "week", "--offline"]);
+        assert_eq!(dates_of(&list), ["this", "week"]);
+        assert!(list.get_flag("offline"));
+
+        let (_, week) = parse(&["meal-plan", "week", "next", "week", "--offline"]);
+        assert_eq!(dates_of(&week), ["next", "week"]);
+
+        let (_, month) = parse(&["meal-plan", "month", "2026-10", "-o", "yaml"]);
+        assert_eq!(dates_of(&month), ["2026-10"]);
+    }
+
+    #[test]
+    fn parses_shop_with_a_list() {
+        // The README's example
+        let (name, shop) = parse(&["meal-plan", "shop", "2024-01-15", "2024-01-21", "--list", "Groceries"]);
+        assert_eq!(name, "shop");
+        assert_eq!(dates_of(&shop), ["2024-01-15", "2024-01-21"]);
+        assert_eq!(shop.get_one::<String>("list").unwrap(), "Groceries");
+        assert!(!shop.get_flag("all"));
+
+        let (_, shop) = parse(&["meal-plan", "shop", "next", "week", "-l", "Groceries", "--yes", "--dry-run"]);
+        assert_eq!(dates_of(&shop), ["next", "week"]);
+        assert!(shop.get_flag("all"));
+        assert!(shop.get_flag("dry_run"));
+    }
+
+    #[test]
+    fn parses_copy_to_a_date() {
+        let (_, copy) = parse(&["meal-plan", "copy", "last", "week", "--to", "mon", "--dry-run"]);
+        assert_eq!(dates_of(&copy), ["last", "week"]);
+        assert_eq!(copy.get_one::<String>("to").unwrap(), "mon");
+        assert!(copy.get_flag("dry_run"));
+    }
+
+    #[test]
+    fn parses_move_and_repeat() {
+        let (_, moved) = parse(&["meal-plan", "move", "event-1", "-1d", "--force"]);
+        assert_eq!(moved.get_one::<String>("date").unwrap(), "-1d");
+        assert!(moved.get_flag("force"));
+
+        let (_, repeat) = parse(&["meal-plan", "repeat", "event-1", "--every", "week", "--until", "+2m"]);
+        assert_eq!(repeat.get_one::<String>("until").unwrap(), "+2m");
+    }
+
+    #[test]
+    fn takes_quoted_start_and_end() {
+        let (_, list) = parse(&["meal-plan", "list", "next mon", "next fri"]);
+        let words: Vec<String> = dates_of(&list).iter().map(|word| word.to_string()).collect();
+        let today = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+        assert_eq!(
+            dates::parse_span(&words, today).unwrap(),
+            (
+                NaiveDate::from_ymd_opt(2026, 10, 19).unwrap(),
+                NaiveDate::from_ymd_opt(2026, 10, 23).unwrap()
+            )
+        );
+
+        assert!(crate::cli()
+            .try_get_matches_from(["anylist", "meal-plan", "list", "next", "mon", "next", "fri"])
+            .is_err());
+    }
+}
@@ -2,7 +2,11 @@ pub mod categories;
 pub mod items;
 pub mod list;
 pub mod login;
+pub mod logout;
 pub mod meal_plans;
+pub mod profile;
 pub mod recipes;
+pub mod shop;
 pub mod stores;
 pub mod tail;
+pub mod whoami;
@@ -0,0 +1,105 @@
+use clap::{Arg, ArgMatches, Command};
+
+use crate::auth::{active_profile, load_config, lock_config, profile_store, write_config};
+use crate::cache;
+use crate::error::CliError;
+use crate::output::{self, ActionRecord, OutputFormat, ProfileRecord, ProfilesRecord};
+
+pub fn command() -> Command {
+    Command::new("profile")
+        .about("Manage saved account profiles")
+        .long_about(
+            "Manage saved account profiles.\n\n\
+             Log in to another account with 'anylist login --profile NAME', then pick\n\
+             the account per command with --profile or ANYLIST_PROFILE, or switch the\n\
+             default with 'anylist profile use NAME'.",
+        )
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(Command::new("list").about("List saved profiles"))
+        .subcommand(
+            Command::new("use")
+                .about("Make a profile the default")
+                .arg(Arg::new("name").required(true).help("Profile name")),
+        )
+        .subcommand(
+            Command::new("remove")
+                .about("Remove a profile and its saved tokens")
+                .arg(Arg::new("name").required(true).help("Profile name")),
+        )
+}
+
+pub async fn exec_command(matches: &ArgMatches, format: OutputFormat) -> Result<(), CliError> {
+    // `use` and `remove` rewrite the config from what they read here
+    let _lock = match matches.subcommand_name() {
+        Some("use" | "remove") => Some(lock_config()?),
+        _ => None,
+    };
+    let mut config = load_config()?;
+
+    match matches.subcommand() {
+        Some(("list", _)) => {
+            let active = active_profile(&config);
+            let profiles = config["profiles"]
+                .as_object()
+                .map(|profiles| {
+                    profiles
+                        .iter()
+                        .map(|(name, profile)| ProfileRecord {
+                            name: name.clone(),
+                            user_id: profile["user_id"].as_str().map(str::to_string),
+                            is_premium: profile["is_premium"].as_str() == Some("true"),
+                            active: *name == active,
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            output::render(format, &ProfilesRecord { profiles })?;
+        }
+        Some(("use", sub_matches)) => {
+            let name = sub_matches.get_one::<String>("name").unwrap();
+            if config["profiles"].get(name).is_none() {
+                return Err(CliError::ProfileNotFound(name.to_string()));
+            }
+
+            config["current_profile"] = serde_json::Value::String(name.to_string());
+            write_config(&config)?;
+
+            output::render(
+                format,
+                &ActionRecord::new("profile.selected", format!("Now using profile '{}'", name)),
+            )?;
+        }
+        Some(("remove", sub_matches)) => {
+            let name = sub_matches.get_one::<String>("name").unwrap();
+            let Some(profile) = config["profiles"].get(name.as_str()) else {
+                return Err(CliError::ProfileNotFound(name.to_string()));
+            };
+            profile_store(profile)?.delete(name)?;
+
+            // Re-read: the file store rewrites the config when deleting tokens
+            let mut config = load_config()?;
+            if let Some(profiles) = config["profiles"].as_object_mut() {
+                profiles.remove(name.as_str());
+            }
+
+            if config["current_profile"].as_str() == Some(name.as_str()) {
+                let next = config["profiles"]
+                    .as_object()
+                    .and_then(|profiles| profiles.keys().next().cloned());
+                config["current_profile"] = next.map_or(serde_json::Value::Null, serde_json::Value::String);
+            }
+            write_config(&config)?;
+            cache::forget_profile(name)?;
+
+            output::render(
+                format,
+                &ActionRecord::new("profile.removed", format!("Removed profile '{}'", name)),
+            )?;
+        }
+        _ => unreachable!("subcommand_required prevents this"),
+    }
+
+    Ok(())
+}
@@ -1,111 +1,27 @@
-use anylist_rs::{AnyListClient, Ingredient, Recipe};
-use clap::{Arg, ArgMatches, Command};
+use anylist_rs::Recipe;
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use inquire::{Confirm, Text};
+use std::io::{self, IsTerminal, Write};
+use std::path::PathBuf;
+use std::{env, fs, process};
 
-use crate::auth::read_tokens;
+use crate::auth::Session;
+use crate::cache::{self, Snapshot};
 use crate::error::CliError;
+use crate::item_parser::parse_item;
+use crate::output::{self, ActionRecord, OutputFormat, RecipeRecord, RecipeSummaries};
+use crate::quantity::{adjust_text, format_amount, parse_servings, UnitSystem};
+use crate::recipe_export::{export, file_stem, ExportFormat};
+use crate::recipe_import::from_html;
+use crate::recipe_search::{search, RecipeQuery};
+use crate::recipe_text::{cleared_on_save, parse_document, parse_ingredient, to_document, RecipeDraft};
+use crate::resolve::{resolve, resolve_to_change};
+use crate::shopping::{add_items, choose_items, ingredient_item, MergeMode, NewItem};
 
-fn display_recipe_list(recipes: Vec<Recipe>) {
-    if recipes.is_empty() {
-        println!("No recipes found.");
-        return;
-    }
-
-    println!("\nYour Recipes:");
-    println!("{}", "=".repeat(13));
-    println!();
-
-    // Sort recipes by name
-    let mut sorted = recipes;
-    sorted.sort_by(|a, b| a.name().to_lowercase().cmp(&b.name().to_lowercase()));
-
-    for recipe in sorted {
-        let ingredient_count = recipe.ingredients().len();
-        let step_count = recipe.preparation_steps().len();
-        print!("  \x1B[1m{}\x1B[0m", recipe.name());
-
-        if ingredient_count > 0 || step_count > 0 {
-            print!(" ({} ingredients, {} steps)", ingredient_count, step_count);
-        }
-
-        if let Some(rating) = recipe.rating() {
-            let stars = "★".repeat(rating as usize);
-            print!(" {}", stars);
-        }
-
-        println!();
-    }
-    println!();
-}
-
-fn display_ingredient(ingredient: &Ingredient) {
-    print!("    • {}", ingredient.name());
-    if let Some(qty) = &ingredient.quantity() {
-        print!(": {}", qty);
-    }
-    if let Some(note) = &ingredient.note() {
-        print!(" ({})", note);
-    }
-    println!();
-}
-
-fn display_recipe_detail(recipe: &Recipe) {
-    println!("\n\x1B[1m{}\x1B[0m", recipe.name());
-    println!("{}", "=".repeat(recipe.name().len()));
-    println!();
-
-    // Display ID
-    println!("ID: {}", recipe.id());
-
-    // Display rating
-    if let Some(rating) = recipe.rating() {
-        let stars = "★".repeat(rating as usize);
-        println!("Rating: {}", stars);
-    }
-
-    // Display source
-    if let Some(source_name) = &recipe.source_name() {
-        print!("Source: {}", source_name);
-        if let Some(source_url) = &recipe.source_url() {
-            print!(" ({})", source_url);
-        }
-        println!();
-    }
-
-    // Display servings
-    if let Some(servings) = &recipe.servings() {
-        println!("Servings: {}", servings);
-    }
-
-    // Display times (convert from seconds to minutes)
-    if let Some(prep_time) = recipe.prep_time() {
-        println!("Prep Time: {} minutes", prep_time / 60);
-    }
-    if let Some(cook_time) = recipe.cook_time() {
-        println!("Cook Time: {} minutes", cook_time / 60);
-    }
-
-    // Display note
-    if let Some(note) = &recipe.note() {
-        println!("\nNote: {}", note);
-    }
-
-    // Display ingredients
-    if !recipe.ingredients().is_empty() {
-        println!("\n\x1B[1mIngredients:\x1B[0m");
-        for ingredient in recipe.ingredients().to_owned().into_iter() {
-            display_ingredient(&ingredient);
-        }
-    }
-
-    // Display preparation steps
-    if !recipe.preparation_steps().is_empty() {
-        println!("\n\x1B[1mPreparation:\x1B[0m");
-        for (i, step) in recipe.preparation_steps().iter().enumerate() {
-            println!("  {}. {}", i + 1, step);
-        }
-    }
-
-    println!();
+fn render_recipe_list(format: OutputFormat, mut recipes: Vec<Recipe>) -> Result<(), CliError> {
+    recipes.sort_by_key(|recipe| recipe.name().to_lowercase());
+    let records = recipes.iter().map(RecipeRecord::from).collect();
+    output::render(format, &RecipeSummaries(records))
 }
 
 pub fn command() -> Command {
@@ -114,7 +30,7 @@ pub fn command() -> Command {
         .long_about(
             "View and manage your AnyList recipes.\n\n\
              By default, this command shows all your recipes.\n\
-             Use subcommands to view recipe details.",
+             Use subcommands to view, create, edit or delete recipes, or to shop for one.",
         )
         .subcommand(
             Command::new("list")
@@ -124,43 +40,719 @@ pub fn command() -> Command {
         .subcommand(
             Command::new("get")
                 .about("Display details for a specific recipe")
+                .long_about(
+                    "Display details for a specific recipe.\n\n\
+                     --servings scales the ingredient quantities, and --units converts\n\
+                     them, so \"1 1/2 cups\" can be shown as \"355 ml\". Quantities that\n\
+                     aren't amounts (\"a pinch\") are shown as written.",
+                )
                 .arg(
                     Arg::new("name")
                         .help("Name or ID of the recipe to display")
                         .required(true)
                         .value_name("RECIPE_NAME_OR_ID"),
+                )
+                .arg(
+                    Arg::new("servings")
+                        .short('s')
+                        .long("servings")
+                        .value_name("N")
+                        .value_parser(value_parser!(f64))
+                        .help("Scale the ingredients to this many servings"),
+                )
+                .arg(
+                    Arg::new("units")
+                        .short('u')
+                        .long("units")
+                        .value_parser(["metric", "imperial"])
+                        .help("Convert the ingredient quantities to these units"),
+                ),
+        )
+        .subcommand(
+            Command::new("shop")
+                .about("Add a recipe's ingredients to a shopping list")
+                .long_about(
+                    "Add a recipe's ingredients to a shopping list.\n\n\
+                     Quantities are scaled when --servings differs from what the recipe\n\
+                     serves. In a terminal you can deselect ingredients you already have.\n\
+                     Ingredients already on the list are merged into the existing items,\n\
+                     and each item's details say which recipe it is for.",
+                )
+                .arg(
+                    Arg::new("name")
+                        .help("Name or ID of the recipe")
+                        .required(true)
+                        .value_name("RECIPE_NAME_OR_ID"),
+                )
+                .arg(
+                    Arg::new("list")
+                        .short('l')
+                        .long("list")
+                        .required(true)
+                        .value_name("LIST_NAME")
+                        .help("List to add the ingredients to"),
+                )
+                .arg(
+                    Arg::new("servings")
+                        .short('s')
+                        .long("servings")
+                        .value_name("N")
+                        .value_parser(value_parser!(f64))
+                        .help("Number of servings to shop for"),
+                )
+                .arg(
+                    Arg::new("all")
+                        .long("all")
+                        .action(ArgAction::SetTrue)
+                        .help("Add every ingredient without asking"),
+                )
+                .args(MergeMode::args()),
+        )
+        .subcommand(
+            Command::new("create")
+                .about("Create a recipe")
+                .long_about(
+                    "Create a recipe from flags, or by answering prompts when run in a\n\
+                     terminal without --ingredient or --step.\n\n\
+                     Ingredients are written like list items (\"200 g spaghetti (al dente)\")\n\
+                     or as \"name | quantity | note\".",
+                )
+                .arg(Arg::new("name").value_name("NAME").help("Recipe name"))
+                .arg(
+                    Arg::new("ingredient")
+                        .short('i')
+                        .long("ingredient")
+                        .value_name("TEXT")
+                        .action(ArgAction::Append)
+                        .help("An ingredient (repeat for more)"),
+                )
+                .arg(
+                    Arg::new("step")
+                        .long("step")
+                        .value_name("TEXT")
+                        .action(ArgAction::Append)
+                        .help("A preparation step (repeat for more, in order)"),
+                ),
+        )
+        .subcommand(
+            Command::new("import")
+                .about("Import a recipe from a web page")
+                .long_about(
+                    "Create a recipe from the schema.org Recipe data that most recipe\n\
+                     sites embed in their pages. Pass a URL, or the path of a saved HTML\n\
+                     file to import without a network connection.\n\n\
+                     The name, ingredients and steps are saved. AnyList recipes created\n\
+                     from the CLI can't hold servings, times, a description or a source\n\
+                     yet, so those are shown but not saved.",
+                )
+                .arg(
+                    Arg::new("source")
+                        .help("URL or HTML file to import from")
+                        .required(true)
+                        .value_name("URL_OR_FILE"),
+                )
+                .arg(
+                    Arg::new("dry_run")
+                        .long("dry-run")
+                        .action(ArgAction::SetTrue)
+                        .help("Show the imported recipe without saving it"),
+                ),
+        )
+        .subcommand(
+            Command::new("search")
+                .about("Find recipes by name, ingredients, rating, time or source")
+                .long_about(
+                    "Find recipes, best matches first.\n\n\
+                     Search text is looked for in names first, then ingredients and steps.\n\
+                     Ingredients match whole words, ignoring case and plurals; --without\n\
+                     also accepts the groups dairy, meat, seafood, nuts and gluten:\n\n\
+                     \x20   anylist recipe search --ingredient chicken --without dairy",
+                )
+                .arg(Arg::new("query").value_name("TEXT").help("Words to search for"))
+                .arg(
+                    Arg::new("ingredient")
+                        .short('i')
+                        .long("ingredient")
+                        .value_name("INGREDIENT")
+                        .action(ArgAction::Append)
+                        .help("Only recipes using this ingredient (repeatable)"),
+                )
+                .arg(
+                    Arg::new("without")
+                        .short('x')
+                        .long("without")
+                        .value_name("INGREDIENT")
+                        .action(ArgAction::Append)
+                        .help("Skip recipes using this ingredient or group (repeatable)"),
+                )
+                .arg(
+                    Arg::new("min_rating")
+                        .long("min-rating")
+                        .value_name("STARS")
+                        .value_parser(value_parser!(i32).range(1..=5))
+                        .help("Only recipes rated at least this many stars"),
+                )
+                .arg(
+                    Arg::new("max_time")
+                        .long("max-time")
+                        .value_name("MINUTES")
+                        .value_parser(value_parser!(i32).range(0..))
+                        .help("Only recipes whose prep and cook time add up to at most this"),
+                )
+                .arg(
+                    Arg::new("source")
+                        .long("source")
+                        .value_name("TEXT")
+                        .help("Only recipes whose source name or URL contains this"),
+                )
+                .arg(
+                    Arg::new("limit")
+                        .short('n')
+                        .long("limit")
+                        .value_name("N")
+                        .value_parser(value_parser!(usize))
+                        .help("Show at most this many recipes"),
+                ),
+        )
+        .subcommand(
+            Command::new("export")
+                .about("Export recipes as Markdown, JSON, Paprika or Cooklang files")
+                .long_about(
+                    "Export recipes as files, for backups or for other recipe apps.\n\n\
+                     Formats: markdown (.md), json (.json, the same data as\n\
+                     'recipe get -o json'), paprika (.yml, Paprika's import format) and\n\
+                     cooklang (.cook). Without --out, a single recipe is printed.",
+                )
+                .arg(
+                    Arg::new("name")
+                        .help("Name or ID of the recipe")
+                        .required_unless_present("all")
+                        .conflicts_with("all")
+                        .value_name("RECIPE_NAME_OR_ID"),
+                )
+                .arg(
+                    Arg::new("all")
+                        .long("all")
+                        .action(ArgAction::SetTrue)
+                        .requires("out")
+                        .help("Export every recipe"),
+                )
+                .arg(
+                    Arg::new("format")
+                        .short('f')
+                        .long("format")
+                        .value_parser(ExportFormat::NAMES)
+                        .default_value("markdown")
+                        .help("File format"),
+                )
+                .arg(
+                    Arg::new("out")
+                        .long("out")
+                        .value_name("DIR")
+                        .value_parser(value_parser!(PathBuf))
+                        .help("Directory to write the files to (created if missing)"),
+                ),
+        )
+        .subcommand(
+            Command::new("edit")
+                .about("Edit a recipe in your editor")
+                .long_about(
+                    "Open a recipe in $VISUAL or $EDITOR as a text document and save the\n\
+                     changes when the editor closes.\n\n\
+                     The name, ingredients and steps can be edited. Servings, times and the\n\
+                     note are shown for reference but can't be saved from the CLI.",
+                )
+                .arg(
+                    Arg::new("name")
+                        .help("Name or ID of the recipe")
+                        .required(true)
+                        .value_name("RECIPE_NAME_OR_ID"),
                 ),
         )
+        .subcommand(
+            Command::new("delete")
+                .about("Delete a recipe")
+                .arg(
+                    Arg::new("name")
+                        .help("Name or ID of the recipe")
+                        .required(true)
+                        .value_name("RECIPE_NAME_OR_ID"),
+                )
+                .arg(
+                    Arg::new("yes")
+                        .short('y')
+                        .long("yes")
+                        .action(ArgAction::SetTrue)
+                        .help("Delete without asking, unless the name only partly matches a recipe"),
+                ),
+        )
+}
+
+/// Show recipes from the local snapshot.
+fn exec_offline(matches: &ArgMatches, format: OutputFormat) -> Result<(), CliError> {
+    let snapshot = Snapshot::load()?;
+    let saved_at = snapshot
+        .recipes_saved_at
+        .clone()
+        .ok_or_else(|| CliError::NotCached("your recipes".to_string()))?;
+    eprintln!("Offline: showing recipes cached at {}", saved_at);
+
+    match matches.subcommand() {
+        Some(("get", sub_matches)) => {
+            let identifier = sub_matches
+                .get_one::<String>("name")
+                .expect("required argument");
+
+            let recipe = resolve(identifier, &snapshot.recipes, io::stdin().is_terminal())?;
+
+            output::render(format, &adjusted_record(recipe, sub_matches)?)?;
+        }
+        Some(("search", sub_matches)) => search_recipes(sub_matches, &snapshot.recipes, format)?,
+        Some(("export", sub_matches)) => export_recipes(sub_matches, &snapshot.recipes, format)?,
+        _ => render_recipe_list(format, snapshot.recipes)?,
+    }
+
+    Ok(())
 }
 
-pub async fn exec_command(matches: &ArgMatches) -> Result<(), CliError> {
-    let tokens = read_tokens()?;
-    let client = AnyListClient::from_tokens(tokens)?;
+pub async fn exec_command(matches: &ArgMatches, format: OutputFormat) -> Result<(), CliError> {
+    if matches.get_flag("offline") {
+        return exec_offline(matches, format);
+    }
+
+    if let Some(("import", sub_matches)) = matches.subcommand() {
+        return import_recipe(sub_matches, format).await;
+    }
+
+    let client = Session::open()?;
 
     match matches.subcommand() {
         Some(("list", _)) => {
             let recipes = client.get_recipes().await?;
-            display_recipe_list(recipes);
+            cache::remember_recipes(&recipes);
+            render_recipe_list(format, recipes)?;
         }
         Some(("get", sub_matches)) => {
             let identifier = sub_matches
                 .get_one::<String>("name")
                 .expect("required argument");
 
-            // Try to get by name first, fall back to ID
-            let recipe = match client.get_recipe_by_name(identifier).await {
-                Ok(r) => r,
-                Err(_) => client.get_recipe_by_id(identifier).await?,
+            let recipes = client.get_recipes().await?;
+            cache::remember_recipes(&recipes);
+            let recipe = resolve(identifier, &recipes, io::stdin().is_terminal())?;
+
+            output::render(format, &adjusted_record(recipe, sub_matches)?)?;
+        }
+        Some(("shop", sub_matches)) => {
+            let query = sub_matches
+                .get_one::<String>("name")
+                .expect("required argument");
+            let list_name = sub_matches
+                .get_one::<String>("list")
+                .expect("required argument");
+
+            let recipes = client.get_recipes().await?;
+            cache::remember_recipes(&recipes);
+            let recipe = resolve(query, &recipes, io::stdin().is_terminal())?;
+
+            let ratio = servings_ratio(recipe, sub_matches.get_one::<f64>("servings").copied())?;
+
+            let items: Vec<NewItem> = recipe
+                .ingredients()
+                .iter()
+                .map(|ingredient| ingredient_item(ingredient, ratio, recipe.name()))
+                .collect();
+            let items = if sub_matches.get_flag("all") {
+                items
+            } else {
+                choose_items(items, "Ingredients to add (deselect what you already have):")?
+            };
+            if items.is_empty() {
+                let message = format!("Nothing to add from '{}'", recipe.name());
+                return output::render(format, &ActionRecord::new("item.none_added", message));
+            }
+
+            let list = client.get_list_by_name(list_name).await?;
+            let record = add_items(&client, &list, items, MergeMode::from_matches(sub_matches)).await;
+            output::render(format, &record)?;
+            if record.failed > 0 {
+                return Err(CliError::PartialFailure(record.failed, record.results.len()));
+            }
+        }
+        Some(("search", sub_matches)) => {
+            let recipes = client.get_recipes().await?;
+            cache::remember_recipes(&recipes);
+            search_recipes(sub_matches, &recipes, format)?;
+        }
+        Some(("export", sub_matches)) => {
+            let recipes = client.get_recipes().await?;
+            cache::remember_recipes(&recipes);
+            export_recipes(sub_matches, &recipes, format)?;
+        }
+        Some(("create", sub_matches)) => {
+            let draft = match sub_matches.get_one::<String>("name") {
+                Some(name) if sub_matches.contains_id("ingredient") || sub_matches.contains_id("step") => RecipeDraft {
+                    name: name.clone(),
+                    ingredients: sub_matches
+                        .get_many::<String>("ingredient")
+                        .unwrap_or_default()
+                        .filter_map(|text| parse_ingredient(text))
+                        .collect(),
+                    steps: sub_matches.get_many::<String>("step").unwrap_or_default().cloned().collect(),
+                },
+                name => prompt_recipe(name.map(String::as_str))?,
+            };
+
+            let recipe = client.create_recipe(&draft.name, draft.ingredients, draft.steps).await?;
+            output::render(
+                format,
+                &ActionRecord::with_record(
+                    "recipe.created",
+                    format!("Created recipe: {} (ID: {})", recipe.name(), recipe.id()),
+                    RecipeRecord::from(&recipe),
+                ),
+            )?;
+        }
+        Some(("edit", sub_matches)) => {
+            let query = sub_matches
+                .get_one::<String>("name")
+                .expect("required argument");
+
+            let recipes = client.get_recipes().await?;
+            cache::remember_recipes(&recipes);
+            let recipe = resolve(query, &recipes, io::stdin().is_terminal())?;
+            let before = RecipeDraft::from(recipe);
+
+            let mut document = to_document(recipe);
+            let (draft, changes) = loop {
+                let edited = edit_text(&document)?;
+                // Closed without saving: cancel, even if the document
+                // wouldn't parse back to exactly the same recipe
+                if edited == document {
+                    let message = format!("No changes to recipe '{}'", recipe.name());
+                    return output::render(format, &ActionRecord::new("recipe.unchanged", message));
+                }
+                document = edited;
+                match parse_document(&document) {
+                    Ok(mut draft) => {
+                        draft.keep_unchanged_ingredients(&before);
+                        draft.keep_unchanged_steps(&before);
+                        let changes = draft.changes_from(&before);
+                        break (draft, changes);
+                    }
+                    Err(err) => {
+                        eprintln!("{}", err);
+                        if !Confirm::new("Edit again?").with_default(true).prompt()? {
+                            return Err(CliError::PromptCancelled);
+                        }
+                    }
+                }
+            };
+            if changes.is_empty() {
+                let message = format!("No changes to recipe '{}'", recipe.name());
+                return output::render(format, &ActionRecord::new("recipe.unchanged", message));
+            }
+
+            let cleared = cleared_on_save(recipe);
+            if let Some((last, rest)) = cleared.split_last() {
+                let fields = if rest.is_empty() {
+                    last.to_string()
+                } else {
+                    format!("{} and {}", rest.join(", "), last)
+                };
+                let prompt = format!("Saving clears this recipe's {}. Continue?", fields);
+                if !Confirm::new(&prompt).with_default(false).prompt()? {
+                    return Err(CliError::PromptCancelled);
+                }
+            }
+
+            client
+                .update_recipe(recipe.id(), &draft.name, draft.ingredients, draft.steps)
+                .await?;
+            let mut message = format!("Updated recipe '{}'", draft.name);
+            for change in changes {
+                message.push_str(&format!("\n  {}", change));
+            }
+            output::render(format, &ActionRecord::new("recipe.updated", message))?;
+        }
+        Some(("delete", sub_matches)) => {
+            let query = sub_matches
+                .get_one::<String>("name")
+                .expect("required argument");
+
+            let recipes = client.get_recipes().await?;
+            let interactive = io::stdin().is_terminal();
+            let recipe = if sub_matches.get_flag("yes") {
+                // --yes skips the question for the recipe named, not for a
+                // partial match: a deleted recipe can't be brought back
+                resolve_to_change(query, &recipes, |_| true, "Delete", false, interactive)?
+            } else {
+                resolve(query, &recipes, interactive)?
             };
+            if !sub_matches.get_flag("yes") {
+                if !interactive {
+                    return Err(CliError::InvalidInput(format!(
+                        "refusing to delete recipe '{}' without confirmation; pass --yes",
+                        recipe.name()
+                    )));
+                }
+                let prompt = format!("Delete recipe '{}'?", recipe.name());
+                if !Confirm::new(&prompt).with_default(false).prompt()? {
+                    return Err(CliError::PromptCancelled);
+                }
+            }
 
-            display_recipe_detail(&recipe);
+            client.delete_recipe(recipe.id()).await?;
+            let remaining: Vec<Recipe> = recipes.iter().filter(|r| r.id() != recipe.id()).cloned().collect();
+            cache::remember_recipes(&remaining);
+            output::render(
+                format,
+                &ActionRecord::new("recipe.deleted", format!("Deleted recipe '{}'", recipe.name())),
+            )?;
         }
         _ => {
             // Default: show all recipes
             let recipes = client.get_recipes().await?;
-            display_recipe_list(recipes);
+            cache::remember_recipes(&recipes);
+            render_recipe_list(format, recipes)?;
         }
     }
 
     Ok(())
 }
+
+/// How much to scale a recipe to serve `wanted`; 1 when no servings are given.
+fn servings_ratio(recipe: &Recipe, wanted: Option<f64>) -> Result<f64, CliError> {
+    match wanted {
+        None => Ok(1.0),
+        Some(wanted) if wanted <= 0.0 => Err(CliError::InvalidInput("--servings must be more than 0".to_string())),
+        Some(wanted) => {
+            let serves = recipe.servings().and_then(parse_servings).ok_or_else(|| {
+                CliError::InvalidInput(format!(
+                    "recipe '{}' doesn't say how many it serves, so it can't be scaled",
+                    recipe.name()
+                ))
+            })?;
+            Ok(wanted / serves)
+        }
+    }
+}
+
+/// The recipe for `recipe get`, scaled by `--servings` and converted to `--units`.
+fn adjusted_record(recipe: &Recipe, matches: &ArgMatches) -> Result<RecipeRecord, CliError> {
+    let mut record = RecipeRecord::from(recipe);
+    let wanted = matches.get_one::<f64>("servings").copied();
+    let ratio = servings_ratio(recipe, wanted)?;
+    let system = matches
+        .get_one::<String>("units")
+        .and_then(|units| UnitSystem::parse(units));
+    if ratio == 1.0 && system.is_none() {
+        return Ok(record);
+    }
+
+    if let Some(wanted) = wanted {
+        record.servings = Some(format_amount(wanted));
+    }
+    for ingredient in &mut record.ingredients {
+        match ingredient.quantity.as_deref().filter(|quantity| !quantity.trim().is_empty()) {
+            Some(quantity) => ingredient.quantity = Some(adjust_text(quantity, ratio, system)),
+            // Some recipes keep the whole line ("2 cups flour") in the name
+            None => {
+                let parsed = parse_item(&ingredient.name);
+                if let Some(quantity) = parsed.quantity {
+                    ingredient.name = parsed.name;
+                    let quantity = quantity.scaled(ratio);
+                    let quantity = system.map_or(quantity, |system| quantity.convert(system));
+                    ingredient.quantity = Some(match parsed.count {
+                        Some(count) => format!("{} x {}", count, quantity.to_recipe_string()),
+                        None => quantity.to_recipe_string(),
+                    });
+                }
+            }
+        }
+    }
+    Ok(record)
+}
+
+fn search_recipes(matches: &ArgMatches, recipes: &[Recipe], format: OutputFormat) -> Result<(), CliError> {
+    let strings = |id: &str| -> Vec<String> { matches.get_many::<String>(id).unwrap_or_default().cloned().collect() };
+    let query = RecipeQuery {
+        text: matches.get_one::<String>("query").cloned(),
+        include: strings("ingredient"),
+        exclude: strings("without"),
+        min_rating: matches.get_one::<i32>("min_rating").copied(),
+        max_minutes: matches.get_one::<i32>("max_time").copied(),
+        source: matches.get_one::<String>("source").cloned(),
+    };
+
+    let limit = matches.get_one::<usize>("limit").copied().unwrap_or(usize::MAX);
+    let records = search(recipes, &query)
+        .into_iter()
+        .take(limit)
+        .map(RecipeRecord::from)
+        .collect();
+    output::render(format, &RecipeSummaries(records))
+}
+
+/// Write the chosen recipes to files in `--out`, or print a single one.
+fn export_recipes(matches: &ArgMatches, recipes: &[Recipe], format: OutputFormat) -> Result<(), CliError> {
+    let export_format = matches
+        .get_one::<String>("format")
+        .and_then(|name| ExportFormat::parse(name))
+        .expect("validated by clap");
+    let chosen: Vec<&Recipe> = match matches.get_one::<String>("name") {
+        Some(query) => vec![resolve(query, recipes, io::stdin().is_terminal())?],
+        None => recipes.iter().collect(),
+    };
+
+    let Some(dir) = matches.get_one::<PathBuf>("out") else {
+        // Only reachable for a single recipe: --all requires --out
+        print!("{}", export(chosen[0], export_format)?);
+        return Ok(());
+    };
+
+    fs::create_dir_all(dir)?;
+    let mut paths: Vec<String> = Vec::new();
+    for recipe in chosen {
+        // Recipes with the same name get "-2", "-3", ... instead of overwriting each other
+        let stem = file_stem(recipe);
+        let mut path = dir.join(format!("{}.{}", stem, export_format.extension()));
+        let mut copy = 1;
+        while paths.contains(&path.display().to_string()) {
+            copy += 1;
+            path = dir.join(format!("{}-{}.{}", stem, copy, export_format.extension()));
+        }
+        fs::write(&path, export(recipe, export_format)?)?;
+        paths.push(path.display().to_string());
+    }
+
+    let message = format!("Exported {} recipe(s) to {}", paths.len(), dir.display());
+    output::render(format, &ActionRecord::with_record("recipe.exported", message, paths))
+}
+
+/// Read a recipe from a URL or saved page, then save it unless `--dry-run`.
+async fn import_recipe(matches: &ArgMatches, format: OutputFormat) -> Result<(), CliError> {
+    let source = matches
+        .get_one::<String>("source")
+        .expect("required argument");
+
+    let imported = if source.starts_with("http://") || source.starts_with("https://") {
+        let fetch_failed = |err: reqwest::Error| CliError::ImportFailed(format!("{}: {}", source, err));
+        let html = reqwest::get(source.as_str())
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(fetch_failed)?
+            .text()
+            .await
+            .map_err(fetch_failed)?;
+        from_html(&html, Some(source))?
+    } else {
+        from_html(&fs::read_to_string(source)?, None)?
+    };
+
+    if matches.get_flag("dry_run") {
+        // Not saved yet, so there is no ID
+        return output::render(format, &imported.record(""));
+    }
+
+    let client = Session::open()?;
+    let recipe = client
+        .create_recipe(&imported.name, imported.ingredients.clone(), imported.steps.clone())
+        .await?;
+
+    let mut message = format!("Imported recipe: {} (ID: {})", recipe.name(), recipe.id());
+    let unsaved: Vec<&str> = [
+        ("servings", imported.servings.is_some()),
+        ("prep time", imported.prep_time.is_some()),
+        ("cook time", imported.cook_time.is_some()),
+        ("description", imported.note.is_some()),
+        ("source", imported.source_name.is_some() || imported.source_url.is_some()),
+    ]
+    .into_iter()
+    .filter_map(|(field, present)| present.then_some(field))
+    .collect();
+    if !unsaved.is_empty() {
+        message.push_str(&format!("\n  Not saved (not supported yet): {}", unsaved.join(", ")));
+    }
+    output::render(
+        format,
+        &ActionRecord::with_record("recipe.imported", message, imported.record(recipe.id())),
+    )
+}
+
+/// Ask for a recipe's name, ingredients and steps, one prompt per line.
+fn prompt_recipe(name: Option<&str>) -> Result<RecipeDraft, CliError> {
+    if !io::stdin().is_terminal() {
+        return Err(CliError::InvalidInput(
+            "give a name and --ingredient or --step when not running in a terminal".to_string(),
+        ));
+    }
+
+    let name = match name {
+        Some(name) => name.to_string(),
+        None => Text::new("Recipe name:").prompt()?,
+    };
+    if name.trim().is_empty() {
+        return Err(CliError::InvalidInput("the recipe needs a name".to_string()));
+    }
+
+    let mut ingredients = Vec::new();
+    loop {
+        let line = Text::new("Ingredient (empty to finish):")
+            .with_help_message("e.g. \"200 g spaghetti (al dente)\"")
+            .prompt()?;
+        match parse_ingredient(&line) {
+            Some(ingredient) => ingredients.push(ingredient),
+            None => break,
+        }
+    }
+
+    let mut steps = Vec::new();
+    loop {
+        let line = Text::new(&format!("Step {} (empty to finish):", steps.len() + 1)).prompt()?;
+        if line.trim().is_empty() {
+            break;
+        }
+        steps.push(line.trim().to_string());
+    }
+
+    Ok(RecipeDraft {
+        name: name.trim().to_string(),
+        ingredients,
+        steps,
+    })
+}
+
+/// Let the user edit `text` in $VISUAL or $EDITOR and return the result.
+fn edit_text(text: &str) -> Result<String, CliError> {
+    if !io::stdin().is_terminal() {
+        return Err(CliError::InvalidInput("editing needs a terminal".to_string()));
+    }
+
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    // A fresh, unguessable file only we can read; removed when `file` drops
+    let mut file = tempfile::Builder::new()
+        .prefix("anylist-recipe-")
+        .suffix(".txt")
+        .tempfile()?;
+    file.write_all(text.as_bytes())?;
+    file.flush()?;
+    let path = file.path();
+
+    // Through the shell so editors with arguments ("code --wait") work
+    let status = process::Command::new("sh")
+        .arg("-c")
+        .arg(format!("{} \"$1\"", editor))
+        .arg("sh")
+        .arg(path)
+        .status();
+    let edited = fs::read_to_string(path);
+
+    let status = status?;
+    if !status.success() {
+        return Err(CliError::InvalidInput(format!("editor '{}' exited with {}", editor, status)));
+    }
+    Ok(edited?)
+}
@@ -0,0 +1,413 @@
+use anylist_rs::{AnyListClient, List, ListItem, SyncEvent};
+use clap::{Arg, ArgMatches, Command};
+use crossterm::{
+    cursor::{Hide, MoveTo, Show},
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute, queue,
+    style::{Attribute, Print, SetAttribute},
+    terminal::{
+        self, disable_raw_mode, enable_raw_mode, Clear, ClearType, EnterAlternateScreen,
+        LeaveAlternateScreen,
+    },
+};
+use std::io::{self, Write};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+use crate::auth::Session;
+use crate::error::CliError;
+
+pub fn command() -> Command {
+    Command::new("shop")
+        .about("Full-screen interactive shopping mode for a list")
+        .long_about(
+            "Open a list in a full-screen view grouped by category.\n\n\
+             Keys: ↑/↓ or j/k to move, space to check/uncheck, a to add an item,\n\
+             r to refresh, q or Esc to quit. Changes made on other devices show up live.",
+        )
+        .arg(
+            Arg::new("list")
+                .required(true)
+                .value_name("LIST_NAME")
+                .help("Name of the list to shop from"),
+        )
+}
+
+enum Row {
+    Header(String),
+    Item(usize),
+}
+
+enum Mode {
+    Browse,
+    Adding(String),
+}
+
+struct ShopState {
+    list: List,
+    rows: Vec<Row>,
+    /// Index into `rows`; always points at a `Row::Item` when there is one
+    cursor: usize,
+    /// First row drawn, for lists taller than the terminal
+    scroll: usize,
+    mode: Mode,
+    status: String,
+}
+
+impl ShopState {
+    fn new(list: List) -> Self {
+        let mut state = ShopState {
+            list,
+            rows: Vec::new(),
+            cursor: 0,
+            scroll: 0,
+            mode: Mode::Browse,
+            status: String::new(),
+        };
+        state.rebuild_rows(None);
+        state
+    }
+
+    /// Replace the list after a refresh, keeping the cursor on the same item.
+    fn replace_list(&mut self, list: List) {
+        let selected = self.selected().map(|item| item.id().to_string());
+        self.list = list;
+        self.rebuild_rows(selected.as_deref());
+    }
+
+    /// Group unchecked items by category, with checked items at the bottom.
+    fn rebuild_rows(&mut self, keep_selected: Option<&str>) {
+        let items = self.list.items();
+        let mut unchecked: Vec<usize> = (0..items.len()).filter(|&i| !items[i].is_checked()).collect();
+        unchecked.sort_by_key(|&i| {
+            (
+                items[i].category().unwrap_or("").to_lowercase(),
+                items[i].name().to_lowercase(),
+            )
+        });
+        let mut checked: Vec<usize> = (0..items.len()).filter(|&i| items[i].is_checked()).collect();
+        checked.sort_by_key(|&i| items[i].name().to_lowercase());
+
+        self.rows.clear();
+        let mut current_category: Option<&str> = None;
+        for i in unchecked {
+            let category = items[i].category().unwrap_or("Uncategorized");
+            if current_category != Some(category) {
+                self.rows.push(Row::Header(category.to_string()));
+                current_category = Some(category);
+            }
+            self.rows.push(Row::Item(i));
+        }
+        if !checked.is_empty() {
+            self.rows.push(Row::Header("Completed".to_string()));
+            self.rows.extend(checked.into_iter().map(Row::Item));
+        }
+
+        let previous = self.cursor;
+        self.cursor = keep_selected
+            .and_then(|id| {
+                self.rows.iter().position(|row| match row {
+                    Row::Item(i) => items[*i].id() == id,
+                    Row::Header(_) => false,
+                })
+            })
+            .unwrap_or(previous.min(self.rows.len().saturating_sub(1)));
+        self.snap_cursor(1);
+    }
+
+    fn selected(&self) -> Option<&ListItem> {
+        match self.rows.get(self.cursor) {
+            Some(Row::Item(i)) => self.list.items().get(*i),
+            _ => None,
+        }
+    }
+
+    fn move_cursor(&mut self, delta: isize) {
+        let target = self.cursor as isize + delta;
+        if target < 0 || target >= self.rows.len() as isize {
+            return;
+        }
+        self.cursor = target as usize;
+        self.snap_cursor(delta.signum());
+    }
+
+    /// Step off header rows in `direction`, falling back to the other way.
+    fn snap_cursor(&mut self, direction: isize) {
+        let is_item = |rows: &[Row], i: usize| matches!(rows.get(i), Some(Row::Item(_)));
+        if self.rows.is_empty() || is_item(&self.rows, self.cursor) {
+            return;
+        }
+        for step in [direction, -direction] {
+            let mut i = self.cursor as isize;
+            while i >= 0 && (i as usize) < self.rows.len() {
+                if is_item(&self.rows, i as usize) {
+                    self.cursor = i as usize;
+                    return;
+                }
+                i += step;
+            }
+        }
+    }
+
+    fn draw(&mut self, out: &mut impl Write) -> io::Result<()> {
+        let (width, height) = terminal::size()?;
+        let width = width as usize;
+        // Title, blank line, then rows; the last line is the status/help bar
+        let body_height = (height as usize).saturating_sub(3).max(1);
+
+        if self.cursor < self.scroll {
+            self.scroll = self.cursor;
+        } else if self.cursor >= self.scroll + body_height {
+            self.scroll = self.cursor + 1 - body_height;
+        }
+
+        queue!(out, Clear(ClearType::All), MoveTo(0, 0))?;
+        let remaining = self.list.items().iter().filter(|item| !item.is_checked()).count();
+        queue!(
+            out,
+            SetAttribute(Attribute::Bold),
+            Print(truncate(&format!("{} — {} remaining", self.list.name(), remaining), width)),
+            SetAttribute(Attribute::Reset)
+        )?;
+
+        if self.rows.is_empty() {
+            queue!(out, MoveTo(2, 2), Print("(no items — press a to add one)"))?;
+        }
+
+        let items = self.list.items();
+        for (line, row_index) in (self.scroll..self.rows.len()).take(body_height).enumerate() {
+            queue!(out, MoveTo(0, (line + 2) as u16))?;
+            match &self.rows[row_index] {
+                Row::Header(name) => {
+                    queue!(
+                        out,
+                        SetAttribute(Attribute::Underlined),
+                        Print(truncate(name, width)),
+                        SetAttribute(Attribute::Reset)
+                    )?;
+                }
+                Row::Item(i) => {
+                    let item = &items[*i];
+                    let mut text = format!(
+                        "  [{}] {}",
+                        if item.is_checked() { "✓" } else { " " },
+                        item.name()
+                    );
+                    if let Some(qty) = item.quantity() {
+                        text.push_str(&format!(" ({})", qty));
+                    }
+                    if !item.details().is_empty() {
+                        text.push_str(&format!(" - {}", item.details()));
+                    }
+                    if row_index == self.cursor {
+                        queue!(out, SetAttribute(Attribute::Reverse))?;
+                    } else if item.is_checked() {
+                        queue!(out, SetAttribute(Attribute::Dim))?;
+                    }
+                    queue!(out, Print(truncate(&text, width)), SetAttribute(Attribute::Reset))?;
+                }
+            }
+        }
+
+        let footer = match &self.mode {
+            Mode::Browse if self.status.is_empty() => {
+                "↑/↓ move  space check  a add  r refresh  q quit".to_string()
+            }
+            Mode::Browse => self.status.clone(),
+            Mode::Adding(buffer) => format!("Add item: {}█  (Enter to add, Esc to cancel)", buffer),
+        };
+        queue!(
+            out,
+            MoveTo(0, height.saturating_sub(1)),
+            SetAttribute(Attribute::Bold),
+            Print(truncate(&footer, width)),
+            SetAttribute(Attribute::Reset)
+        )?;
+        out.flush()
+    }
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        text.to_string()
+    } else {
+        let mut truncated: String = text.chars().take(width.saturating_sub(1)).collect();
+        truncated.push('…');
+        truncated
+    }
+}
+
+/// What the event loop should do after a key press.
+enum Action {
+    None,
+    Quit,
+    Refresh,
+    Toggle,
+    Add(String),
+}
+
+fn handle_key(state: &mut ShopState, key: KeyEvent) -> Action {
+    if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+        return Action::Quit;
+    }
+
+    match &mut state.mode {
+        Mode::Adding(buffer) => match key.code {
+            KeyCode::Enter => {
+                let name = buffer.trim().to_string();
+                state.mode = Mode::Browse;
+                if name.is_empty() {
+                    Action::None
+                } else {
+                    Action::Add(name)
+                }
+            }
+            KeyCode::Esc => {
+                state.mode = Mode::Browse;
+                Action::None
+            }
+            KeyCode::Backspace => {
+                buffer.pop();
+                Action::None
+            }
+            KeyCode::Char(c) => {
+                buffer.push(c);
+                Action::None
+            }
+            _ => Action::None,
+        },
+        Mode::Browse => {
+            state.status.clear();
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Char('Q') | KeyCode::Esc => Action::Quit,
+                KeyCode::Up | KeyCode::Char('k') => {
+                    state.move_cursor(-1);
+                    Action::None
+                }
+                KeyCode::Down | KeyCode::Char('j') => {
+                    state.move_cursor(1);
+                    Action::None
+                }
+                KeyCode::Char(' ') | KeyCode::Enter => Action::Toggle,
+                KeyCode::Char('a') => {
+                    state.mode = Mode::Adding(String::new());
+                    Action::None
+                }
+                KeyCode::Char('r') => Action::Refresh,
+                _ => Action::None,
+            }
+        }
+    }
+}
+
+pub async fn exec_command(matches: &ArgMatches) -> Result<(), CliError> {
+    let list_name = matches.get_one::<String>("list").expect("required argument");
+
+    let session = Session::open()?;
+    let client = session.shared();
+    let list = client.get_list_by_name(list_name).await?;
+
+    // Forward list changes from other devices into the event loop
+    let (changes_tx, changes_rx) = mpsc::unbounded_channel();
+    let mut sync = client
+        .start_realtime_sync(move |event| {
+            if event == SyncEvent::ShoppingListsChanged {
+                let _ = changes_tx.send(());
+            }
+        })
+        .await?;
+
+    let mut stdout = io::stdout();
+    enable_raw_mode().map_err(CliError::IoError)?;
+    execute!(stdout, EnterAlternateScreen, Hide).map_err(CliError::IoError)?;
+
+    let result = run_shop(&client, ShopState::new(list), changes_rx).await;
+
+    // Always restore the terminal, even if the loop failed
+    execute!(stdout, Show, LeaveAlternateScreen).ok();
+    disable_raw_mode().ok();
+    sync.disconnect().await.ok();
+
+    result
+}
+
+async fn run_shop(
+    client: &AnyListClient,
+    mut state: ShopState,
+    mut changes: mpsc::UnboundedReceiver<()>,
+) -> Result<(), CliError> {
+    let mut stdout = io::stdout();
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+
+    state.draw(&mut stdout)?;
+    loop {
+        tokio::select! {
+            _ = &mut ctrl_c => break,
+            Some(()) = changes.recv() => {
+                // Collapse bursts of notifications into one fetch
+                while changes.try_recv().is_ok() {}
+                refresh(client, &mut state, "Updated from another device").await;
+                state.draw(&mut stdout)?;
+            }
+            _ = tokio::time::sleep(Duration::from_millis(50)) => {
+                if !event::poll(Duration::from_millis(0)).map_err(CliError::IoError)? {
+                    continue;
+                }
+                let key = match event::read().map_err(CliError::IoError)? {
+                    Event::Key(key) if key.kind == KeyEventKind::Press => key,
+                    Event::Resize(_, _) => {
+                        state.draw(&mut stdout)?;
+                        continue;
+                    }
+                    _ => continue,
+                };
+
+                match handle_key(&mut state, key) {
+                    Action::Quit => break,
+                    Action::None => {}
+                    Action::Refresh => refresh(client, &mut state, "Refreshed").await,
+                    Action::Toggle => {
+                        if let Some(item) = state.selected() {
+                            let (list_id, item_id) = (item.list_id().to_string(), item.id().to_string());
+                            let name = item.name().to_string();
+                            let result = if item.is_checked() {
+                                client.uncheck_item(&list_id, &item_id).await
+                            } else {
+                                client.cross_off_item(&list_id, &item_id).await
+                            };
+                            match result {
+                                Ok(()) => refresh(client, &mut state, &format!("Toggled '{}'", name)).await,
+                                Err(err) => state.status = format!("Error: {}", err),
+                            }
+                        }
+                    }
+                    Action::Add(name) => {
+                        let list_id = state.list.id().to_string();
+                        match client.add_item(&list_id, &name).await {
+                            Ok(item) => {
+                                let message = format!("Added '{}'", item.name());
+                                refresh(client, &mut state, &message).await;
+                            }
+                            Err(err) => state.status = format!("Error: {}", err),
+                        }
+                    }
+                }
+                state.draw(&mut stdout)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Refetch the list, reporting failures in the status bar rather than exiting.
+async fn refresh(client: &AnyListClient, state: &mut ShopState, message: &str) {
+    match client.get_list_by_id(state.list.id()).await {
+        Ok(list) => {
+            state.replace_list(list);
+            state.status = message.to_string();
+        }
+        Err(err) => state.status = format!("Refresh failed: {}", err),
+    }
+}
@@ -1,8 +1,8 @@
-use anylist_rs::AnyListClient;
 use clap::{Arg, ArgMatches, Command};
 
-use crate::auth::read_tokens;
+use crate::auth::Session;
 use crate::error::CliError;
+use crate::output::{self, ActionRecord, OutputFormat, StoreRecord, StoresRecord};
 
 pub fn command() -> Command {
     Command::new("store")
@@ -35,34 +35,40 @@ pub fn command() -> Command {
         )
 }
 
-pub async fn exec_command(matches: &ArgMatches) -> Result<(), CliError> {
-    let tokens = read_tokens()?;
-    let client = AnyListClient::from_tokens(tokens)?;
+pub async fn exec_command(matches: &ArgMatches, format: OutputFormat) -> Result<(), CliError> {
+    let client = Session::open()?;
 
     match matches.subcommand() {
         Some(("list", sub_matches)) => {
             let list_name = sub_matches.get_one::<String>("list").unwrap();
 
             let list = client.get_list_by_name(list_name).await?;
-            let stores = client.get_stores_for_list(&list.id()).await?;
+            let stores = client.get_stores_for_list(list.id()).await?;
 
-            if stores.is_empty() {
-                println!("No stores found for list '{}'", list.name());
-            } else {
-                println!("Stores for list '{}':", list.name());
-                for store in stores {
-                    println!("  {} - {}", store.id(), store.name());
-                }
-            }
+            output::render(
+                format,
+                &StoresRecord {
+                    list_id: list.id().to_string(),
+                    list_name: list.name().to_string(),
+                    stores: stores.iter().map(StoreRecord::from).collect(),
+                },
+            )?;
         }
         Some(("add", sub_matches)) => {
             let list_name = sub_matches.get_one::<String>("list").unwrap();
             let name = sub_matches.get_one::<String>("name").unwrap();
 
             let list = client.get_list_by_name(list_name).await?;
-            let store = client.create_store(&list.id(), name).await?;
+            let store = client.create_store(list.id(), name).await?;
 
-            println!("Created store '{}' for list '{}'", store.name(), list.name());
+            output::render(
+                format,
+                &ActionRecord::with_record(
+                    "store.created",
+                    format!("Created store '{}' for list '{}'", store.name(), list.name()),
+                    StoreRecord::from(&store),
+                ),
+            )?;
         }
         Some(("update", sub_matches)) => {
             let list_name = sub_matches.get_one::<String>("list").unwrap();
@@ -70,18 +76,27 @@ pub async fn exec_command(matches: &ArgMatches) -> Result<(), CliError> {
             let name = sub_matches.get_one::<String>("name").unwrap();
 
             let list = client.get_list_by_name(list_name).await?;
-            client.update_store(&list.id(), store_id, name).await?;
+            client.update_store(list.id(), store_id, name).await?;
 
-            println!("Updated store to '{}' in list '{}'", name, list.name());
+            output::render(
+                format,
+                &ActionRecord::new(
+                    "store.updated",
+                    format!("Updated store to '{}' in list '{}'", name, list.name()),
+                ),
+            )?;
         }
         Some(("delete", sub_matches)) => {
             let list_name = sub_matches.get_one::<String>("list").unwrap();
             let store_id = sub_matches.get_one::<String>("store_id").unwrap();
 
             let list = client.get_list_by_name(list_name).await?;
-            client.delete_store(&list.id(), store_id).await?;
+            client.delete_store(list.id(), store_id).await?;
 
-            println!("Deleted store from list '{}'", list.name());
+            output::render(
+                format,
+                &ActionRecord::new("store.deleted", format!("Deleted store from list '{}'", list.name())),
+            )?;
         }
         _ => unreachable!("subcommand_required prevents this"),
     }
@@ -1,16 +1,25 @@
-use anylist_rs::AnyListClient;
 use chrono::Local;
 use clap::{ArgMatches, Command};
 use crossterm::{
     event::{self, Event, KeyCode, KeyEvent},
     terminal::{disable_raw_mode, enable_raw_mode},
 };
-use std::io::{self, Write};
-use std::sync::Arc;
+use std::io::{self, IsTerminal, Write};
 use std::time::Duration;
 
-use crate::auth::read_tokens;
+use crate::auth::Session;
 use crate::error::CliError;
+use crate::output::{self, OutputFormat, SyncEventRecord};
+
+/// Print a status line, keeping stdout clean for structured output.
+fn status(format: OutputFormat, message: &str) {
+    if format.is_structured() {
+        eprint!("{}\r\n", message);
+    } else {
+        print!("{}\r\n", message);
+        io::stdout().flush().ok();
+    }
+}
 
 pub fn command() -> Command {
     Command::new("tail")
@@ -21,26 +30,32 @@ pub fn command() -> Command {
         )
 }
 
-pub async fn exec_command(_matches: &ArgMatches) -> Result<(), CliError> {
-    let tokens = read_tokens()?;
-    let client = Arc::new(AnyListClient::from_tokens(tokens)?);
+pub async fn exec_command(_matches: &ArgMatches, format: OutputFormat) -> Result<(), CliError> {
+    let session = Session::open()?;
+    let client = session.shared();
+
+    status(format, "Connecting to AnyList WebSocket...");
 
-    println!("Connecting to AnyList WebSocket...");
+    // Raw mode disables the terminal's newline translation, so lines need an
+    // explicit carriage return. Pipes get plain newlines (e.g. valid JSON Lines).
+    let eol = if io::stdout().is_terminal() { "\r\n" } else { "\n" };
 
     // Start real-time sync with explicit stdout flushing
     let mut sync = client.start_realtime_sync(move |event| {
-        let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S");
-        print!("[{}] {:?}\n\r", timestamp, event);
+        let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        let record = SyncEventRecord::new(timestamp, &event);
+        if let Ok(entry) = output::render_stream_entry(format, &record) {
+            print!("{}", entry.replace('\n', eol));
+        }
         let _ = io::stdout().flush();
     }).await?;
 
-    println!("Connected! Monitoring events... (Press Ctrl+C or 'q' to quit)\n");
-    io::stdout().flush().ok();
+    status(format, "Connected! Monitoring events... (Press Ctrl+C or 'q' to quit)\r\n");
 
     // Enable raw mode for single keypress detection
     enable_raw_mode().map_err(crate::error::CliError::IoError)?;
 
-    let result = monitor_events(&mut sync).await;
+    let result = monitor_events(&mut sync, format).await;
 
     // Always disable raw mode on exit
     disable_raw_mode().ok();
@@ -49,7 +64,7 @@ pub async fn exec_command(_matches: &ArgMatches) -> Result<(), CliError> {
         Ok(_) => {
             // Gracefully disconnect
             sync.disconnect().await?;
-            println!("\nDisconnected.");
+            status(format, "\nDisconnected.");
             Ok(())
         }
         Err(e) => {
@@ -59,7 +74,10 @@ pub async fn exec_command(_matches: &ArgMatches) -> Result<(), CliError> {
     }
 }
 
-async fn monitor_events(sync: &mut anylist_rs::RealtimeSync) -> Result<(), CliError> {
+async fn monitor_events(
+    sync: &mut anylist_rs::RealtimeSync,
+    format: OutputFormat,
+) -> Result<(), CliError> {
     use anylist_rs::ConnectionState;
 
     let ctrl_c = tokio::signal::ctrl_c();
@@ -70,29 +88,27 @@ async fn monitor_events(sync: &mut anylist_rs::RealtimeSync) -> Result<(), CliEr
         iteration += 1;
         tokio::select! {
             _ = &mut ctrl_c => {
-                println!("\r\nReceived Ctrl+C, shutting down...");
+                status(format, "\r\nReceived Ctrl+C, shutting down...");
                 break;
             }
             _ = tokio::time::sleep(Duration::from_millis(100)) => {
                 // Check for keypresses
                 if event::poll(Duration::from_millis(0))
-                    .map_err(crate::error::CliError::IoError)? {
-
-                    if let Event::Key(KeyEvent { code, .. }) = event::read()
-                        .map_err(crate::error::CliError::IoError)? {
-
-                        match code {
-                            KeyCode::Char('q') | KeyCode::Char('Q') => {
-                                println!("\r\nReceived 'q', shutting down...");
-                                break;
-                            }
-                            KeyCode::Char('c') if cfg!(unix) => {
-                                // Ctrl+C on Unix
-                                println!("\r\nShutting down...");
-                                break;
-                            }
-                            _ => {}
+                    .map_err(crate::error::CliError::IoError)?
+                    && let Event::Key(KeyEvent { code, .. }) = event::read()
+                        .map_err(crate::error::CliError::IoError)?
+                {
+                    match code {
+                        KeyCode::Char('q') | KeyCode::Char('Q') => {
+                            status(format, "\r\nReceived 'q', shutting down...");
+                            break;
+                        }
+                        KeyCode::Char('c') if cfg!(unix) => {
+                            // Ctrl+C on Unix
+                            status(format, "\r\nShutting down...");
+                            break;
                         }
+                        _ => {}
                     }
                 }
 
@@ -105,17 +121,17 @@ async fn monitor_events(sync: &mut anylist_rs::RealtimeSync) -> Result<(), CliEr
                 // Only exit on Closed or Disconnected, not Reconnecting
                 match state {
                     ConnectionState::Closed => {
-                        println!("\r\nConnection permanently closed.");
+                        status(format, "\r\nConnection permanently closed.");
                         break;
                     }
                     ConnectionState::Disconnected => {
-                        println!("\r\nConnection disconnected.");
+                        status(format, "\r\nConnection disconnected.");
                         break;
                     }
                     ConnectionState::Reconnecting => {
                         // Let it try to reconnect, don't exit
                         if iteration % 50 == 0 {
-                            println!("Still attempting to reconnect...");
+                            status(format, "Still attempting to reconnect...");
                         }
                     }
                     ConnectionState::Connected | ConnectionState::Connecting => {
@@ -0,0 +1,44 @@
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chrono::{DateTime, Local, Utc};
+use clap::{ArgMatches, Command};
+
+use crate::auth::{active_profile, config_file_path, load_config, read_tokens};
+use crate::error::CliError;
+use crate::output::{self, OutputFormat, WhoamiRecord};
+
+pub fn command() -> Command {
+    Command::new("whoami")
+        .about("Show the account behind the active profile")
+}
+
+pub async fn exec_command(_matches: &ArgMatches, format: OutputFormat) -> Result<(), CliError> {
+    let tokens = read_tokens()?;
+    let config = load_config()?;
+    let profile = active_profile(&config);
+
+    let expires_at = token_expiry(&tokens.access_token);
+
+    output::render(
+        format,
+        &WhoamiRecord {
+            credential_store: config["profiles"][&profile]["credential_store"]
+                .as_str()
+                .unwrap_or("file")
+                .to_string(),
+            profile,
+            user_id: tokens.user_id,
+            is_premium: tokens.is_premium_user,
+            config_path: config_file_path()?.display().to_string(),
+            access_token_expires_at: expires_at.map(|at| at.with_timezone(&Local).to_rfc3339()),
+            access_token_expired: expires_at.map(|at| at <= Utc::now()),
+        },
+    )
+}
+
+/// Read the `exp` claim of a JWT access token without verifying it.
+fn token_expiry(access_token: &str) -> Option<DateTime<Utc>> {
+    let payload = access_token.split('.').nth(1)?;
+    let claims: serde_json::Value = serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload).ok()?).ok()?;
+    DateTime::from_timestamp(claims["exp"].as_i64()?, 0)
+}
@@ -0,0 +1,799 @@
+//! Where a profile's access and refresh tokens are kept.
+//!
+//! Profile metadata (user ID, premium flag, which store holds the tokens)
+//! always lives in `config.json`. The tokens themselves go to a
+//! [`CredentialStore`]: the Secret Service keyring when `secret-tool` is
+//! installed, otherwise the config file itself, encrypted with a passphrase
+//! when one can be asked for and in plain text otherwise. The config file is
+//! kept at `0600` either way.
+
+use anylist_rs::SavedTokens;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use openssl::hash::MessageDigest;
+use openssl::symm::{self, Cipher};
+use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::sync::{Mutex, OnceLock, PoisonError};
+
+use crate::error::CliError;
+
+/// Backend that persists tokens for a profile.
+pub trait CredentialStore {
+    /// Identifier saved in the profile's `credential_store` field.
+    fn kind(&self) -> StoreKind;
+
+    fn load(&self, profile: &str) -> Result<Option<SavedTokens>, CliError>;
+
+    fn save(&self, profile: &str, tokens: &SavedTokens) -> Result<(), CliError>;
+
+    fn delete(&self, profile: &str) -> Result<(), CliError>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StoreKind {
+    File,
+    EncryptedFile,
+    SecretService,
+}
+
+impl StoreKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            StoreKind::File => "file",
+            StoreKind::EncryptedFile => "encrypted-file",
+            StoreKind::SecretService => "secret-service",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "file" => Some(StoreKind::File),
+            "encrypted-file" => Some(StoreKind::EncryptedFile),
+            "secret-service" => Some(StoreKind::SecretService),
+            _ => None,
+        }
+    }
+
+    /// The system keyring when there is one, otherwise the config file.
+    /// Encrypting the file is opt-in: every later run, including scripts and
+    /// cron jobs, would need the passphrase.
+    pub fn best_available() -> Self {
+        if SecretServiceStore::is_available() {
+            StoreKind::SecretService
+        } else {
+            StoreKind::File
+        }
+    }
+}
+
+/// Open the store of the given kind, keeping file-backed tokens in `config_file`.
+pub fn open_store(kind: StoreKind, config_file: &Path) -> Box<dyn CredentialStore> {
+    match kind {
+        StoreKind::File => Box::new(FileStore::new(config_file)),
+        StoreKind::EncryptedFile => Box::new(EncryptedFileStore::new(config_file)),
+        StoreKind::SecretService => Box::new(SecretServiceStore),
+    }
+}
+
+/// Save a profile's tokens to `store`, run `switch` to point the profile at
+/// it, and only then remove the tokens from `previous` when that is another
+/// store. If any step fails, the profile's tokens can still be read from the
+/// store its config names.
+pub fn move_tokens(
+    store: &dyn CredentialStore,
+    previous: Option<&dyn CredentialStore>,
+    profile: &str,
+    tokens: &SavedTokens,
+    switch: impl FnOnce() -> Result<(), CliError>,
+) -> Result<(), CliError> {
+    store.save(profile, tokens)?;
+    switch()?;
+    if let Some(previous) = previous
+        && previous.kind() != store.kind()
+    {
+        previous.delete(profile)?;
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Private file helpers
+// ============================================================================
+
+/// Write a file that only the current user can read, replacing it atomically
+/// so a crash or full disk leaves either the old contents or the new ones.
+pub fn write_private(path: &Path, contents: &str) -> Result<(), CliError> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    // Temp files are created with mode 0600, and the rename replaces any
+    // looser permissions left by older versions
+    let mut file = tempfile::NamedTempFile::new_in(dir)?;
+    file.write_all(contents.as_bytes())?;
+    file.as_file().sync_all()?;
+    file.persist(path).map_err(|err| err.error)?;
+    Ok(())
+}
+
+/// Files locked by this process, with how many `FileLock`s share each one.
+static LOCKED_FILES: Mutex<Vec<(PathBuf, usize, fs::File)>> = Mutex::new(Vec::new());
+
+/// An exclusive lock on a file, released when the last `FileLock` for the
+/// path in this process is dropped.
+pub struct FileLock {
+    path: PathBuf,
+}
+
+/// Lock `path` against other processes until the returned guard is dropped.
+/// Locking a path this process already holds succeeds immediately, so a
+/// command can hold the lock around helpers that take it themselves.
+pub fn lock_file(path: &Path) -> Result<FileLock, CliError> {
+    let mut locked = LOCKED_FILES.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(entry) = locked.iter_mut().find(|entry| entry.0 == path) {
+        entry.1 += 1;
+    } else {
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(path)?;
+        file.lock()?;
+        locked.push((path.to_path_buf(), 1, file));
+    }
+    Ok(FileLock {
+        path: path.to_path_buf(),
+    })
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let mut locked = LOCKED_FILES.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(index) = locked.iter().position(|entry| entry.0 == self.path) {
+            locked[index].1 -= 1;
+            if locked[index].1 == 0 {
+                // Closing the file releases the lock
+                locked.swap_remove(index);
+            }
+        }
+    }
+}
+
+/// Warn when a file holding secrets can be read by other users.
+pub fn warn_if_exposed(path: &Path) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        if let Ok(metadata) = fs::metadata(path)
+            && metadata.permissions().mode() & 0o077 != 0
+        {
+            eprintln!(
+                "Warning: {} is readable by other users. Run 'chmod 600 {}' to protect your tokens.",
+                path.display(),
+                path.display()
+            );
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+}
+
+// ============================================================================
+// File store
+// ============================================================================
+
+/// Tokens stored as plain strings in the profile's entry in `config.json`.
+pub struct FileStore {
+    path: PathBuf,
+}
+
+impl FileStore {
+    pub fn new(path: &Path) -> Self {
+        FileStore {
+            path: path.to_path_buf(),
+        }
+    }
+
+    fn read(&self) -> Result<serde_json::Value, CliError> {
+        if !self.path.exists() {
+            return Ok(serde_json::json!({ "profiles": {} }));
+        }
+        Ok(serde_json::from_str(&fs::read_to_string(&self.path)?)?)
+    }
+
+    fn write(&self, config: &serde_json::Value) -> Result<(), CliError> {
+        write_private(&self.path, &serde_json::to_string(config)?)
+    }
+
+    /// Lock the file for a read-modify-write; `config.json` shares
+    /// `config.lock` with `auth::lock_config`.
+    fn lock(&self) -> Result<FileLock, CliError> {
+        lock_file(&self.path.with_extension("lock"))
+    }
+}
+
+impl CredentialStore for FileStore {
+    fn kind(&self) -> StoreKind {
+        StoreKind::File
+    }
+
+    fn load(&self, profile: &str) -> Result<Option<SavedTokens>, CliError> {
+        warn_if_exposed(&self.path);
+
+        let config = self.read()?;
+        let entry = &config["profiles"][profile];
+        // Logged-out profiles keep their entry without tokens
+        if entry.get("access_token").is_none() {
+            return Ok(None);
+        }
+        tokens_from_json(entry).map(Some)
+    }
+
+    fn save(&self, profile: &str, tokens: &SavedTokens) -> Result<(), CliError> {
+        let _lock = self.lock()?;
+        let mut config = self.read()?;
+        let entry = &mut config["profiles"][profile];
+        entry["access_token"] = serde_json::Value::String(tokens.access_token.to_string());
+        entry["refresh_token"] = serde_json::Value::String(tokens.refresh_token.to_string());
+        entry["user_id"] = serde_json::Value::String(tokens.user_id.to_string());
+        entry["is_premium"] = serde_json::Value::String(tokens.is_premium_user.to_string());
+        self.write(&config)
+    }
+
+    fn delete(&self, profile: &str) -> Result<(), CliError> {
+        let _lock = self.lock()?;
+        let mut config = self.read()?;
+        if let Some(entry) = config["profiles"][profile].as_object_mut() {
+            entry.remove("access_token");
+            entry.remove("refresh_token");
+        }
+        self.write(&config)
+    }
+}
+
+/// Parse tokens in the config file's layout, where `is_premium` is a string.
+fn tokens_from_json(entry: &serde_json::Value) -> Result<SavedTokens, CliError> {
+    let access_token = entry["access_token"]
+        .as_str()
+        .ok_or_else(|| CliError::ConfigFileInvalid("access_token".to_string()))?
+        .to_string();
+
+    let refresh_token = entry["refresh_token"]
+        .as_str()
+        .ok_or_else(|| CliError::ConfigFileInvalid("refresh_token".to_string()))?
+        .to_string();
+
+    let user_id = entry["user_id"]
+        .as_str()
+        .ok_or_else(|| CliError::ConfigFileInvalid("user_id".to_string()))?
+        .to_string();
+
+    let is_premium_user = entry["is_premium"]
+        .as_str()
+        .ok_or_else(|| CliError::ConfigFileInvalid("is_premium".to_string()))?
+        .parse::<bool>()
+        .map_err(|_| CliError::ConfigFileInvalid("is_premium (invalid boolean)".to_string()))?;
+
+    Ok(SavedTokens {
+        access_token,
+        refresh_token,
+        user_id,
+        is_premium_user,
+    })
+}
+
+// ============================================================================
+// Encrypted file store
+// ============================================================================
+
+/// Environment variable holding the passphrase for encrypted tokens, for
+/// scripts and other runs without a terminal to prompt on.
+pub const PASSPHRASE_ENV: &str = "ANYLIST_PASSPHRASE";
+
+/// OWASP's recommendation for PBKDF2-HMAC-SHA256
+const KDF_ITERATIONS: usize = 600_000;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+
+/// Asked for once per run, however many times tokens are read or saved.
+static PASSPHRASE: OnceLock<String> = OnceLock::new();
+
+/// Tokens in the profile's entry in `config.json`, encrypted with AES-256-GCM
+/// under a key derived from a passphrase.
+pub struct EncryptedFileStore {
+    file: FileStore,
+    passphrase: Option<String>,
+}
+
+impl EncryptedFileStore {
+    pub fn new(path: &Path) -> Self {
+        EncryptedFileStore {
+            file: FileStore::new(path),
+            passphrase: None,
+        }
+    }
+
+    /// A store that uses `passphrase` instead of asking for one.
+    #[cfg(test)]
+    fn with_passphrase(path: &Path, passphrase: &str) -> Self {
+        EncryptedFileStore {
+            file: FileStore::new(path),
+            passphrase: Some(passphrase.to_string()),
+        }
+    }
+
+    /// Whether a passphrase can be had without failing: from the environment,
+    /// or by prompting on a terminal.
+    pub fn is_available() -> bool {
+        std::env::var_os(PASSPHRASE_ENV).is_some() || io::stdin().is_terminal()
+    }
+
+    /// The passphrase from the environment or a prompt. A new passphrase is
+    /// asked for twice.
+    fn passphrase(&self, new: bool) -> Result<String, CliError> {
+        if let Some(passphrase) = &self.passphrase {
+            return Ok(passphrase.clone());
+        }
+        if let Some(passphrase) = PASSPHRASE.get() {
+            return Ok(passphrase.clone());
+        }
+        let passphrase = match std::env::var(PASSPHRASE_ENV) {
+            Ok(passphrase) => passphrase,
+            Err(_) if io::stdin().is_terminal() => {
+                let prompt = inquire::Password::new("Passphrase for your AnyList tokens:")
+                    .with_display_mode(inquire::PasswordDisplayMode::Masked);
+                if new {
+                    prompt.with_custom_confirmation_message("Passphrase again:").prompt()?
+                } else {
+                    prompt.without_confirmation().prompt()?
+                }
+            }
+            Err(_) => {
+                return Err(CliError::CredentialStoreError(format!(
+                    "the tokens are encrypted; set {} or run in a terminal",
+                    PASSPHRASE_ENV
+                )));
+            }
+        };
+        if passphrase.is_empty() {
+            return Err(CliError::CredentialStoreError("the passphrase is empty".to_string()));
+        }
+        Ok(PASSPHRASE.get_or_init(|| passphrase).clone())
+    }
+}
+
+fn crypto_error(err: openssl::error::ErrorStack) -> CliError {
+    CliError::CredentialStoreError(format!("encryption failed: {}", err))
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32], CliError> {
+    let mut key = [0; 32];
+    openssl::pkcs5::pbkdf2_hmac(passphrase.as_bytes(), salt, KDF_ITERATIONS, MessageDigest::sha256(), &mut key)
+        .map_err(crypto_error)?;
+    Ok(key)
+}
+
+/// Encrypt `plaintext`, returning the salt, nonce and ciphertext (with the
+/// tag appended) as base64 in one JSON object.
+fn encrypt(passphrase: &str, plaintext: &[u8]) -> Result<serde_json::Value, CliError> {
+    let mut salt = [0; SALT_LEN];
+    let mut nonce = [0; NONCE_LEN];
+    openssl::rand::rand_bytes(&mut salt).map_err(crypto_error)?;
+    openssl::rand::rand_bytes(&mut nonce).map_err(crypto_error)?;
+
+    let key = derive_key(passphrase, &salt)?;
+    let mut tag = [0; TAG_LEN];
+    let mut ciphertext =
+        symm::encrypt_aead(Cipher::aes_256_gcm(), &key, Some(&nonce), &[], plaintext, &mut tag).map_err(crypto_error)?;
+    ciphertext.extend_from_slice(&tag);
+
+    Ok(serde_json::json!({
+        "kdf": "pbkdf2-sha256",
+        "iterations": KDF_ITERATIONS,
+        "salt": STANDARD.encode(salt),
+        "nonce": STANDARD.encode(nonce),
+        "ciphertext": STANDARD.encode(ciphertext),
+    }))
+}
+
+fn decrypt(passphrase: &str, sealed: &serde_json::Value) -> Result<Vec<u8>, CliError> {
+    let field = |name: &str| {
+        sealed[name]
+            .as_str()
+            .and_then(|text| STANDARD.decode(text).ok())
+            .ok_or_else(|| CliError::ConfigFileInvalid(format!("encrypted_tokens.{}", name)))
+    };
+    if sealed["kdf"] != "pbkdf2-sha256" || sealed["iterations"] != KDF_ITERATIONS {
+        return Err(CliError::ConfigFileInvalid("encrypted_tokens (unknown key derivation)".to_string()));
+    }
+    let salt = field("salt")?;
+    let nonce = field("nonce")?;
+    let mut ciphertext = field("ciphertext")?;
+    if ciphertext.len() < TAG_LEN {
+        return Err(CliError::ConfigFileInvalid("encrypted_tokens.ciphertext".to_string()));
+    }
+    let tag = ciphertext.split_off(ciphertext.len() - TAG_LEN);
+
+    let key = derive_key(passphrase, &salt)?;
+    symm::decrypt_aead(Cipher::aes_256_gcm(), &key, Some(&nonce), &[], &ciphertext, &tag)
+        .map_err(|_| CliError::CredentialStoreError("could not decrypt the tokens; is the passphrase right?".to_string()))
+}
+
+impl CredentialStore for EncryptedFileStore {
+    fn kind(&self) -> StoreKind {
+        StoreKind::EncryptedFile
+    }
+
+    fn load(&self, profile: &str) -> Result<Option<SavedTokens>, CliError> {
+        warn_if_exposed(&self.file.path);
+
+        let config = self.file.read()?;
+        let sealed = &config["profiles"][profile]["encrypted_tokens"];
+        if sealed.is_null() {
+            return Ok(None);
+        }
+        let plaintext = decrypt(&self.passphrase(false)?, sealed)?;
+        Ok(Some(serde_json::from_slice(&plaintext)?))
+    }
+
+    fn save(&self, profile: &str, tokens: &SavedTokens) -> Result<(), CliError> {
+        let _lock = self.file.lock()?;
+        let mut config = self.file.read()?;
+        // Keep the passphrase the tokens already use, so other profiles and
+        // later runs aren't locked out by a typo
+        let existing = !config["profiles"][profile]["encrypted_tokens"].is_null();
+        let sealed = encrypt(&self.passphrase(!existing)?, &serde_json::to_vec(tokens)?)?;
+        config["profiles"][profile]["encrypted_tokens"] = sealed;
+        self.file.write(&config)
+    }
+
+    fn delete(&self, profile: &str) -> Result<(), CliError> {
+        let _lock = self.file.lock()?;
+        let mut config = self.file.read()?;
+        if let Some(entry) = config["profiles"][profile].as_object_mut() {
+            entry.remove("encrypted_tokens");
+        }
+        self.file.write(&config)
+    }
+}
+
+// ============================================================================
+// Secret Service store
+// ============================================================================
+
+/// Tokens kept in the desktop keyring (GNOME Keyring, KWallet, KeePassXC, ...)
+/// through libsecret's `secret-tool`.
+pub struct SecretServiceStore;
+
+const SECRET_SERVICE_ATTRIBUTE: &str = "anylist_cli";
+
+impl SecretServiceStore {
+    pub fn is_available() -> bool {
+        cfg!(target_os = "linux")
+            && std::env::var_os("DBUS_SESSION_BUS_ADDRESS").is_some()
+            && std::env::var_os("PATH").is_some_and(|paths| {
+                std::env::split_paths(&paths).any(|dir| dir.join("secret-tool").is_file())
+            })
+    }
+
+    /// Run `secret-tool`, failing only when it can't be started.
+    fn run(args: &[&str], stdin: Option<&str>) -> Result<Output, CliError> {
+        let mut child = Command::new("secret-tool")
+            .args(args)
+            .stdin(if stdin.is_some() { Stdio::piped() } else { Stdio::null() })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|err| CliError::CredentialStoreError(format!("could not run secret-tool: {}", err)))?;
+
+        if let (Some(input), Some(mut pipe)) = (stdin, child.stdin.take()) {
+            pipe.write_all(input.as_bytes())?;
+        }
+
+        Ok(child.wait_with_output()?)
+    }
+
+    fn failure(output: &Output) -> CliError {
+        let message = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        CliError::CredentialStoreError(if message.is_empty() {
+            format!("secret-tool failed ({})", output.status)
+        } else {
+            message
+        })
+    }
+
+    fn secret_tool(args: &[&str], stdin: Option<&str>) -> Result<String, CliError> {
+        let output = Self::run(args, stdin)?;
+        if !output.status.success() {
+            return Err(Self::failure(&output));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+impl CredentialStore for SecretServiceStore {
+    fn kind(&self) -> StoreKind {
+        StoreKind::SecretService
+    }
+
+    fn load(&self, profile: &str) -> Result<Option<SavedTokens>, CliError> {
+        let output = Self::run(&["lookup", "service", SECRET_SERVICE_ATTRIBUTE, "profile", profile], None)?;
+        // `lookup` exits non-zero without a message when nothing is stored;
+        // D-Bus and keyring errors come with one
+        if !output.status.success() {
+            return if output.stderr.is_empty() {
+                Ok(None)
+            } else {
+                Err(Self::failure(&output))
+            };
+        }
+        let secret = String::from_utf8_lossy(&output.stdout);
+        if secret.trim().is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_str(secret.trim())?))
+    }
+
+    fn save(&self, profile: &str, tokens: &SavedTokens) -> Result<(), CliError> {
+        let label = format!("AnyList CLI ({})", profile);
+        Self::secret_tool(
+            &[
+                "store",
+                "--label",
+                &label,
+                "service",
+                SECRET_SERVICE_ATTRIBUTE,
+                "profile",
+                profile,
+            ],
+            Some(&serde_json::to_string(tokens)?),
+        )?;
+        Ok(())
+    }
+
+    fn delete(&self, profile: &str) -> Result<(), CliError> {
+        Self::secret_tool(&["clear", "service", SECRET_SERVICE_ATTRIBUTE, "profile", profile], None)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::{Cell, RefCell};
+    use std::collections::HashMap;
+
+    /// Keeps tokens in memory, optionally refusing to save.
+    struct MockStore {
+        kind: StoreKind,
+        tokens: RefCell<HashMap<String, SavedTokens>>,
+        fail_save: bool,
+    }
+
+    impl MockStore {
+        fn new(kind: StoreKind) -> Self {
+            MockStore {
+                kind,
+                tokens: RefCell::new(HashMap::new()),
+                fail_save: false,
+            }
+        }
+
+        fn failing(kind: StoreKind) -> Self {
+            MockStore {
+                fail_save: true,
+                ..MockStore::new(kind)
+            }
+        }
+
+        fn holds(&self, profile: &str) -> bool {
+            self.tokens.borrow().contains_key(profile)
+        }
+    }
+
+    impl CredentialStore for MockStore {
+        fn kind(&self) -> StoreKind {
+            self.kind
+        }
+
+        fn load(&self, profile: &str) -> Result<Option<SavedTokens>, CliError> {
+            Ok(self.tokens.borrow().get(profile).cloned())
+        }
+
+        fn save(&self, profile: &str, tokens: &SavedTokens) -> Result<(), CliError> {
+            if self.fail_save {
+                return Err(CliError::CredentialStoreError("keyring locked".to_string()));
+            }
+            self.tokens.borrow_mut().insert(profile.to_string(), tokens.clone());
+            Ok(())
+        }
+
+        fn delete(&self, profile: &str) -> Result<(), CliError> {
+            self.tokens.borrow_mut().remove(profile);
+            Ok(())
+        }
+    }
+
+    fn tokens(access_token: &str) -> SavedTokens {
+        SavedTokens {
+            access_token: access_token.to_string(),
+            refresh_token: format!("{}-refresh", access_token),
+            user_id: "user-1".to_string(),
+            is_premium_user: true,
+        }
+    }
+
+    fn assert_same(a: &SavedTokens, b: &SavedTokens) {
+        assert_eq!(
+            (&a.access_token, &a.refresh_token, &a.user_id, a.is_premium_user),
+            (&b.access_token, &b.refresh_token, &b.user_id, b.is_premium_user)
+        );
+    }
+
+    #[test]
+    fn moves_tokens_between_stores() {
+        let old = MockStore::new(StoreKind::File);
+        let new = MockStore::new(StoreKind::SecretService);
+        old.save("work", &tokens("old")).unwrap();
+
+        let switched = Cell::new(false);
+        move_tokens(&new, Some(&old), "work", &tokens("new"), || {
+            assert!(new.holds("work"), "saved before switching");
+            assert!(old.holds("work"), "kept until switched");
+            switched.set(true);
+            Ok(())
+        })
+        .unwrap();
+
+        assert!(switched.get());
+        assert!(!old.holds("work"));
+        assert_same(&new.load("work").unwrap().unwrap(), &tokens("new"));
+    }
+
+    #[test]
+    fn keeps_old_tokens_when_saving_fails() {
+        let old = MockStore::new(StoreKind::File);
+        let new = MockStore::failing(StoreKind::SecretService);
+        old.save("work", &tokens("old")).unwrap();
+
+        let result = move_tokens(&new, Some(&old), "work", &tokens("new"), || {
+            panic!("the profile must not switch to a store without its tokens")
+        });
+
+        assert!(matches!(result, Err(CliError::CredentialStoreError(_))));
+        assert_same(&old.load("work").unwrap().unwrap(), &tokens("old"));
+    }
+
+    #[test]
+    fn keeps_old_tokens_when_switching_fails() {
+        let old = MockStore::new(StoreKind::File);
+        let new = MockStore::new(StoreKind::SecretService);
+        old.save("work", &tokens("old")).unwrap();
+
+        let result = move_tokens(&new, Some(&old), "work", &tokens("new"), || {
+            Err(CliError::ConfigFileInvalid("disk full".to_string()))
+        });
+
+        assert!(result.is_err());
+        assert!(old.holds("work"));
+    }
+
+    #[test]
+    fn updates_tokens_in_the_same_store() {
+        let store = MockStore::new(StoreKind::SecretService);
+        let previous = MockStore::new(StoreKind::SecretService);
+        previous.save("work", &tokens("old")).unwrap();
+
+        move_tokens(&store, Some(&previous), "work", &tokens("new"), || Ok(())).unwrap();
+        // Same kind means same backend in practice, so nothing is deleted
+        assert!(previous.holds("work"));
+        assert_same(&store.load("work").unwrap().unwrap(), &tokens("new"));
+    }
+
+    #[test]
+    fn file_store_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        let store = FileStore::new(&path);
+
+        assert!(store.load("default").unwrap().is_none());
+        store.save("default", &tokens("abc")).unwrap();
+        assert_same(&store.load("default").unwrap().unwrap(), &tokens("abc"));
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+
+        store.delete("default").unwrap();
+        assert!(store.load("default").unwrap().is_none());
+        // The profile itself survives logging out
+        let config: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(config["profiles"]["default"]["user_id"], "user-1");
+    }
+
+    #[test]
+    fn write_private_replaces_loose_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        fs::write(&path, "old contents").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        }
+
+        write_private(&path, "new").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+        // No temp files left behind
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn nested_locks_share_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.lock");
+
+        let outer = lock_file(&path).unwrap();
+        // Would deadlock if the inner lock opened the file again
+        let inner = lock_file(&path).unwrap();
+        drop(inner);
+        assert!(fs::File::open(&path).unwrap().try_lock().is_err());
+
+        drop(outer);
+        assert!(fs::File::open(&path).unwrap().try_lock().is_ok());
+    }
+
+    #[test]
+    fn encrypted_file_store_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        let store = EncryptedFileStore::with_passphrase(&path, "correct horse");
+
+        assert!(store.load("default").unwrap().is_none());
+        store.save("default", &tokens("secret-access")).unwrap();
+        assert_same(&store.load("default").unwrap().unwrap(), &tokens("secret-access"));
+
+        let contents = fs::read_to_string(&path).unwrap();
+        assert!(!contents.contains("secret-access"));
+        assert!(contents.contains("encrypted_tokens"));
+
+        let wrong = EncryptedFileStore::with_passphrase(&path, "battery staple");
+        assert!(matches!(wrong.load("default"), Err(CliError::CredentialStoreError(_))));
+
+        store.delete("default").unwrap();
+        assert!(store.load("default").unwrap().is_none());
+    }
+
+    #[test]
+    fn detects_tampering() {
+        let sealed = encrypt("passphrase", b"tokens").unwrap();
+        assert_eq!(decrypt("passphrase", &sealed).unwrap(), b"tokens");
+
+        let mut tampered = sealed.clone();
+        let mut ciphertext = STANDARD.decode(sealed["ciphertext"].as_str().unwrap()).unwrap();
+        ciphertext[0] ^= 1;
+        tampered["ciphertext"] = serde_json::Value::String(STANDARD.encode(ciphertext));
+        assert!(decrypt("passphrase", &tampered).is_err());
+    }
+
+    #[test]
+    fn names_store_kinds() {
+        for kind in [StoreKind::File, StoreKind::EncryptedFile, StoreKind::SecretService] {
+            assert_eq!(StoreKind::parse(kind.as_str()), Some(kind));
+        }
+        assert_eq!(StoreKind::parse("keychain"), None);
+    }
+}
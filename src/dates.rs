@@ -0,0 +1,319 @@
+//! Dates as people type them.
+//!
+//! [`parse_date`] reads a single day: `2026-10-23`, `today`, `tomorrow`,
+//! `yesterday`, a weekday (`fri` is the coming Friday, `next fri` the Friday
+//! of next week, `last fri` the one before today) or an offset such as `+3d`,
+//! `-1w` or `+2m`. [`parse_range`] also reads spans: `this week`, `next
+//! month`, `last week`, a month (`2026-10`) or an ISO week (`2026-W43`).
+//! Weeks start on Monday.
+
+use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
+
+use crate::error::CliError;
+
+/// The format the AnyList API expects.
+pub fn format_date(date: NaiveDate) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
+
+pub fn today() -> NaiveDate {
+    chrono::Local::now().date_naive()
+}
+
+fn invalid(text: &str) -> CliError {
+    CliError::InvalidDate(text.to_string())
+}
+
+fn parse_weekday(word: &str) -> Option<Weekday> {
+    Some(match word {
+        "mon" | "monday" => Weekday::Mon,
+        "tue" | "tues" | "tuesday" => Weekday::Tue,
+        "wed" | "wednesday" => Weekday::Wed,
+        "thu" | "thur" | "thurs" | "thursday" => Weekday::Thu,
+        "fri" | "friday" => Weekday::Fri,
+        "sat" | "saturday" => Weekday::Sat,
+        "sun" | "sunday" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+/// The Monday of the week `date` falls in.
+pub fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Days::new(u64::from(date.weekday().num_days_from_monday()))
+}
+
+pub fn month_start(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).expect("every month has a first day")
+}
+
+/// The last day of the month starting on `start`, if it's in chrono's range.
+pub fn month_end(start: NaiveDate) -> Option<NaiveDate> {
+    start.checked_add_months(Months::new(1))?.pred_opt()
+}
+
+/// A step between dates: "3d", "2w", "1m", or "day", "week", "fortnight",
+/// "month".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interval {
+    Days(u32),
+    Weeks(u32),
+    Months(u32),
+}
+
+impl Interval {
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim().to_lowercase();
+        match text.as_str() {
+            "day" | "daily" => return Some(Interval::Days(1)),
+            "week" | "weekly" => return Some(Interval::Weeks(1)),
+            "fortnight" | "fortnightly" => return Some(Interval::Weeks(2)),
+            "month" | "monthly" => return Some(Interval::Months(1)),
+            _ => {}
+        }
+        let unit = text.chars().last()?;
+        let amount: u32 = text[..text.len() - unit.len_utf8()].parse().ok()?;
+        match unit {
+            'd' => Some(Interval::Days(amount)),
+            'w' => Some(Interval::Weeks(amount)),
+            'm' => Some(Interval::Months(amount)),
+            _ => None,
+        }
+    }
+
+    /// Whether the step is zero, going nowhere.
+    pub fn is_empty(self) -> bool {
+        matches!(self, Interval::Days(0) | Interval::Weeks(0) | Interval::Months(0))
+    }
+
+    /// `date` moved `times` steps forward. Months keep the day where they
+    /// can: a month after January 31 is the last day of February.
+    pub fn forward(self, date: NaiveDate, times: u32) -> Option<NaiveDate> {
+        match self {
+            Interval::Days(days) => date.checked_add_days(Days::new(u64::from(days) * u64::from(times))),
+            Interval::Weeks(weeks) => date.checked_add_days(Days::new(u64::from(weeks) * 7 * u64::from(times))),
+            Interval::Months(months) => date.checked_add_months(Months::new(months.checked_mul(times)?)),
+        }
+    }
+
+    /// `date` moved `times` steps back.
+    pub fn back(self, date: NaiveDate, times: u32) -> Option<NaiveDate> {
+        match self {
+            Interval::Days(days) => date.checked_sub_days(Days::new(u64::from(days) * u64::from(times))),
+            Interval::Weeks(weeks) => date.checked_sub_days(Days::new(u64::from(weeks) * 7 * u64::from(times))),
+            Interval::Months(months) => date.checked_sub_months(Months::new(months.checked_mul(times)?)),
+        }
+    }
+}
+
+/// "+3d", "-1w", "+2m"
+fn parse_offset(text: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let (forward, rest) = match text.chars().next()? {
+        '+' => (true, &text[1..]),
+        '-' => (false, &text[1..]),
+        _ => return None,
+    };
+    // Only counted steps: "+week" isn't an offset
+    if !rest.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    let interval = Interval::parse(rest)?;
+    if forward {
+        interval.forward(today, 1)
+    } else {
+        interval.back(today, 1)
+    }
+}
+
+pub fn parse_date(text: &str, today: NaiveDate) -> Result<NaiveDate, CliError> {
+    let normalized = text.trim().to_lowercase();
+    let words: Vec<&str> = normalized.split_whitespace().collect();
+
+    let date = match words.as_slice() {
+        ["today"] => Some(today),
+        ["tomorrow"] => today.succ_opt(),
+        ["yesterday"] => today.pred_opt(),
+        [day] if parse_weekday(day).is_some() => {
+            let weekday = parse_weekday(day).expect("checked above");
+            let ahead = (7 + weekday.num_days_from_monday() - today.weekday().num_days_from_monday()) % 7;
+            today.checked_add_days(Days::new(ahead.into()))
+        }
+        ["next", day] => parse_weekday(day).and_then(|weekday| {
+            week_start(today).checked_add_days(Days::new(7 + u64::from(weekday.num_days_from_monday())))
+        }),
+        ["last", day] => parse_weekday(day).and_then(|weekday| {
+            let back = (7 + today.weekday().num_days_from_monday() - weekday.num_days_from_monday()) % 7;
+            today.checked_sub_days(Days::new(if back == 0 { 7 } else { back.into() }))
+        }),
+        [word] if word.starts_with(['+', '-']) => parse_offset(word, today),
+        [word] => NaiveDate::parse_from_str(word, "%Y-%m-%d").ok(),
+        _ => None,
+    };
+    date.ok_or_else(|| invalid(text))
+}
+
+/// A span of days, first and last included. A single date is a one-day span.
+pub fn parse_range(text: &str, today: NaiveDate) -> Result<(NaiveDate, NaiveDate), CliError> {
+    let normalized = text.trim().to_lowercase();
+    let words: Vec<&str> = normalized.split_whitespace().collect();
+
+    let week = |start: NaiveDate| Some((start, start.checked_add_days(Days::new(6))?));
+    let month = |start: NaiveDate| Some((start, month_end(start)?));
+    let range = match words.as_slice() {
+        ["this", "week"] => week(week_start(today)),
+        ["next", "week"] => week_start(today).checked_add_days(Days::new(7)).and_then(week),
+        ["last", "week"] => week_start(today).checked_sub_days(Days::new(7)).and_then(week),
+        ["this", "month"] => month(month_start(today)),
+        ["next", "month"] => month_start(today).checked_add_months(Months::new(1)).and_then(month),
+        ["last", "month"] => month_start(today).checked_sub_months(Months::new(1)).and_then(month),
+        [word] => parse_iso_week(word).and_then(week).or_else(|| {
+            NaiveDate::parse_from_str(&format!("{}-01", word), "%Y-%m-%d")
+                .ok()
+                .and_then(month)
+        }),
+        _ => None,
+    };
+    match range {
+        Some(range) => Ok(range),
+        None => parse_date(text, today).map(|date| (date, date)),
+    }
+}
+
+/// The Monday of an ISO week such as "2026-w43".
+fn parse_iso_week(word: &str) -> Option<NaiveDate> {
+    let (year, week) = word.split_once("-w")?;
+    NaiveDate::from_isoywd_opt(year.parse().ok()?, week.parse().ok()?, Weekday::Mon)
+}
+
+/// The span covered by command-line values: one expression ("this week",
+/// "next fri"), or a start and an end ("today +6d", "next mon" "next fri").
+pub fn parse_span(words: &[String], today: NaiveDate) -> Result<(NaiveDate, NaiveDate), CliError> {
+    let text = words.join(" ");
+    let (first, last) = match parse_range(&text, today) {
+        Ok(range) => range,
+        Err(err) => (1..words.len())
+            .find_map(|split| {
+                let (start, _) = parse_range(&words[..split].join(" "), today).ok()?;
+                let (_, end) = parse_range(&words[split..].join(" "), today).ok()?;
+                Some((start, end))
+            })
+            .ok_or(err)?,
+    };
+    if last < first {
+        return Err(CliError::InvalidInput(format!(
+            "the end date {} is before the start date {}",
+            format_date(last),
+            format_date(first)
+        )));
+    }
+    Ok((first, last))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A Sunday, so "this week" ends today and plain weekdays look ahead.
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, 18).unwrap()
+    }
+
+    fn day(text: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap()
+    }
+
+    /// Input, and the first and last day it should cover.
+    type Case = (&'static str, &'static str, &'static str);
+
+    const RANGES: &[Case] = &[
+        ("2026-10-23", "2026-10-23", "2026-10-23"),
+        ("today", "2026-10-18", "2026-10-18"),
+        ("Today", "2026-10-18", "2026-10-18"),
+        ("tomorrow", "2026-10-19", "2026-10-19"),
+        ("yesterday", "2026-10-17", "2026-10-17"),
+        ("fri", "2026-10-23", "2026-10-23"),
+        ("monday", "2026-10-19", "2026-10-19"),
+        ("sun", "2026-10-18", "2026-10-18"),
+        ("next fri", "2026-10-23", "2026-10-23"),
+        ("next sun", "2026-10-25", "2026-10-25"),
+        ("last fri", "2026-10-16", "2026-10-16"),
+        ("last sun", "2026-10-11", "2026-10-11"),
+        ("+3d", "2026-10-21", "2026-10-21"),
+        ("-1w", "2026-10-11", "2026-10-11"),
+        ("+2m", "2026-12-18", "2026-12-18"),
+        ("this week", "2026-10-12", "2026-10-18"),
+        ("next week", "2026-10-19", "2026-10-25"),
+        ("last week", "2026-10-05", "2026-10-11"),
+        ("this month", "2026-10-01", "2026-10-31"),
+        ("next month", "2026-11-01", "2026-11-30"),
+        ("last month", "2026-09-01", "2026-09-30"),
+        ("2026-10", "2026-10-01", "2026-10-31"),
+        ("2026-12", "2026-12-01", "2026-12-31"),
+        ("2024-02", "2024-02-01", "2024-02-29"),
+        ("2026-W43", "2026-10-19", "2026-10-25"),
+        ("2026-w43", "2026-10-19", "2026-10-25"),
+        // ISO weeks that cross a year boundary
+        ("2026-W53", "2026-12-28", "2027-01-03"),
+        ("2026-W01", "2025-12-29", "2026-01-04"),
+        ("2027-W01", "2027-01-04", "2027-01-10"),
+    ];
+
+    const INVALID: &[&str] = &[
+        "",
+        "someday",
+        "next",
+        "next year",
+        "fri sat",
+        "+3x",
+        "+week",
+        "3d",
+        "2026-13",
+        "2026-02-30",
+        "2027-W53",
+        "2026-W00",
+        "+99999999d",
+        "+4000000000m",
+    ];
+
+    #[test]
+    fn parses_ranges() {
+        for (text, first, last) in RANGES {
+            assert_eq!(parse_range(text, today()).unwrap(), (day(first), day(last)), "{}", text);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_dates() {
+        for text in INVALID {
+            let err = parse_range(text, today()).unwrap_err();
+            assert!(matches!(err, CliError::InvalidDate(_)), "{:?} gave {:?}", text, err);
+        }
+    }
+
+    #[test]
+    fn single_dates_are_days() {
+        assert_eq!(parse_date("next fri", today()).unwrap(), day("2026-10-23"));
+        assert!(parse_date("this week", today()).is_err());
+    }
+
+    #[test]
+    fn spans_take_a_start_and_an_end() {
+        let span = |words: &[&str]| {
+            let words: Vec<String> = words.iter().map(|word| word.to_string()).collect();
+            parse_span(&words, today())
+        };
+        assert_eq!(span(&["this", "week"]).unwrap(), (day("2026-10-12"), day("2026-10-18")));
+        assert_eq!(span(&["today", "+6d"]).unwrap(), (day("2026-10-18"), day("2026-10-24")));
+        assert_eq!(span(&["next mon", "next fri"]).unwrap(), (day("2026-10-19"), day("2026-10-23")));
+        // A span for the start and end gives the first day of one to the last of the other
+        assert_eq!(span(&["2026-W43", "2026-11"]).unwrap(), (day("2026-10-19"), day("2026-11-30")));
+
+        assert!(matches!(span(&["2026-10-25", "2026-10-19"]), Err(CliError::InvalidInput(_))));
+        assert!(matches!(span(&["someday", "soon"]), Err(CliError::InvalidDate(_))));
+    }
+
+    #[test]
+    fn month_end_stays_in_range() {
+        assert_eq!(month_end(day("2026-02-01")), Some(day("2026-02-28")));
+        assert_eq!(month_end(month_start(NaiveDate::MAX)), None);
+    }
+}
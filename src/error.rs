@@ -6,9 +6,21 @@ pub enum CliError {
     ConfigDirCreationFailed(std::io::Error),
     ConfigFileNotFound,
     ConfigFileInvalid(String),
+    ProfileNotFound(String),
     LoginFailed(String),
+    NotLoggedIn(String),
     PromptCancelled,
     ListNotFound(String),
+    NoMatch(&'static str, String),
+    AmbiguousMatch(&'static str, String),
+    NotCached(String),
+    NoMealCalendar,
+    OfflineUnsupported(String),
+    PartialFailure(usize, usize),
+    InvalidInput(String),
+    InvalidDate(String),
+    CredentialStoreError(String),
+    ImportFailed(String),
     AnyListError(anylist_rs::AnyListError),
     IoError(std::io::Error),
     JsonError(serde_json::Error),
@@ -29,15 +41,60 @@ impl fmt::Display for CliError {
             CliError::ConfigFileInvalid(field) => {
                 write!(f, "Config file is invalid: {} not found. Please run 'anylist login' again.", field)
             }
+            CliError::ProfileNotFound(name) => {
+                write!(f, "Profile '{}' not found. Run 'anylist login --profile {}' to create it.", name, name)
+            }
             CliError::LoginFailed(msg) => {
                 write!(f, "Login failed: {}", msg)
             }
+            CliError::NotLoggedIn(profile) => {
+                write!(f, "Not logged in (profile '{}'). Please run 'anylist login' first.", profile)
+            }
             CliError::PromptCancelled => {
                 write!(f, "Operation cancelled by user")
             }
             CliError::ListNotFound(name) => {
                 write!(f, "List '{}' not found", name)
             }
+            CliError::NoMatch(kind, query) => {
+                write!(f, "No {} matching '{}'", kind, query)
+            }
+            CliError::AmbiguousMatch(kind, detail) => {
+                write!(f, "Ambiguous {} name: {}. Use the ID instead.", kind, detail)
+            }
+            CliError::NotCached(what) => {
+                write!(f, "No offline copy of {}. Run the command once while online to cache it.", what)
+            }
+            CliError::NoMealCalendar => {
+                write!(
+                    f,
+                    "No meal-planning calendar found. Open Meal Planning in the AnyList app once, \
+                     or pass --calendar."
+                )
+            }
+            CliError::OfflineUnsupported(command) => {
+                write!(f, "'{}' is not available with --offline", command)
+            }
+            CliError::PartialFailure(failed, total) => {
+                write!(f, "{} of {} operations failed", failed, total)
+            }
+            CliError::InvalidInput(msg) => {
+                write!(f, "Invalid input: {}", msg)
+            }
+            CliError::InvalidDate(text) => {
+                write!(
+                    f,
+                    "Invalid date: {}. Use YYYY-MM-DD, today, tomorrow, a weekday (fri, next fri), \
+                     an offset (+3d, -1w), this/next/last week or month, 2026-10 or 2026-W43.",
+                    text
+                )
+            }
+            CliError::CredentialStoreError(msg) => {
+                write!(f, "Credential store error: {}", msg)
+            }
+            CliError::ImportFailed(msg) => {
+                write!(f, "Could not import recipe: {}", msg)
+            }
             CliError::AnyListError(err) => {
                 write!(f, "AnyList API error: {}", err)
             }
@@ -0,0 +1,272 @@
+//! Free-text shopping list entries.
+//!
+//! [`parse_item`] splits text like `3x 500g Greek yogurt (plain) #Dairy` into
+//! a name ("Greek yogurt"), a pack count (3), a quantity (500 g), details
+//! ("plain") and a category ("Dairy"). Anything it doesn't recognise stays in
+//! the name, so "7up" or "2% milk" come through untouched.
+
+use crate::quantity::Quantity;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedItem {
+    pub name: String,
+    /// Multiplier written as "3x", "3 x" or a trailing "x3"
+    pub count: Option<u32>,
+    pub quantity: Option<Quantity>,
+    pub details: Option<String>,
+    pub category: Option<String>,
+}
+
+impl ParsedItem {
+    /// The quantity as AnyList stores it: "3 x 500 g", "1/2 cup" or "3".
+    pub fn quantity_text(&self) -> Option<String> {
+        match (self.count, self.quantity) {
+            (Some(count), Some(quantity)) => Some(format!("{} x {}", count, quantity.to_recipe_string())),
+            (Some(count), None) => Some(count.to_string()),
+            (None, Some(quantity)) => Some(quantity.to_recipe_string()),
+            (None, None) => None,
+        }
+    }
+}
+
+pub fn parse_item(text: &str) -> ParsedItem {
+    let (rest, details) = take_details(text);
+
+    let mut category = None;
+    let mut words = Vec::new();
+    for word in rest.split_whitespace() {
+        match word.strip_prefix('#') {
+            // "#Frozen_Foods" for categories with spaces
+            Some(tag) if !tag.is_empty() => category = Some(tag.replace('_', " ")),
+            _ => words.push(word),
+        }
+    }
+
+    let mut count = None;
+    if let Some((found, used)) = leading_count(&words) {
+        count = Some(found);
+        words.drain(..used);
+    } else if let Some(found) = words.last().and_then(|word| trailing_count(word))
+        && words.len() > 1
+    {
+        count = Some(found);
+        words.pop();
+    }
+
+    let mut quantity = None;
+    if words.len() > 1
+        && let Some((found, used)) = Quantity::parse_prefix(&words)
+    {
+        quantity = Some(found);
+        words.drain(..used);
+        // "2 cups of flour"
+        if words.len() > 1 && words[0].eq_ignore_ascii_case("of") {
+            words.remove(0);
+        }
+    }
+
+    let name = words.join(" ");
+    if name.trim().is_empty() {
+        // Nothing left for a name; keep the text as written
+        return ParsedItem {
+            name: text.trim().to_string(),
+            count: None,
+            quantity: None,
+            details: None,
+            category: None,
+        };
+    }
+
+    ParsedItem {
+        name,
+        count,
+        quantity,
+        details,
+        category,
+    }
+}
+
+/// Take the last "(...)" group out of the text as details.
+fn take_details(text: &str) -> (String, Option<String>) {
+    if let (Some(open), Some(close)) = (text.rfind('('), text.rfind(')'))
+        && open < close
+    {
+        let details = text[open + 1..close].trim();
+        let rest = format!("{} {}", &text[..open], &text[close + 1..]);
+        if details.is_empty() {
+            return (rest, None);
+        }
+        return (rest, Some(details.to_string()));
+    }
+    (text.to_string(), None)
+}
+
+/// "3x", "3×" or "3 x" at the start.
+fn leading_count(words: &[&str]) -> Option<(u32, usize)> {
+    let first = *words.first()?;
+    if let Some(number) = first.strip_suffix(['x', 'X', '×'])
+        && let Ok(count) = number.parse::<u32>()
+    {
+        return (words.len() > 1).then_some((count, 1));
+    }
+    if words.len() > 2
+        && matches!(words[1], "x" | "X" | "×")
+        && let Ok(count) = first.parse::<u32>()
+    {
+        return Some((count, 2));
+    }
+    None
+}
+
+/// "x3" or "×3" at the end.
+fn trailing_count(word: &str) -> Option<u32> {
+    word.strip_prefix(['x', 'X', '×'])?.parse().ok()
+}
+
+/// A key for spotting the same item written differently: "Tomatoes",
+/// "tomato" and " TOMATO " all give "tomato".
+pub fn normalize_name(name: &str) -> String {
+    let mut words = lowercase_words(name);
+    if let Some(last) = words.last_mut() {
+        *last = singular(last);
+    }
+    words.join(" ")
+}
+
+/// Like [`normalize_name`], but every word is made singular, for finding
+/// words in longer text: "Tomatoes and chillies, diced" gives
+/// "tomato and chilly diced".
+pub fn normalize_words(text: &str) -> String {
+    lowercase_words(text)
+        .iter()
+        .map(|word| singular(word))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn lowercase_words(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn singular(word: &str) -> String {
+    if word.len() <= 3 {
+        return word.to_string();
+    }
+    if let Some(stem) = word.strip_suffix("ies") {
+        return format!("{}y", stem);
+    }
+    if let Some(stem) = word.strip_suffix("oes") {
+        return format!("{}o", stem);
+    }
+    if word.ends_with('s') && !word.ends_with("ss") {
+        return word[..word.len() - 1].to_string();
+    }
+    word.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Input, then the expected name, quantity text, details and category.
+    type Case = (&'static str, &'static str, Option<&'static str>, Option<&'static str>, Option<&'static str>);
+
+    const CASES: &[Case] = &[
+        // Plain names
+        ("apples", "apples", None, None, None),
+        ("  Greek yogurt  ", "Greek yogurt", None, None, None),
+        // Quantities
+        ("2 apples", "apples", Some("2"), None, None),
+        ("1.5 kg potatoes", "potatoes", Some("1.5 kg"), None, None),
+        ("2 lbs apples", "apples", Some("2 lbs"), None, None),
+        ("500g Greek yogurt", "Greek yogurt", Some("500 g"), None, None),
+        ("2lbs ground beef", "ground beef", Some("2 lbs"), None, None),
+        ("12 oz. pasta", "pasta", Some("12 oz"), None, None),
+        ("2 fl oz vanilla", "vanilla", Some("2 fl oz"), None, None),
+        ("1 dozen eggs", "eggs", Some("1 dozen"), None, None),
+        ("2 cans chickpeas", "chickpeas", Some("2 cans"), None, None),
+        ("2 cups of flour", "flour", Some("2 cups"), None, None),
+        // Fractions
+        ("1/2 cup sugar", "sugar", Some("1/2 cup"), None, None),
+        ("1 1/2 cups flour", "flour", Some("1 1/2 cups"), None, None),
+        ("½ lb butter", "butter", Some("1/2 lb"), None, None),
+        ("1½ cups milk", "milk", Some("1 1/2 cups"), None, None),
+        ("2 ¾ cups stock", "stock", Some("2 3/4 cups"), None, None),
+        ("2x 1/2 lb butter", "butter", Some("2 x 1/2 lb"), None, None),
+        ("0.5 kg flour", "flour", Some("0.5 kg"), None, None),
+        // Multipliers
+        ("3x 500g Greek yogurt", "Greek yogurt", Some("3 x 500 g"), None, None),
+        ("3X milk", "milk", Some("3"), None, None),
+        ("2× bread", "bread", Some("2"), None, None),
+        ("3 x tomatoes", "tomatoes", Some("3"), None, None),
+        ("eggs x12", "eggs", Some("12"), None, None),
+        ("yogurt 500g x2", "yogurt 500g", Some("2"), None, None),
+        // Trailing notes and categories
+        ("milk (semi-skimmed)", "milk", None, Some("semi-skimmed"), None),
+        ("2 lbs apples (Honeycrisp)", "apples", Some("2 lbs"), Some("Honeycrisp"), None),
+        ("bread ()", "bread", None, None, None),
+        ("peas #Frozen", "peas", None, None, Some("Frozen")),
+        ("peas #Frozen_Foods", "peas", None, None, Some("Frozen Foods")),
+        (
+            "3x 500g Greek yogurt (plain) #Dairy",
+            "Greek yogurt",
+            Some("3 x 500 g"),
+            Some("plain"),
+            Some("Dairy"),
+        ),
+        // Names with digits stay whole
+        ("7up", "7up", None, None, None),
+        ("2% milk", "2% milk", None, None, None),
+        ("V8 juice", "V8 juice", None, None, None),
+        ("Route 66 chips", "Route 66 chips", None, None, None),
+        // Nothing left for a name: the text is kept as written
+        ("3", "3", None, None, None),
+        ("2 cans", "cans", Some("2"), None, None),
+        ("x2", "x2", None, None, None),
+        ("500g", "500g", None, None, None),
+        ("#Dairy", "#Dairy", None, None, None),
+    ];
+
+    #[test]
+    fn parses_items() {
+        for &(input, name, quantity, details, category) in CASES {
+            let parsed = parse_item(input);
+            assert_eq!(parsed.name, name, "name of {:?}", input);
+            assert_eq!(parsed.quantity_text().as_deref(), quantity, "quantity of {:?}", input);
+            assert_eq!(parsed.details.as_deref(), details, "details of {:?}", input);
+            assert_eq!(parsed.category.as_deref(), category, "category of {:?}", input);
+        }
+    }
+
+    #[test]
+    fn counts_and_quantities_are_separate() {
+        let parsed = parse_item("3x 500g Greek yogurt");
+        assert_eq!(parsed.count, Some(3));
+        let quantity = parsed.quantity.expect("quantity");
+        assert_eq!(quantity.amount, 500.0);
+        assert_eq!(quantity.unit.map(|unit| unit.symbol), Some("g"));
+    }
+
+    #[test]
+    fn normalizes_names() {
+        for (name, key) in [
+            ("Tomatoes", "tomato"),
+            (" TOMATO ", "tomato"),
+            ("Cherries", "cherry"),
+            ("potatoes", "potato"),
+            ("Greek yogurt", "greek yogurt"),
+            ("Swiss", "swiss"),
+            ("peas", "pea"),
+            ("gas", "gas"),
+            ("ice-cream", "ice cream"),
+        ] {
+            assert_eq!(normalize_name(name), key, "{:?}", name);
+        }
+        assert_eq!(normalize_name("Tomatoes and chillies"), "tomatoes and chilly");
+        assert_eq!(normalize_words("Tomatoes and chillies, diced"), "tomato and chilly diced");
+    }
+}
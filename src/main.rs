@@ -1,10 +1,27 @@
 mod auth;
+mod cache;
 mod commands;
+mod credentials;
+mod dates;
 mod error;
+mod item_parser;
+mod meal_calendar;
+mod meal_suggest;
+mod output;
+mod quantity;
+mod recipe_export;
+mod recipe_import;
+mod recipe_search;
+mod recipe_text;
+mod resolve;
+mod shopping;
 
-use clap::Command;
-use commands::{categories, items, list, login, meal_plans, recipes, stores, tail};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use commands::{
+    categories, items, list, login, logout, meal_plans, profile, recipes, shop, stores, tail, whoami,
+};
 use error::CliError;
+use output::OutputFormat;
 use std::process;
 
 #[tokio::main]
@@ -15,50 +32,134 @@ async fn main() {
     }
 }
 
-async fn run() -> Result<(), CliError> {
-    let matches = Command::new("anylist")
+/// The full command line: global flags and every subcommand.
+fn cli() -> Command {
+    Command::new("anylist")
         .version(env!("CARGO_PKG_VERSION"))
         .author(env!("CARGO_PKG_AUTHORS"))
         .about("Manage your AnyList shopping lists, items, recipes, meal plans, and more.")
         .subcommand_required(true)
         .arg_required_else_help(true)
+        .arg(OutputFormat::arg())
+        .arg(
+            Arg::new("profile")
+                .long("profile")
+                .global(true)
+                .env("ANYLIST_PROFILE")
+                .value_name("NAME")
+                .help("Account profile to use (see 'anylist profile list')"),
+        )
+        .arg(
+            Arg::new("offline")
+                .long("offline")
+                .global(true)
+                .action(ArgAction::SetTrue)
+                .help("Read from the local cache; item add/check/uncheck are queued until the next online command"),
+        )
         .subcommand(login::command())
+        .subcommand(logout::command())
+        .subcommand(whoami::command())
+        .subcommand(profile::command())
         .subcommand(list::command())
         .subcommand(items::command())
         .subcommand(stores::command())
         .subcommand(categories::command())
         .subcommand(meal_plans::command())
         .subcommand(recipes::command())
+        .subcommand(shop::command())
         .subcommand(tail::command())
-        .get_matches();
+}
+
+async fn run() -> Result<(), CliError> {
+    let matches = cli().get_matches();
+
+    let format = OutputFormat::from_matches(&matches);
+    if let Some(profile) = matches.get_one::<String>("profile") {
+        auth::select_profile(profile);
+    }
+
+    if matches.get_flag("offline") {
+        check_offline_support(&matches)?;
+    } else if !matches!(matches.subcommand_name(), Some("login" | "logout" | "whoami" | "profile")) {
+        cache::sync_queued_changes().await;
+    }
 
     match matches.subcommand() {
         Some(("login", sub_matches)) => {
-            login::exec_command(sub_matches).await?;
+            login::exec_command(sub_matches, format).await?;
+        }
+        Some(("logout", sub_matches)) => {
+            logout::exec_command(sub_matches, format).await?;
+        }
+        Some(("whoami", sub_matches)) => {
+            whoami::exec_command(sub_matches, format).await?;
+        }
+        Some(("profile", sub_matches)) => {
+            profile::exec_command(sub_matches, format).await?;
         }
         Some(("list", sub_matches)) => {
-            list::exec_command(sub_matches).await?;
+            list::exec_command(sub_matches, format).await?;
         }
         Some(("item", sub_matches)) => {
-            items::exec_command(sub_matches).await?;
+            items::exec_command(sub_matches, format).await?;
         }
         Some(("store", sub_matches)) => {
-            stores::exec_command(sub_matches).await?;
+            stores::exec_command(sub_matches, format).await?;
         }
         Some(("category", sub_matches)) => {
-            categories::exec_command(sub_matches).await?;
+            categories::exec_command(sub_matches, format).await?;
         }
         Some(("meal-plan", sub_matches)) => {
-            meal_plans::exec_command(sub_matches).await?;
+            meal_plans::exec_command(sub_matches, format).await?;
         }
         Some(("recipe", sub_matches)) => {
-            recipes::exec_command(sub_matches).await?;
+            recipes::exec_command(sub_matches, format).await?;
+        }
+        Some(("shop", sub_matches)) => {
+            shop::exec_command(sub_matches).await?;
         }
         Some(("tail", sub_matches)) => {
-            tail::exec_command(sub_matches).await?;
+            tail::exec_command(sub_matches, format).await?;
         }
         _ => unreachable!("clap should prevent this due to subcommand_required(true)"),
     }
 
     Ok(())
 }
+
+/// Commands that can run from the local cache with `--offline`.
+const OFFLINE_COMMANDS: &[&str] = &[
+    "logout",
+    "whoami",
+    "list",
+    "list get",
+    "item add",
+    "item check",
+    "item uncheck",
+    "recipe",
+    "recipe list",
+    "recipe get",
+    "recipe search",
+    "recipe export",
+    "meal-plan list",
+    "meal-plan week",
+    "meal-plan month",
+    "meal-plan calendars",
+    "meal-plan labels",
+];
+
+fn check_offline_support(matches: &ArgMatches) -> Result<(), CliError> {
+    let mut path = Vec::new();
+    let mut current = matches;
+    while let Some((name, sub_matches)) = current.subcommand() {
+        path.push(name);
+        current = sub_matches;
+    }
+
+    let command = path.join(" ");
+    if OFFLINE_COMMANDS.contains(&command.as_str()) {
+        Ok(())
+    } else {
+        Err(CliError::OfflineUnsupported(command))
+    }
+}
@@ -0,0 +1,63 @@
+//! The meal-planning calendar: its ID and meal labels.
+//!
+//! `anylist_rs` returns events with bare label IDs and doesn't expose the
+//! calendar or its labels, so they are read from the raw user data. Each
+//! account has a single meal-planning calendar.
+
+use anylist_rs::AnyListClient;
+use serde::{Deserialize, Serialize};
+
+use crate::error::CliError;
+
+/// A meal label such as "Breakfast" or "Dinner".
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MealLabel {
+    pub id: String,
+    pub name: String,
+    pub sort_index: i32,
+    /// "#RRGGBB"
+    #[serde(default)]
+    pub color: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MealCalendar {
+    pub id: String,
+    /// In the order the AnyList apps show them
+    pub labels: Vec<MealLabel>,
+}
+
+impl MealCalendar {
+    pub fn label_name(&self, label_id: &str) -> Option<&str> {
+        self.labels
+            .iter()
+            .find(|label| label.id == label_id)
+            .map(|label| label.name.as_str())
+    }
+}
+
+/// The account's meal-planning calendar, or None for accounts that have
+/// never used meal planning.
+pub async fn fetch(client: &AnyListClient) -> Result<Option<MealCalendar>, CliError> {
+    let data = client.get_user_data().await?;
+    let Some(response) = data.meal_planning_calendar_response else {
+        return Ok(None);
+    };
+
+    let mut labels: Vec<MealLabel> = response
+        .labels
+        .into_iter()
+        .map(|label| MealLabel {
+            name: label.name.unwrap_or_else(|| label.identifier.clone()),
+            id: label.identifier,
+            sort_index: label.sort_index.unwrap_or(i32::MAX),
+            color: label.hex_color,
+        })
+        .collect();
+    labels.sort_by_key(|label| label.sort_index);
+
+    Ok(Some(MealCalendar {
+        id: response.calendar_id,
+        labels,
+    }))
+}
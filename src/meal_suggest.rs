@@ -0,0 +1,233 @@
+//! Picking recipes for a meal plan.
+//!
+//! [`suggest`] gives each day a recipe that passes the filters and hasn't
+//! been planned recently or picked for another day, choosing at random with
+//! better-rated recipes more likely. Weekdays can have a tighter time limit,
+//! so they are filled first. The same seed and recipes give the same plan.
+
+use anylist_rs::Recipe;
+use chrono::{Datelike, NaiveDate};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::HashSet;
+
+use crate::recipe_search::{total_minutes, RecipeQuery};
+
+#[derive(Debug, Clone, Default)]
+pub struct MealConstraints {
+    /// Ingredient, rating and time filters for every day
+    pub query: RecipeQuery,
+    /// Longest prep plus cook time from Monday to Friday, in minutes
+    pub weekday_max_minutes: Option<i32>,
+    /// IDs of recipes planned recently, which won't be picked again
+    pub recent: HashSet<String>,
+}
+
+fn is_weekday(date: NaiveDate) -> bool {
+    date.weekday().num_days_from_monday() < 5
+}
+
+/// Unrated recipes count as three stars.
+fn weight(recipe: &Recipe) -> u32 {
+    recipe.rating().unwrap_or(3).clamp(0, 5) as u32 + 1
+}
+
+impl MealConstraints {
+    fn fits(&self, recipe: &Recipe, date: NaiveDate) -> bool {
+        !is_weekday(date)
+            // Recipes without times can't be shown to fit
+            || self
+                .weekday_max_minutes
+                .is_none_or(|max| total_minutes(recipe).is_some_and(|minutes| minutes <= max))
+    }
+}
+
+/// A recipe for each day, in the order of `days`, or None for days no
+/// remaining recipe fits.
+pub fn suggest<'a>(
+    recipes: &'a [Recipe],
+    days: &[NaiveDate],
+    constraints: &MealConstraints,
+    rng: &mut impl Rng,
+) -> Vec<(NaiveDate, Option<&'a Recipe>)> {
+    // Sorted so that the seed alone decides the plan, whatever order the
+    // recipes arrive in
+    let mut pool: Vec<&Recipe> = recipes
+        .iter()
+        .filter(|recipe| constraints.query.matches(recipe) && !constraints.recent.contains(recipe.id()))
+        .collect();
+    pool.sort_by(|a, b| a.id().cmp(b.id()));
+
+    let mut order: Vec<usize> = (0..days.len()).collect();
+    order.sort_by_key(|&index| (!is_weekday(days[index]), days[index]));
+
+    let mut plan: Vec<(NaiveDate, Option<&Recipe>)> = days.iter().map(|&date| (date, None)).collect();
+    let mut picked: HashSet<&str> = HashSet::new();
+    for index in order {
+        let date = days[index];
+        let candidates: Vec<&Recipe> = pool
+            .iter()
+            .copied()
+            .filter(|recipe| !picked.contains(recipe.id()) && constraints.fits(recipe, date))
+            .collect();
+        if let Ok(&recipe) = candidates.choose_weighted(rng, |recipe| weight(recipe)) {
+            picked.insert(recipe.id());
+            plan[index].1 = Some(recipe);
+        }
+    }
+    plan
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Days;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use serde_json::json;
+
+    fn recipe(id: &str, rating: Option<i32>, minutes: Option<i32>) -> Recipe {
+        serde_json::from_value(json!({
+            "id": id,
+            "name": format!("Recipe {}", id),
+            "ingredients": [],
+            "preparation_steps": [],
+            "note": null,
+            "source_name": null,
+            "source_url": null,
+            "servings": null,
+            "prep_time": null,
+            "cook_time": minutes.map(|minutes| minutes * 60),
+            "rating": rating,
+            "photo_urls": []
+        }))
+        .expect("valid recipe")
+    }
+
+    fn recipes() -> Vec<Recipe> {
+        vec![
+            recipe("a", Some(5), Some(20)),
+            recipe("b", Some(4), Some(45)),
+            recipe("c", None, Some(30)),
+            recipe("d", Some(1), None),
+            recipe("e", Some(3), Some(90)),
+            recipe("f", Some(5), Some(15)),
+            recipe("g", Some(2), Some(25)),
+            recipe("h", Some(4), Some(60)),
+        ]
+    }
+
+    /// A week from Monday 2026-10-19.
+    fn week() -> Vec<NaiveDate> {
+        let monday = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        (0..7).map(|offset| monday + Days::new(offset)).collect()
+    }
+
+    fn ids(plan: &[(NaiveDate, Option<&Recipe>)]) -> Vec<Option<String>> {
+        plan.iter().map(|(_, recipe)| recipe.map(|recipe| recipe.id().to_string())).collect()
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_plan() {
+        let recipes = recipes();
+        let mut reversed = recipes.clone();
+        reversed.reverse();
+        let constraints = MealConstraints::default();
+
+        let plan = suggest(&recipes, &week(), &constraints, &mut StdRng::seed_from_u64(7));
+        let again = suggest(&recipes, &week(), &constraints, &mut StdRng::seed_from_u64(7));
+        let shuffled = suggest(&reversed, &week(), &constraints, &mut StdRng::seed_from_u64(7));
+        assert_eq!(ids(&plan), ids(&again));
+        assert_eq!(ids(&plan), ids(&shuffled), "the order recipes arrive in doesn't matter");
+
+        let dates: Vec<NaiveDate> = plan.iter().map(|(date, _)| *date).collect();
+        assert_eq!(dates, week());
+    }
+
+    #[test]
+    fn picks_each_recipe_once() {
+        let recipes = recipes();
+        for seed in 0..20 {
+            let plan = suggest(&recipes, &week(), &MealConstraints::default(), &mut StdRng::seed_from_u64(seed));
+            let mut picked: Vec<String> = ids(&plan).into_iter().map(|id| id.expect("enough recipes")).collect();
+            picked.sort();
+            picked.dedup();
+            assert_eq!(picked.len(), 7, "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn weekdays_keep_to_their_time_limit() {
+        let recipes = recipes();
+        let constraints = MealConstraints {
+            weekday_max_minutes: Some(30),
+            ..MealConstraints::default()
+        };
+        for seed in 0..20 {
+            let plan = suggest(&recipes, &week(), &constraints, &mut StdRng::seed_from_u64(seed));
+            let weekdays: Vec<&Recipe> = plan
+                .iter()
+                .filter(|(date, _)| is_weekday(*date))
+                .filter_map(|(_, recipe)| *recipe)
+                .collect();
+            // Only four recipes take 30 minutes or less, so one weekday is left open
+            assert_eq!(weekdays.len(), 4, "seed {}", seed);
+            for recipe in weekdays {
+                assert!(total_minutes(recipe).is_some_and(|minutes| minutes <= 30), "seed {}", seed);
+            }
+            assert!(plan.iter().filter(|(date, _)| !is_weekday(*date)).all(|(_, recipe)| recipe.is_some()));
+        }
+    }
+
+    #[test]
+    fn weekdays_without_a_fit_stay_empty() {
+        let recipes = vec![recipe("quick", Some(5), Some(10)), recipe("slow", Some(5), Some(120))];
+        let constraints = MealConstraints {
+            weekday_max_minutes: Some(30),
+            ..MealConstraints::default()
+        };
+        let plan = suggest(&recipes, &week(), &constraints, &mut StdRng::seed_from_u64(1));
+        let filled: Vec<(NaiveDate, &str)> = plan
+            .iter()
+            .filter_map(|(date, recipe)| recipe.map(|recipe| (*date, recipe.id())))
+            .collect();
+        // Monday is filled first with the only quick recipe; the slow one
+        // goes to the weekend
+        assert_eq!(filled.len(), 2);
+        assert_eq!(filled[0], (week()[0], "quick"));
+        assert!(!is_weekday(filled[1].0));
+        assert_eq!(filled[1].1, "slow");
+    }
+
+    #[test]
+    fn recent_recipes_are_not_repeated() {
+        let recipes = recipes();
+        let constraints = MealConstraints {
+            recent: ["a", "b", "c"].into_iter().map(str::to_string).collect(),
+            ..MealConstraints::default()
+        };
+        for seed in 0..20 {
+            let plan = suggest(&recipes, &week(), &constraints, &mut StdRng::seed_from_u64(seed));
+            let picked: Vec<String> = ids(&plan).into_iter().flatten().collect();
+            assert_eq!(picked.len(), 5, "only five recipes are left");
+            assert!(picked.iter().all(|id| !["a", "b", "c"].contains(&id.as_str())), "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn filters_apply_to_every_day() {
+        let recipes = recipes();
+        let constraints = MealConstraints {
+            query: RecipeQuery {
+                min_rating: Some(4),
+                ..RecipeQuery::default()
+            },
+            ..MealConstraints::default()
+        };
+        let plan = suggest(&recipes, &week(), &constraints, &mut StdRng::seed_from_u64(3));
+        let picked: Vec<String> = ids(&plan).into_iter().flatten().collect();
+        let mut sorted = picked.clone();
+        sorted.sort();
+        assert_eq!(sorted, vec!["a", "b", "f", "h"]);
+    }
+}
@@ -0,0 +1,166 @@
+//! Calendar grids for table output.
+//!
+//! [`column_width`] shares the available width between the columns, or
+//! returns None when they would be too narrow to read, so the caller can
+//! print a list instead. [`write_grid`] wraps cell text to that width.
+
+use std::io::{self, Write};
+
+/// Narrowest column worth drawing
+const MIN_COLUMN_WIDTH: usize = 8;
+/// Widest column, so one long recipe name doesn't stretch the whole grid
+const MAX_COLUMN_WIDTH: usize = 24;
+
+pub struct GridRow {
+    pub heading: String,
+    /// One cell per column, each holding any number of entries
+    pub cells: Vec<Vec<String>>,
+}
+
+pub fn width_of(text: &str) -> usize {
+    text.chars().count()
+}
+
+/// Wrap text at spaces, cutting words that don't fit on a line of their own.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let word = if width_of(word) > width {
+            let cut: String = word.chars().take(width - 1).collect();
+            cut + "…"
+        } else {
+            word.to_string()
+        };
+        if !line.is_empty() && width_of(&line) + 1 + width_of(&word) > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(&word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// The width of each of `columns` columns next to row headings of
+/// `heading_width`, in `width` characters, given the longest text a cell
+/// would hold.
+pub fn column_width(width: usize, heading_width: usize, columns: usize, longest: usize) -> Option<usize> {
+    // Each column takes its width plus " │ "
+    let available = width.saturating_sub(heading_width) / columns.max(1);
+    let column_width = available
+        .saturating_sub(3)
+        .min(longest.max(MIN_COLUMN_WIDTH))
+        .min(MAX_COLUMN_WIDTH);
+    (column_width >= MIN_COLUMN_WIDTH).then_some(column_width)
+}
+
+/// Write `rows` under `columns`. The column at `highlight` (today, say) gets
+/// a bold heading.
+pub fn write_grid(
+    out: &mut dyn Write,
+    columns: &[String],
+    highlight: Option<usize>,
+    rows: &[GridRow],
+    column_width: usize,
+) -> io::Result<()> {
+    let heading_width = rows.iter().map(|row| width_of(&row.heading)).max().unwrap_or(0);
+
+    let rule = {
+        let mut rule = "─".repeat(heading_width);
+        for _ in columns {
+            rule.push_str(&format!("─┼─{}", "─".repeat(column_width)));
+        }
+        rule
+    };
+
+    let mut header = " ".repeat(heading_width);
+    for (i, column) in columns.iter().enumerate() {
+        let cell = format!("{:<width$}", column, width = column_width);
+        if highlight == Some(i) {
+            header.push_str(&format!(" │ \x1B[1m{}\x1B[0m", cell));
+        } else {
+            header.push_str(&format!(" │ {}", cell));
+        }
+    }
+    writeln!(out, "{}", header.trim_end())?;
+
+    for row in rows {
+        writeln!(out, "{}", rule)?;
+        let cells: Vec<Vec<String>> = row
+            .cells
+            .iter()
+            .map(|entries| entries.iter().flat_map(|entry| wrap(entry, column_width)).collect())
+            .collect();
+        let height = cells.iter().map(Vec::len).max().unwrap_or(0).max(1);
+        for line in 0..height {
+            let heading = if line == 0 { row.heading.as_str() } else { "" };
+            let mut text = format!("{:<width$}", heading, width = heading_width);
+            for cell in &cells {
+                let entry = cell.get(line).map(String::as_str).unwrap_or("");
+                text.push_str(&format!(" │ {:<width$}", entry, width = column_width));
+            }
+            writeln!(out, "{}", text.trim_end())?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shares_the_width_between_columns() {
+        // 7 columns next to 6-character headings
+        assert_eq!(column_width(80, 6, 7, 30), None);
+        assert_eq!(column_width(100, 6, 7, 30), Some(10));
+        assert_eq!(column_width(200, 6, 7, 30), Some(MAX_COLUMN_WIDTH));
+        assert_eq!(column_width(200, 6, 7, 5), Some(MIN_COLUMN_WIDTH));
+        assert_eq!(column_width(0, 0, 0, 0), None);
+    }
+
+    #[test]
+    fn wraps_and_cuts_cell_text() {
+        assert_eq!(wrap("Fish and chips", 8), ["Fish and", "chips"]);
+        assert_eq!(wrap("Spaghetti bolognese", 8), ["Spaghet…", "bologne…"]);
+        assert_eq!(wrap("", 8), Vec::<String>::new());
+    }
+
+    #[test]
+    fn writes_rows_under_columns() {
+        let columns = ["Mon 1".to_string(), "Tue 2".to_string()];
+        let rows = [
+            GridRow {
+                heading: "Lunch".to_string(),
+                cells: vec![vec!["Spaghetti bolognese".to_string()], Vec::new()],
+            },
+            GridRow {
+                heading: "Dinner".to_string(),
+                cells: vec![Vec::new(), vec!["Soup".to_string(), "Supercalifragilistic".to_string()]],
+            },
+            GridRow {
+                heading: "Other".to_string(),
+                cells: vec![Vec::new(), Vec::new()],
+            },
+        ];
+        let mut out = Vec::new();
+        write_grid(&mut out, &columns, Some(1), &rows, 8).unwrap();
+        let lines = [
+            "       │ Mon 1    │ \x1B[1mTue 2   \x1B[0m",
+            "───────┼──────────┼─────────",
+            "Lunch  │ Spaghet… │",
+            "       │ bologne… │",
+            "───────┼──────────┼─────────",
+            "Dinner │          │ Soup",
+            "       │          │ Superca…",
+            "───────┼──────────┼─────────",
+            "Other  │          │",
+        ];
+        assert_eq!(String::from_utf8(out).unwrap(), lines.map(|line| format!("{}\n", line)).concat());
+    }
+}
@@ -0,0 +1,145 @@
+//! Shared rendering layer for command output.
+//!
+//! Commands build records (see [`records`]) and hand them to [`render`], which
+//! prints them in the format selected with the global `--output` flag. The
+//! `json` and `yaml` formats wrap every record in a versioned envelope so
+//! scripts can rely on a stable shape:
+//!
+//! ```json
+//! { "schema_version": 1, "kind": "list", "data": { ... } }
+//! ```
+
+mod grid;
+mod records;
+pub mod yaml;
+
+pub use records::*;
+
+use clap::{Arg, ArgMatches};
+use serde::Serialize;
+use std::io::{self, Write};
+
+use crate::error::CliError;
+
+/// Version of the machine-readable record schema. Bump this whenever a field
+/// is removed or changes meaning; adding fields is backwards compatible.
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// Human-friendly output with headings and terminal styling
+    #[default]
+    Table,
+    /// Undecorated, tab-separated output for `grep`/`cut`/`awk`
+    Plain,
+    Json,
+    Yaml,
+}
+
+impl OutputFormat {
+    /// The global `--output` argument, registered on the root command.
+    pub fn arg() -> Arg {
+        Arg::new("output")
+            .short('o')
+            .long("output")
+            .global(true)
+            .value_name("FORMAT")
+            .value_parser(["table", "plain", "json", "yaml"])
+            .default_value("table")
+            .help("Output format")
+    }
+
+    pub fn from_matches(matches: &ArgMatches) -> Self {
+        match matches.get_one::<String>("output").map(|s| s.as_str()) {
+            Some("plain") => OutputFormat::Plain,
+            Some("json") => OutputFormat::Json,
+            Some("yaml") => OutputFormat::Yaml,
+            _ => OutputFormat::Table,
+        }
+    }
+
+    /// Whether this format is meant to be parsed by other programs. Progress
+    /// and status messages should go to stderr when this is true.
+    pub fn is_structured(self) -> bool {
+        matches!(self, OutputFormat::Json | OutputFormat::Yaml)
+    }
+}
+
+/// Columns available for table output: `$COLUMNS`, the terminal's width, or
+/// 80 when neither is known.
+pub fn terminal_width() -> usize {
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse().ok())
+        .or_else(|| crossterm::terminal::size().ok().map(|(width, _)| usize::from(width)))
+        .unwrap_or(80)
+}
+
+/// A value that can be printed in every [`OutputFormat`].
+pub trait Render: Serialize {
+    /// Stable identifier for the record type, emitted as `kind` in the envelope.
+    const KIND: &'static str;
+
+    fn write_table(&self, out: &mut dyn Write) -> io::Result<()>;
+
+    fn write_plain(&self, out: &mut dyn Write) -> io::Result<()>;
+}
+
+#[derive(Serialize)]
+struct Envelope<'a, T: Serialize> {
+    schema_version: u32,
+    kind: &'static str,
+    data: &'a T,
+}
+
+impl<'a, T: Render> Envelope<'a, T> {
+    fn new(data: &'a T) -> Self {
+        Envelope {
+            schema_version: SCHEMA_VERSION,
+            kind: T::KIND,
+            data,
+        }
+    }
+}
+
+/// Print a record to stdout in the given format.
+pub fn render<T: Render>(format: OutputFormat, value: &T) -> Result<(), CliError> {
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    match format {
+        OutputFormat::Table => value.write_table(&mut out)?,
+        OutputFormat::Plain => value.write_plain(&mut out)?,
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(&mut out, &Envelope::new(value))?;
+            writeln!(out)?;
+        }
+        OutputFormat::Yaml => {
+            let doc = serde_json::to_value(Envelope::new(value))?;
+            out.write_all(yaml::to_string(&doc).as_bytes())?;
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// Render a record as one entry of a stream (used by `tail`).
+///
+/// JSON entries are compact, one per line, so the stream is valid JSON Lines.
+/// YAML entries are separate documents.
+pub fn render_stream_entry<T: Render>(format: OutputFormat, value: &T) -> Result<String, CliError> {
+    let mut buf = Vec::new();
+    match format {
+        OutputFormat::Table => value.write_table(&mut buf)?,
+        OutputFormat::Plain => value.write_plain(&mut buf)?,
+        OutputFormat::Json => {
+            serde_json::to_writer(&mut buf, &Envelope::new(value))?;
+            buf.push(b'\n');
+        }
+        OutputFormat::Yaml => {
+            let doc = serde_json::to_value(Envelope::new(value))?;
+            buf.extend_from_slice(b"---\n");
+            buf.extend_from_slice(yaml::to_string(&doc).as_bytes());
+        }
+    }
+    Ok(String::from_utf8_lossy(&buf).into_owned())
+}
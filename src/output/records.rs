@@ -0,0 +1,1297 @@
+//! Stable output records for AnyList data.
+//!
+//! These mirror the `anylist_rs` types the CLI displays but are owned by the
+//! CLI, so the machine-readable output only changes when [`SCHEMA_VERSION`]
+//! does.
+//!
+//! [`SCHEMA_VERSION`]: super::SCHEMA_VERSION
+
+use anylist_rs::{Ingredient, List, ListItem, MealPlanEvent, Recipe, Store, SyncEvent};
+use chrono::{Days, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Write};
+
+use super::grid::{column_width, width_of, write_grid, GridRow};
+use super::Render;
+use crate::dates;
+use crate::meal_calendar::{MealCalendar, MealLabel};
+
+fn non_empty(s: &str) -> Option<String> {
+    if s.is_empty() {
+        None
+    } else {
+        Some(s.to_string())
+    }
+}
+
+fn opt(s: &Option<String>) -> &str {
+    s.as_deref().unwrap_or("")
+}
+
+// ============================================================================
+// Lists and items
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ListItemRecord {
+    pub id: String,
+    pub list_id: String,
+    pub name: String,
+    pub quantity: Option<String>,
+    pub details: Option<String>,
+    pub category: Option<String>,
+    pub checked: bool,
+}
+
+impl From<&ListItem> for ListItemRecord {
+    fn from(item: &ListItem) -> Self {
+        ListItemRecord {
+            id: item.id().to_string(),
+            list_id: item.list_id().to_string(),
+            name: item.name().to_string(),
+            quantity: item.quantity().map(str::to_string),
+            details: non_empty(item.details()),
+            category: item.category().map(str::to_string),
+            checked: item.is_checked(),
+        }
+    }
+}
+
+impl Render for ListItemRecord {
+    const KIND: &'static str = "list_item";
+
+    fn write_table(&self, out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "{} ({})", self.name, self.id)?;
+        if let Some(q) = &self.quantity {
+            writeln!(out, "  Quantity: {}", q)?;
+        }
+        if let Some(d) = &self.details {
+            writeln!(out, "  Details: {}", d)?;
+        }
+        if let Some(c) = &self.category {
+            writeln!(out, "  Category: {}", c)?;
+        }
+        Ok(())
+    }
+
+    fn write_plain(&self, out: &mut dyn Write) -> io::Result<()> {
+        writeln!(
+            out,
+            "{}\t{}\t{}\t{}\t{}\t{}",
+            self.id,
+            if self.checked { "x" } else { " " },
+            self.name,
+            opt(&self.quantity),
+            opt(&self.details),
+            opt(&self.category)
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ListRecord {
+    pub id: String,
+    pub name: String,
+    pub items: Vec<ListItemRecord>,
+}
+
+impl From<&List> for ListRecord {
+    fn from(list: &List) -> Self {
+        ListRecord {
+            id: list.id().to_string(),
+            name: list.name().to_string(),
+            items: list.items().iter().map(ListItemRecord::from).collect(),
+        }
+    }
+}
+
+impl Render for ListRecord {
+    const KIND: &'static str = "list";
+
+    fn write_table(&self, out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "\n{}", self.name)?;
+        writeln!(out, "{}", "=".repeat(self.name.len()))?;
+        writeln!(out)?;
+
+        // Display unchecked items
+        let mut unchecked: Vec<&ListItemRecord> =
+            self.items.iter().filter(|item| !item.checked).collect();
+        if !unchecked.is_empty() {
+            unchecked.sort_by_key(|item| item.name.to_lowercase());
+
+            for item in unchecked {
+                write!(out, "  [ ] \x1B[1m{}\x1B[0m ({})", item.name, item.id)?;
+                if let Some(qty) = &item.quantity {
+                    write!(out, " ({})", qty)?;
+                }
+                if let Some(details) = &item.details {
+                    write!(out, " - {}", details)?;
+                }
+                if let Some(cat) = &item.category {
+                    write!(out, " [{}]", cat)?;
+                }
+                writeln!(out)?;
+            }
+        } else {
+            writeln!(out, "  (no items)")?;
+        }
+
+        // Display checked items
+        let checked: Vec<&ListItemRecord> = self.items.iter().filter(|item| item.checked).collect();
+        if !checked.is_empty() {
+            writeln!(out, "\nCompleted:")?;
+            for item in checked {
+                write!(out, "  [✓] {}", item.name)?;
+                if let Some(qty) = &item.quantity {
+                    write!(out, " ({})", qty)?;
+                }
+                writeln!(out)?;
+            }
+        }
+        writeln!(out)
+    }
+
+    fn write_plain(&self, out: &mut dyn Write) -> io::Result<()> {
+        for item in &self.items {
+            item.write_plain(out)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ListSummaryRecord {
+    pub id: String,
+    pub name: String,
+    pub item_count: usize,
+    pub unchecked_count: usize,
+}
+
+impl From<&List> for ListSummaryRecord {
+    fn from(list: &List) -> Self {
+        ListSummaryRecord {
+            id: list.id().to_string(),
+            name: list.name().to_string(),
+            item_count: list.items().len(),
+            unchecked_count: list.items().iter().filter(|item| !item.is_checked()).count(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(transparent)]
+pub struct ListSummaries(pub Vec<ListSummaryRecord>);
+
+impl Render for ListSummaries {
+    const KIND: &'static str = "list_summaries";
+
+    fn write_table(&self, out: &mut dyn Write) -> io::Result<()> {
+        if self.0.is_empty() {
+            return writeln!(out, "No lists found.");
+        }
+
+        writeln!(out, "\nYour Lists:")?;
+        writeln!(out, "{}", "=".repeat(11))?;
+        writeln!(out)?;
+        for list in &self.0 {
+            writeln!(out, "  • {} ({} items)", list.name, list.unchecked_count)?;
+        }
+        writeln!(out)
+    }
+
+    fn write_plain(&self, out: &mut dyn Write) -> io::Result<()> {
+        for list in &self.0 {
+            writeln!(out, "{}\t{}\t{}", list.id, list.name, list.unchecked_count)?;
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Stores and categories
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StoreRecord {
+    pub id: String,
+    pub name: String,
+    pub sort_index: i32,
+}
+
+impl From<&Store> for StoreRecord {
+    fn from(store: &Store) -> Self {
+        StoreRecord {
+            id: store.id().to_string(),
+            name: store.name().to_string(),
+            sort_index: store.sort_index(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StoresRecord {
+    pub list_id: String,
+    pub list_name: String,
+    pub stores: Vec<StoreRecord>,
+}
+
+impl Render for StoresRecord {
+    const KIND: &'static str = "stores";
+
+    fn write_table(&self, out: &mut dyn Write) -> io::Result<()> {
+        if self.stores.is_empty() {
+            return writeln!(out, "No stores found for list '{}'", self.list_name);
+        }
+        writeln!(out, "Stores for list '{}':", self.list_name)?;
+        for store in &self.stores {
+            writeln!(out, "  {} - {}", store.id, store.name)?;
+        }
+        Ok(())
+    }
+
+    fn write_plain(&self, out: &mut dyn Write) -> io::Result<()> {
+        for store in &self.stores {
+            writeln!(out, "{}\t{}", store.id, store.name)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CategoryRecord {
+    pub id: String,
+    pub name: String,
+    pub category_group_id: String,
+}
+
+// ============================================================================
+// Recipes
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IngredientRecord {
+    pub name: String,
+    pub quantity: Option<String>,
+    pub note: Option<String>,
+    pub raw_ingredient: Option<String>,
+}
+
+impl From<&Ingredient> for IngredientRecord {
+    fn from(ingredient: &Ingredient) -> Self {
+        IngredientRecord {
+            name: ingredient.name().to_string(),
+            quantity: ingredient.quantity().map(str::to_string),
+            note: ingredient.note().map(str::to_string),
+            raw_ingredient: ingredient.raw_ingredient().map(str::to_string),
+        }
+    }
+}
+
+impl IngredientRecord {
+    fn write_table_line(&self, out: &mut dyn Write) -> io::Result<()> {
+        write!(out, "    • {}", self.name)?;
+        if let Some(qty) = &self.quantity {
+            write!(out, ": {}", qty)?;
+        }
+        if let Some(note) = &self.note {
+            write!(out, " ({})", note)?;
+        }
+        writeln!(out)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecipeRecord {
+    pub id: String,
+    pub name: String,
+    pub rating: Option<i32>,
+    pub source_name: Option<String>,
+    pub source_url: Option<String>,
+    pub servings: Option<String>,
+    pub prep_time_seconds: Option<i32>,
+    pub cook_time_seconds: Option<i32>,
+    pub note: Option<String>,
+    pub ingredients: Vec<IngredientRecord>,
+    pub preparation_steps: Vec<String>,
+}
+
+impl From<&Recipe> for RecipeRecord {
+    fn from(recipe: &Recipe) -> Self {
+        RecipeRecord {
+            id: recipe.id().to_string(),
+            name: recipe.name().to_string(),
+            rating: recipe.rating(),
+            source_name: recipe.source_name().map(str::to_string),
+            source_url: recipe.source_url().map(str::to_string),
+            servings: recipe.servings().map(str::to_string),
+            prep_time_seconds: recipe.prep_time(),
+            cook_time_seconds: recipe.cook_time(),
+            note: recipe.note().map(str::to_string),
+            ingredients: recipe.ingredients().iter().map(IngredientRecord::from).collect(),
+            preparation_steps: recipe.preparation_steps().to_vec(),
+        }
+    }
+}
+
+impl Render for RecipeRecord {
+    const KIND: &'static str = "recipe";
+
+    fn write_table(&self, out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "\n\x1B[1m{}\x1B[0m", self.name)?;
+        writeln!(out, "{}", "=".repeat(self.name.len()))?;
+        writeln!(out)?;
+
+        writeln!(out, "ID: {}", self.id)?;
+
+        if let Some(rating) = self.rating {
+            writeln!(out, "Rating: {}", "★".repeat(rating as usize))?;
+        }
+
+        if let Some(source_name) = &self.source_name {
+            write!(out, "Source: {}", source_name)?;
+            if let Some(source_url) = &self.source_url {
+                write!(out, " ({})", source_url)?;
+            }
+            writeln!(out)?;
+        }
+
+        if let Some(servings) = &self.servings {
+            writeln!(out, "Servings: {}", servings)?;
+        }
+
+        // Times are stored in seconds
+        if let Some(prep_time) = self.prep_time_seconds {
+            writeln!(out, "Prep Time: {} minutes", prep_time / 60)?;
+        }
+        if let Some(cook_time) = self.cook_time_seconds {
+            writeln!(out, "Cook Time: {} minutes", cook_time / 60)?;
+        }
+
+        if let Some(note) = &self.note {
+            writeln!(out, "\nNote: {}", note)?;
+        }
+
+        if !self.ingredients.is_empty() {
+            writeln!(out, "\n\x1B[1mIngredients:\x1B[0m")?;
+            for ingredient in &self.ingredients {
+                ingredient.write_table_line(out)?;
+            }
+        }
+
+        if !self.preparation_steps.is_empty() {
+            writeln!(out, "\n\x1B[1mPreparation:\x1B[0m")?;
+            for (i, step) in self.preparation_steps.iter().enumerate() {
+                writeln!(out, "  {}. {}", i + 1, step)?;
+            }
+        }
+
+        writeln!(out)
+    }
+
+    fn write_plain(&self, out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "id\t{}", self.id)?;
+        writeln!(out, "name\t{}", self.name)?;
+        if let Some(rating) = self.rating {
+            writeln!(out, "rating\t{}", rating)?;
+        }
+        if let Some(source_name) = &self.source_name {
+            writeln!(out, "source_name\t{}", source_name)?;
+        }
+        if let Some(source_url) = &self.source_url {
+            writeln!(out, "source_url\t{}", source_url)?;
+        }
+        if let Some(servings) = &self.servings {
+            writeln!(out, "servings\t{}", servings)?;
+        }
+        if let Some(prep_time) = self.prep_time_seconds {
+            writeln!(out, "prep_time_seconds\t{}", prep_time)?;
+        }
+        if let Some(cook_time) = self.cook_time_seconds {
+            writeln!(out, "cook_time_seconds\t{}", cook_time)?;
+        }
+        if let Some(note) = &self.note {
+            writeln!(out, "note\t{}", note.replace('\n', " "))?;
+        }
+        for ingredient in &self.ingredients {
+            writeln!(
+                out,
+                "ingredient\t{}\t{}\t{}",
+                ingredient.name,
+                opt(&ingredient.quantity),
+                opt(&ingredient.note)
+            )?;
+        }
+        for step in &self.preparation_steps {
+            writeln!(out, "step\t{}", step.replace('\n', " "))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(transparent)]
+pub struct RecipeSummaries(pub Vec<RecipeRecord>);
+
+impl Render for RecipeSummaries {
+    const KIND: &'static str = "recipes";
+
+    fn write_table(&self, out: &mut dyn Write) -> io::Result<()> {
+        if self.0.is_empty() {
+            return writeln!(out, "No recipes found.");
+        }
+
+        writeln!(out, "\nYour Recipes:")?;
+        writeln!(out, "{}", "=".repeat(13))?;
+        writeln!(out)?;
+
+        for recipe in &self.0 {
+            let ingredient_count = recipe.ingredients.len();
+            let step_count = recipe.preparation_steps.len();
+            write!(out, "  \x1B[1m{}\x1B[0m", recipe.name)?;
+
+            if ingredient_count > 0 || step_count > 0 {
+                write!(out, " ({} ingredients, {} steps)", ingredient_count, step_count)?;
+            }
+
+            if let Some(rating) = recipe.rating {
+                write!(out, " {}", "★".repeat(rating as usize))?;
+            }
+
+            writeln!(out)?;
+        }
+        writeln!(out)
+    }
+
+    fn write_plain(&self, out: &mut dyn Write) -> io::Result<()> {
+        for recipe in &self.0 {
+            writeln!(
+                out,
+                "{}\t{}\t{}",
+                recipe.id,
+                recipe.name,
+                recipe.rating.map(|r| r.to_string()).unwrap_or_default()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Meal plans
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MealPlanEventRecord {
+    pub id: String,
+    pub date: String,
+    pub title: Option<String>,
+    pub recipe_id: Option<String>,
+    pub label_id: Option<String>,
+    pub details: Option<String>,
+}
+
+impl From<&MealPlanEvent> for MealPlanEventRecord {
+    fn from(event: &MealPlanEvent) -> Self {
+        MealPlanEventRecord {
+            id: event.id().to_string(),
+            date: event.date().to_string(),
+            title: event.title().map(str::to_string),
+            recipe_id: event.recipe_id().map(str::to_string),
+            label_id: event.label_id().map(str::to_string),
+            details: event.details().map(str::to_string),
+        }
+    }
+}
+
+impl MealPlanEventRecord {
+    fn write_table_block(&self, out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "Date: {}", self.date)?;
+        if let Some(title) = &self.title {
+            writeln!(out, "  Title: {}", title)?;
+        }
+        if let Some(recipe_id) = &self.recipe_id {
+            writeln!(out, "  Recipe ID: {}", recipe_id)?;
+        }
+        if let Some(label_id) = &self.label_id {
+            writeln!(out, "  Label ID: {}", label_id)?;
+        }
+        if let Some(details) = &self.details {
+            writeln!(out, "  Details: {}", details)?;
+        }
+        writeln!(out, "  Event ID: {}", self.id)
+    }
+}
+
+impl Render for MealPlanEventRecord {
+    const KIND: &'static str = "meal_plan_event";
+
+    fn write_table(&self, out: &mut dyn Write) -> io::Result<()> {
+        self.write_table_block(out)
+    }
+
+    fn write_plain(&self, out: &mut dyn Write) -> io::Result<()> {
+        writeln!(
+            out,
+            "{}\t{}\t{}\t{}\t{}",
+            self.id,
+            self.date,
+            opt(&self.title),
+            opt(&self.recipe_id),
+            opt(&self.label_id)
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MealPlanEventsRecord {
+    pub start_date: String,
+    pub end_date: String,
+    pub events: Vec<MealPlanEventRecord>,
+}
+
+impl Render for MealPlanEventsRecord {
+    const KIND: &'static str = "meal_plan_events";
+
+    fn write_table(&self, out: &mut dyn Write) -> io::Result<()> {
+        if self.events.is_empty() {
+            return writeln!(out, "No meal plan events found in this date range.");
+        }
+
+        writeln!(out, "Meal plan events ({} to {}):", self.start_date, self.end_date)?;
+        writeln!(out)?;
+        for event in &self.events {
+            event.write_table_block(out)?;
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+
+    fn write_plain(&self, out: &mut dyn Write) -> io::Result<()> {
+        for event in &self.events {
+            event.write_plain(out)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MealLabelRecord {
+    pub id: String,
+    pub name: String,
+    pub color: Option<String>,
+}
+
+impl From<&MealLabel> for MealLabelRecord {
+    fn from(label: &MealLabel) -> Self {
+        MealLabelRecord {
+            id: label.id.clone(),
+            name: label.name.clone(),
+            color: label.color.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MealLabelsRecord {
+    pub calendar_id: String,
+    pub labels: Vec<MealLabelRecord>,
+}
+
+impl From<&MealCalendar> for MealLabelsRecord {
+    fn from(calendar: &MealCalendar) -> Self {
+        MealLabelsRecord {
+            calendar_id: calendar.id.clone(),
+            labels: calendar.labels.iter().map(MealLabelRecord::from).collect(),
+        }
+    }
+}
+
+impl Render for MealLabelsRecord {
+    const KIND: &'static str = "meal_labels";
+
+    fn write_table(&self, out: &mut dyn Write) -> io::Result<()> {
+        if self.labels.is_empty() {
+            return writeln!(out, "No meal labels found");
+        }
+        writeln!(out, "Meal labels:")?;
+        for label in &self.labels {
+            writeln!(out, "  {} - {}", label.id, label.name)?;
+        }
+        Ok(())
+    }
+
+    fn write_plain(&self, out: &mut dyn Write) -> io::Result<()> {
+        for label in &self.labels {
+            writeln!(out, "{}\t{}\t{}", label.id, label.name, opt(&label.color))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MealCalendarRecord {
+    pub id: String,
+    /// Label names, in order
+    pub labels: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MealCalendarsRecord {
+    pub calendars: Vec<MealCalendarRecord>,
+}
+
+impl From<Option<&MealCalendar>> for MealCalendarsRecord {
+    fn from(calendar: Option<&MealCalendar>) -> Self {
+        MealCalendarsRecord {
+            calendars: calendar
+                .into_iter()
+                .map(|calendar| MealCalendarRecord {
+                    id: calendar.id.clone(),
+                    labels: calendar.labels.iter().map(|label| label.name.clone()).collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Render for MealCalendarsRecord {
+    const KIND: &'static str = "meal_calendars";
+
+    fn write_table(&self, out: &mut dyn Write) -> io::Result<()> {
+        if self.calendars.is_empty() {
+            return writeln!(out, "No meal-planning calendars found");
+        }
+        writeln!(out, "Meal-planning calendars:")?;
+        for calendar in &self.calendars {
+            writeln!(out, "  {} - labels: {}", calendar.id, calendar.labels.join(", "))?;
+        }
+        Ok(())
+    }
+
+    fn write_plain(&self, out: &mut dyn Write) -> io::Result<()> {
+        for calendar in &self.calendars {
+            writeln!(out, "{}\t{}", calendar.id, calendar.labels.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+/// A planned meal with its recipe and label resolved to names.
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedMealRecord {
+    pub event_id: String,
+    pub date: String,
+    pub label: Option<String>,
+    /// The recipe's name, or the event's title
+    pub name: String,
+    pub recipe_id: Option<String>,
+}
+
+impl PlannedMealRecord {
+    pub fn new(event: &MealPlanEvent, recipes: &[Recipe], calendar: Option<&MealCalendar>) -> Self {
+        let recipe = event
+            .recipe_id()
+            .and_then(|id| recipes.iter().find(|recipe| recipe.id() == id));
+        let name = match (recipe, event.title(), event.recipe_id()) {
+            (Some(recipe), _, _) => recipe.name().to_string(),
+            (None, Some(title), _) if !title.is_empty() => title.to_string(),
+            (None, _, Some(recipe_id)) => format!("Recipe {}", recipe_id),
+            (None, _, None) => "Untitled".to_string(),
+        };
+        PlannedMealRecord {
+            event_id: event.id().to_string(),
+            date: event.date().to_string(),
+            label: event
+                .label_id()
+                .and_then(|id| calendar?.label_name(id))
+                .map(str::to_string),
+            name,
+            recipe_id: event.recipe_id().map(str::to_string),
+        }
+    }
+}
+
+/// Meals laid out as a calendar: days as columns, meal labels as rows.
+#[derive(Debug, Clone, Serialize)]
+pub struct MealPlanGridRecord {
+    pub start_date: String,
+    pub end_date: String,
+    /// Meal labels in row order
+    pub labels: Vec<String>,
+    pub meals: Vec<PlannedMealRecord>,
+}
+
+/// Row for meals without a label, or with one that no longer exists.
+const OTHER_MEALS: &str = "Other";
+
+impl MealPlanGridRecord {
+    pub fn new(
+        start_date: &str,
+        end_date: &str,
+        events: &[MealPlanEvent],
+        recipes: &[Recipe],
+        calendar: Option<&MealCalendar>,
+    ) -> Self {
+        let labels: Vec<String> = calendar
+            .map(|calendar| calendar.labels.iter().map(|label| label.name.clone()).collect())
+            .unwrap_or_default();
+
+        let mut meals: Vec<PlannedMealRecord> = events
+            .iter()
+            .map(|event| PlannedMealRecord::new(event, recipes, calendar))
+            .collect();
+        let row = |meal: &PlannedMealRecord| {
+            meal.label
+                .as_ref()
+                .and_then(|label| labels.iter().position(|name| name == label))
+                .unwrap_or(labels.len())
+        };
+        meals.sort_by(|a, b| a.date.cmp(&b.date).then(row(a).cmp(&row(b))));
+
+        MealPlanGridRecord {
+            start_date: start_date.to_string(),
+            end_date: end_date.to_string(),
+            labels,
+            meals,
+        }
+    }
+
+    fn row_headings(&self) -> Vec<&str> {
+        let mut headings: Vec<&str> = self.labels.iter().map(String::as_str).collect();
+        if self.meals.iter().any(|meal| meal.label.is_none()) {
+            headings.push(OTHER_MEALS);
+        }
+        headings
+    }
+
+    fn meals_on<'a>(&'a self, date: &'a str) -> impl Iterator<Item = &'a PlannedMealRecord> {
+        self.meals.iter().filter(move |meal| meal.date == date)
+    }
+
+    /// One grid per week, Monday to Sunday. Days outside the range are blank.
+    fn write_weeks(
+        &self,
+        out: &mut dyn Write,
+        first: NaiveDate,
+        last: NaiveDate,
+        column_width: usize,
+    ) -> io::Result<()> {
+        let today = dates::today();
+        let headings = self.row_headings();
+
+        let mut monday = dates::week_start(first);
+        while monday <= last {
+            let days: Vec<Option<NaiveDate>> = (0..7)
+                .map(|offset| monday.checked_add_days(Days::new(offset)).filter(|day| (first..=last).contains(day)))
+                .collect();
+            let columns: Vec<String> = days
+                .iter()
+                .map(|day| day.map(|day| day.format("%a %-d").to_string()).unwrap_or_default())
+                .collect();
+            let highlight = days.iter().position(|day| *day == Some(today));
+            let rows: Vec<GridRow> = headings
+                .iter()
+                .map(|heading| GridRow {
+                    heading: heading.to_string(),
+                    cells: days
+                        .iter()
+                        .map(|day| match day {
+                            Some(day) => self
+                                .meals_on(&dates::format_date(*day))
+                                .filter(|meal| meal.label.as_deref().unwrap_or(OTHER_MEALS) == *heading)
+                                .map(|meal| meal.name.clone())
+                                .collect(),
+                            None => Vec::new(),
+                        })
+                        .collect(),
+                })
+                .collect();
+
+            if monday > dates::week_start(first) {
+                writeln!(out)?;
+            }
+            write_grid(out, &columns, highlight, &rows, column_width)?;
+            match monday.checked_add_days(Days::new(7)) {
+                Some(next) => monday = next,
+                None => break,
+            }
+        }
+        Ok(())
+    }
+
+    /// The fallback for narrow terminals: each day with meals, then its meals.
+    fn write_agenda(&self, out: &mut dyn Write) -> io::Result<()> {
+        let mut current_date = None;
+        for meal in &self.meals {
+            if current_date != Some(&meal.date) {
+                if current_date.is_some() {
+                    writeln!(out)?;
+                }
+                let heading = NaiveDate::parse_from_str(&meal.date, "%Y-%m-%d")
+                    .map(|date| date.format("%a %-d %b").to_string())
+                    .unwrap_or_else(|_| meal.date.clone());
+                writeln!(out, "\x1B[1m{}\x1B[0m", heading)?;
+                current_date = Some(&meal.date);
+            }
+            writeln!(out, "  {}: {}", meal.label.as_deref().unwrap_or(OTHER_MEALS), meal.name)?;
+        }
+        Ok(())
+    }
+
+    /// Weekly grids fitting `width` characters, or an agenda when they wouldn't.
+    fn write_calendar(&self, out: &mut dyn Write, width: usize) -> io::Result<()> {
+        if self.meals.is_empty() {
+            return writeln!(out, "No meals planned from {} to {}.", self.start_date, self.end_date);
+        }
+
+        writeln!(out, "Meal plan ({} to {}):", self.start_date, self.end_date)?;
+        writeln!(out)?;
+        let range = NaiveDate::parse_from_str(&self.start_date, "%Y-%m-%d")
+            .and_then(|first| Ok((first, NaiveDate::parse_from_str(&self.end_date, "%Y-%m-%d")?)));
+        let heading_width = self.row_headings().iter().map(|heading| width_of(heading)).max().unwrap_or(0);
+        // "Wed 30" is the longest day heading
+        let longest = self.meals.iter().map(|meal| width_of(&meal.name)).max().unwrap_or(0).max(6);
+        match (range, column_width(width, heading_width, 7, longest)) {
+            (Ok((first, last)), Some(column_width)) => self.write_weeks(out, first, last, column_width),
+            _ => self.write_agenda(out),
+        }
+    }
+}
+
+impl Render for MealPlanGridRecord {
+    const KIND: &'static str = "meal_plan_grid";
+
+    fn write_table(&self, out: &mut dyn Write) -> io::Result<()> {
+        self.write_calendar(out, super::terminal_width())
+    }
+
+    fn write_plain(&self, out: &mut dyn Write) -> io::Result<()> {
+        for meal in &self.meals {
+            writeln!(
+                out,
+                "{}\t{}\t{}\t{}\t{}",
+                meal.date,
+                meal.label.as_deref().unwrap_or(""),
+                meal.name,
+                opt(&meal.recipe_id),
+                meal.event_id
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Real-time events
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncEventRecord {
+    pub timestamp: String,
+    pub event: &'static str,
+}
+
+impl SyncEventRecord {
+    pub fn new(timestamp: String, event: &SyncEvent) -> Self {
+        SyncEventRecord {
+            timestamp,
+            event: sync_event_name(event),
+        }
+    }
+}
+
+/// Stable snake_case names for sync events; `Debug` output is not a contract.
+fn sync_event_name(event: &SyncEvent) -> &'static str {
+    match event {
+        SyncEvent::ShoppingListsChanged => "shopping_lists_changed",
+        SyncEvent::CategorizedItemsChanged => "categorized_items_changed",
+        SyncEvent::ListFoldersChanged => "list_folders_changed",
+        SyncEvent::ListSettingsChanged => "list_settings_changed",
+        SyncEvent::StarterListsChanged => "starter_lists_changed",
+        SyncEvent::StarterListOrderChanged => "starter_list_order_changed",
+        SyncEvent::StarterListSettingsChanged => "starter_list_settings_changed",
+        SyncEvent::MobileAppSettingsChanged => "mobile_app_settings_changed",
+        SyncEvent::UserCategoriesChanged => "user_categories_changed",
+        SyncEvent::RecipeDataChanged => "recipe_data_changed",
+        SyncEvent::MealPlanCalendarChanged => "meal_plan_calendar_changed",
+        SyncEvent::AccountInfoChanged => "account_info_changed",
+        SyncEvent::SubscriptionInfoChanged => "subscription_info_changed",
+        SyncEvent::AccountDeleted => "account_deleted",
+        SyncEvent::Heartbeat => "heartbeat",
+    }
+}
+
+impl Render for SyncEventRecord {
+    const KIND: &'static str = "sync_event";
+
+    fn write_table(&self, out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "[{}] {}", self.timestamp, self.event)
+    }
+
+    fn write_plain(&self, out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "{}\t{}", self.timestamp, self.event)
+    }
+}
+
+// ============================================================================
+// Profiles
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileRecord {
+    pub name: String,
+    pub user_id: Option<String>,
+    pub is_premium: bool,
+    pub active: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfilesRecord {
+    pub profiles: Vec<ProfileRecord>,
+}
+
+impl Render for ProfilesRecord {
+    const KIND: &'static str = "profiles";
+
+    fn write_table(&self, out: &mut dyn Write) -> io::Result<()> {
+        if self.profiles.is_empty() {
+            return writeln!(out, "No profiles found. Run 'anylist login' first.");
+        }
+        for profile in &self.profiles {
+            write!(
+                out,
+                "{} {}",
+                if profile.active { "*" } else { " " },
+                profile.name
+            )?;
+            if let Some(user_id) = &profile.user_id {
+                write!(out, " ({})", user_id)?;
+            }
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+
+    fn write_plain(&self, out: &mut dyn Write) -> io::Result<()> {
+        for profile in &self.profiles {
+            writeln!(
+                out,
+                "{}\t{}\t{}",
+                profile.name,
+                opt(&profile.user_id),
+                if profile.active { "active" } else { "" }
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The account behind the active profile.
+#[derive(Debug, Clone, Serialize)]
+pub struct WhoamiRecord {
+    pub profile: String,
+    pub user_id: String,
+    pub is_premium: bool,
+    pub credential_store: String,
+    pub config_path: String,
+    /// When the access token expires (RFC 3339), if it carries an expiry
+    pub access_token_expires_at: Option<String>,
+    pub access_token_expired: Option<bool>,
+}
+
+impl Render for WhoamiRecord {
+    const KIND: &'static str = "whoami";
+
+    fn write_table(&self, out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "Profile:     {}", self.profile)?;
+        writeln!(out, "User ID:     {}", self.user_id)?;
+        writeln!(out, "Premium:     {}", if self.is_premium { "yes" } else { "no" })?;
+        let freshness = match (&self.access_token_expires_at, self.access_token_expired) {
+            (Some(at), Some(true)) => format!("expired at {} (refreshed on the next command)", at),
+            (Some(at), _) => format!("valid until {}", at),
+            _ => "unknown".to_string(),
+        };
+        writeln!(out, "Token:       {}", freshness)?;
+        writeln!(out, "Stored in:   {}", self.credential_store)?;
+        writeln!(out, "Config:      {}", self.config_path)
+    }
+
+    fn write_plain(&self, out: &mut dyn Write) -> io::Result<()> {
+        writeln!(
+            out,
+            "{}\t{}\t{}\t{}\t{}\t{}",
+            self.profile,
+            self.user_id,
+            if self.is_premium { "premium" } else { "free" },
+            opt(&self.access_token_expires_at),
+            self.credential_store,
+            self.config_path
+        )
+    }
+}
+
+// ============================================================================
+// Mutations
+// ============================================================================
+
+/// Outcome of a command that changes data.
+///
+/// `message` is what a person sees; `record` carries the affected object (when
+/// the API returns one) for scripts.
+#[derive(Debug, Clone, Serialize)]
+pub struct ActionRecord<T: Serialize> {
+    pub action: &'static str,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub record: Option<T>,
+}
+
+impl ActionRecord<()> {
+    pub fn new(action: &'static str, message: impl Into<String>) -> Self {
+        ActionRecord {
+            action,
+            message: message.into(),
+            record: None,
+        }
+    }
+}
+
+impl<T: Serialize> ActionRecord<T> {
+    pub fn with_record(action: &'static str, message: impl Into<String>, record: T) -> Self {
+        ActionRecord {
+            action,
+            message: message.into(),
+            record: Some(record),
+        }
+    }
+}
+
+impl<T: Serialize> Render for ActionRecord<T> {
+    const KIND: &'static str = "action";
+
+    fn write_table(&self, out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "{}", self.message)
+    }
+
+    fn write_plain(&self, out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "{}", self.message)
+    }
+}
+
+/// Duplicate items folded into one by `list dedupe`.
+#[derive(Debug, Clone, Serialize)]
+pub struct MergedItemsRecord {
+    /// The surviving item, with the merged quantity
+    pub kept: ListItemRecord,
+    pub removed: Vec<ListItemRecord>,
+}
+
+/// An item a command would add, shown before the list is changed.
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedItemRecord {
+    pub name: String,
+    pub quantity: Option<String>,
+    pub details: Option<String>,
+}
+
+/// Outcome of a command applied to several items, one result per item.
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchRecord {
+    pub action: &'static str,
+    pub list_name: String,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub results: Vec<BatchItemResult>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchItemResult {
+    pub input: String,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub item: Option<ListItemRecord>,
+}
+
+impl BatchItemResult {
+    pub fn ok(input: &str, item: ListItemRecord) -> Self {
+        BatchItemResult {
+            input: input.to_string(),
+            ok: true,
+            error: None,
+            note: None,
+            item: Some(item),
+        }
+    }
+
+    pub fn with_note(mut self, note: &str) -> Self {
+        self.note = Some(note.to_string());
+        self
+    }
+
+    pub fn failed(input: &str, error: String) -> Self {
+        BatchItemResult {
+            input: input.to_string(),
+            ok: false,
+            error: Some(error),
+            note: None,
+            item: None,
+        }
+    }
+}
+
+impl BatchRecord {
+    pub fn new(action: &'static str, list_name: &str, results: Vec<BatchItemResult>) -> Self {
+        let succeeded = results.iter().filter(|result| result.ok).count();
+        BatchRecord {
+            action,
+            list_name: list_name.to_string(),
+            succeeded,
+            failed: results.len() - succeeded,
+            results,
+        }
+    }
+}
+
+impl Render for BatchRecord {
+    const KIND: &'static str = "batch";
+
+    fn write_table(&self, out: &mut dyn Write) -> io::Result<()> {
+        for result in &self.results {
+            match (&result.item, &result.error) {
+                (Some(item), _) => {
+                    write!(out, "✓ {}", item.name)?;
+                    if let Some(quantity) = &item.quantity {
+                        write!(out, " ({})", quantity)?;
+                    }
+                    if let Some(note) = &result.note {
+                        write!(out, " - {}", note)?;
+                    }
+                    writeln!(out)?;
+                }
+                (None, error) => writeln!(out, "✗ {}: {}", result.input, opt(error))?,
+            }
+        }
+        writeln!(
+            out,
+            "\n{} of {} item(s) succeeded in list '{}'",
+            self.succeeded,
+            self.results.len(),
+            self.list_name
+        )
+    }
+
+    fn write_plain(&self, out: &mut dyn Write) -> io::Result<()> {
+        for result in &self.results {
+            writeln!(
+                out,
+                "{}\t{}\t{}",
+                if result.ok { "ok" } else { "failed" },
+                result.input,
+                opt(&result.error)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meal(date: &str, label: Option<&str>, name: &str) -> PlannedMealRecord {
+        PlannedMealRecord {
+            event_id: format!("{}-{}", date, name),
+            date: date.to_string(),
+            label: label.map(str::to_string),
+            name: name.to_string(),
+            recipe_id: None,
+        }
+    }
+
+    fn calendar(start_date: &str, end_date: &str, meals: Vec<PlannedMealRecord>, width: usize) -> String {
+        let record = MealPlanGridRecord {
+            start_date: start_date.to_string(),
+            end_date: end_date.to_string(),
+            labels: vec!["Lunch".to_string(), "Dinner".to_string()],
+            meals,
+        };
+        let mut out = Vec::new();
+        record.write_calendar(&mut out, width).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn meals() -> Vec<PlannedMealRecord> {
+        vec![
+            meal("2024-01-31", Some("Dinner"), "Spaghetti bolognese"),
+            meal("2024-02-01", Some("Lunch"), "Soup"),
+            meal("2024-02-01", None, "Leftovers"),
+            meal("2024-02-03", Some("Dinner"), "Chicken tikka masala with rice"),
+            meal("2024-02-06", Some("Lunch"), "Supercalifragilistic"),
+        ]
+    }
+
+    /// The text of each cell on the grid lines, skipping rules and blank lines.
+    fn grid_cells(text: &str) -> Vec<Vec<&str>> {
+        text.lines()
+            .filter(|line| line.contains('│'))
+            .map(|line| line.split('│').map(str::trim).collect())
+            .collect()
+    }
+
+    #[test]
+    fn lays_out_weeks_across_a_month_end() {
+        let text = calendar("2024-01-31", "2024-02-06", meals(), 100);
+        assert!(text.starts_with("Meal plan (2024-01-31 to 2024-02-06):\n\n"));
+        assert_eq!(
+            grid_cells(&text),
+            [
+                // Monday and Tuesday fall before the range
+                ["", "", "", "Wed 31", "Thu 1", "Fri 2", "Sat 3", "Sun 4"],
+                ["Lunch", "", "", "", "Soup", "", "", ""],
+                ["Dinner", "", "", "Spaghetti", "", "", "Chicken", ""],
+                ["", "", "", "bolognese", "", "", "tikka", ""],
+                ["", "", "", "", "", "", "masala", ""],
+                ["", "", "", "", "", "", "with rice", ""],
+                ["Other", "", "", "", "Leftovers", "", "", ""],
+                // and the rest of the second week after it
+                ["", "Mon 5", "Tue 6", "", "", "", "", ""],
+                ["Lunch", "", "Supercali…", "", "", "", "", ""],
+                ["Dinner", "", "", "", "", "", "", ""],
+                ["Other", "", "", "", "", "", "", ""],
+            ]
+        );
+    }
+
+    #[test]
+    fn widens_columns_up_to_a_limit() {
+        let text = calendar("2024-01-31", "2024-02-06", meals(), 200);
+        let cells = grid_cells(&text);
+        assert_eq!(cells[2], ["Dinner", "", "", "Spaghetti bolognese", "", "", "Chicken tikka masala", ""]);
+        assert_eq!(cells[3], ["", "", "", "", "", "", "with rice", ""]);
+        assert_eq!(cells[6], ["Lunch", "", "Supercalifragilistic", "", "", "", "", ""]);
+    }
+
+    #[test]
+    fn lists_days_when_too_narrow() {
+        assert_eq!(
+            calendar("2024-01-31", "2024-02-06", meals(), 60),
+            "Meal plan (2024-01-31 to 2024-02-06):\n\n\
+             \x1B[1mWed 31 Jan\x1B[0m\n  Dinner: Spaghetti bolognese\n\n\
+             \x1B[1mThu 1 Feb\x1B[0m\n  Lunch: Soup\n  Other: Leftovers\n\n\
+             \x1B[1mSat 3 Feb\x1B[0m\n  Dinner: Chicken tikka masala with rice\n\n\
+             \x1B[1mTue 6 Feb\x1B[0m\n  Lunch: Supercalifragilistic\n"
+        );
+    }
+
+    #[test]
+    fn says_when_nothing_is_planned() {
+        assert_eq!(
+            calendar("2024-01-31", "2024-02-06", Vec::new(), 100),
+            "No meals planned from 2024-01-31 to 2024-02-06.\n"
+        );
+    }
+}
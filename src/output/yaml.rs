@@ -0,0 +1,176 @@
+//! Minimal YAML emitter for `serde_json::Value` trees.
+//!
+//! Records are plain maps, lists and scalars, so a small block-style emitter
+//! is enough and saves pulling in a YAML crate.
+
+use regex::Regex;
+use serde_json::Value;
+use std::sync::OnceLock;
+
+pub fn to_string(value: &Value) -> String {
+    let mut out = String::new();
+    match value {
+        Value::Object(map) if !map.is_empty() => write_map(&mut out, map, 0),
+        Value::Array(items) if !items.is_empty() => write_seq(&mut out, items, 0),
+        scalar => {
+            out.push_str(&scalar_to_string(scalar));
+            out.push('\n');
+        }
+    }
+    out
+}
+
+fn write_map(out: &mut String, map: &serde_json::Map<String, Value>, indent: usize) {
+    for (key, value) in map {
+        out.push_str(&" ".repeat(indent));
+        out.push_str(&quote_if_needed(key));
+        out.push(':');
+        write_nested(out, value, indent);
+    }
+}
+
+fn write_seq(out: &mut String, items: &[Value], indent: usize) {
+    for value in items {
+        out.push_str(&" ".repeat(indent));
+        out.push('-');
+        match value {
+            Value::Object(map) if !map.is_empty() => {
+                // Put the first key on the same line as the dash
+                let mut nested = String::new();
+                write_map(&mut nested, map, indent + 2);
+                out.push(' ');
+                out.push_str(&nested[indent + 2..]);
+            }
+            _ => write_nested(out, value, indent),
+        }
+    }
+}
+
+/// Write the value that follows a `key:` or `-` marker.
+fn write_nested(out: &mut String, value: &Value, indent: usize) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            out.push('\n');
+            write_map(out, map, indent + 2);
+        }
+        Value::Array(items) if !items.is_empty() => {
+            out.push('\n');
+            write_seq(out, items, indent + 2);
+        }
+        scalar => {
+            out.push(' ');
+            out.push_str(&scalar_to_string(scalar));
+            out.push('\n');
+        }
+    }
+}
+
+fn scalar_to_string(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => quote_if_needed(s),
+        Value::Array(_) => "[]".to_string(),
+        Value::Object(_) => "{}".to_string(),
+    }
+}
+
+/// Matches plain scalars that YAML 1.1 readers (PyYAML, Ruby, most config
+/// tooling) turn into something other than a string: numbers in any base,
+/// with `_` separators or base-60 colons, `.inf`/`.nan`, and timestamps.
+fn non_string_scalar() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(
+            r"(?x)^(
+                [-+]?0b[01_]+
+              | [-+]?0o?[0-7_]+
+              | [-+]?0x[0-9a-fA-F_]+
+              | [-+]?[0-9][0-9_]*(:[0-5]?[0-9])+(\.[0-9_]*)?
+              | [-+]?[0-9][0-9_]*(\.[0-9_]*)?([eE][-+]?[0-9]+)?
+              | [-+]?\.[0-9][0-9_]*([eE][-+]?[0-9]+)?
+              | [-+]?\.(inf|Inf|INF)
+              | \.(nan|NaN|NAN)
+              | [0-9]{4}-[0-9]{1,2}-[0-9]{1,2}([Tt\ ].*)?
+            )$",
+        )
+        .expect("valid regex")
+    })
+}
+
+fn quote_if_needed(s: &str) -> String {
+    let reserved = matches!(
+        s.to_lowercase().as_str(),
+        "" | "~" | "null" | "true" | "false" | "y" | "n" | "yes" | "no" | "on" | "off" | "<<" | "="
+    );
+    let looks_numeric = s.parse::<f64>().is_ok() || non_string_scalar().is_match(s);
+    let special_start = s.starts_with(|c: char| {
+        c.is_whitespace() || "-?:,[]{}#&*!|>'\"%@`".contains(c)
+    });
+    let special_inner = s.contains(": ")
+        || s.contains(" #")
+        || s.ends_with(':')
+        || s.ends_with(char::is_whitespace)
+        || s.contains(|c: char| c.is_control());
+
+    if reserved || looks_numeric || special_start || special_inner {
+        // JSON string syntax is valid YAML double-quoted syntax
+        serde_json::to_string(s).unwrap_or_else(|_| format!("\"{}\"", s))
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn writes_nested_records() {
+        let doc = json!({
+            "name": "Groceries",
+            "items": [
+                { "name": "Milk", "quantity": "1 l" },
+                { "name": "Eggs", "tags": [] }
+            ],
+            "owner": { "id": "u1" }
+        });
+        assert_eq!(
+            to_string(&doc),
+            "name: Groceries\n\
+             items:\n\
+             \x20 - name: Milk\n    quantity: 1 l\n\
+             \x20 - name: Eggs\n    tags: []\n\
+             owner:\n  id: u1\n"
+        );
+    }
+
+    #[test]
+    fn quotes_strings_yaml_would_read_as_other_types() {
+        for text in [
+            "2026-10-19", "2026-10-19T18:00:00Z", "2026-10-19 18:00", "0x1F", "0o17", "017",
+            "0b101", "1_000", "1:30", "190:20:30.15", "1e3", "+12", ".5", ".inf", "-.Inf", ".NaN",
+            "yes", "No", "ON", "off", "y", "N", "true", "null", "~", "", "<<", "=",
+        ] {
+            let quoted = quote_if_needed(text);
+            assert_eq!(quoted, serde_json::to_string(text).unwrap(), "{text} should be quoted");
+        }
+    }
+
+    #[test]
+    fn leaves_ordinary_strings_plain() {
+        for text in ["Milk", "2 eggs", "1 l", "1/2 cup", "Dinner at 7", "v1.2.3", "x", "yesterday", "_", "."] {
+            assert_eq!(quote_if_needed(text), text);
+        }
+    }
+
+    #[test]
+    fn quotes_syntax_characters() {
+        assert_eq!(quote_if_needed("- dash"), "\"- dash\"");
+        assert_eq!(quote_if_needed("note: keep cold"), "\"note: keep cold\"");
+        assert_eq!(quote_if_needed("two\nlines"), "\"two\\nlines\"");
+        assert_eq!(to_string(&json!({ "flag": true, "count": 3, "missing": null })), "flag: true\ncount: 3\nmissing: null\n");
+    }
+}
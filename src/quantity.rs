@@ -0,0 +1,580 @@
+//! Amounts with units, as written on shopping lists and in recipes.
+//!
+//! Understands whole numbers, decimals (with a decimal comma, or commas between
+//! thousands: "1,5" and "1,000"), fractions ("1/2", "1 1/2", "½") and
+//! common metric, US and package units, and converts between units of the same
+//! dimension so quantities like "1 L" and "500 ml" can be added together, or
+//! a recipe can be shown in metric or imperial units.
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dimension {
+    /// Measured in grams
+    Mass,
+    /// Measured in millilitres
+    Volume,
+    /// Plain counts; "dozen" is a count of 12
+    Count,
+    /// Cans, bags, bunches, ...: only compatible with the same unit
+    Package,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Unit {
+    pub symbol: &'static str,
+    pub plural: &'static str,
+    pub aliases: &'static [&'static str],
+    pub dimension: Dimension,
+    /// Size in the dimension's base unit (g, ml or 1)
+    pub factor: f64,
+}
+
+macro_rules! unit {
+    ($symbol:literal, $plural:literal, $dimension:ident, $factor:expr, [$($alias:literal),*]) => {
+        Unit {
+            symbol: $symbol,
+            plural: $plural,
+            aliases: &[$($alias),*],
+            dimension: Dimension::$dimension,
+            factor: $factor,
+        }
+    };
+}
+
+pub static UNITS: &[Unit] = &[
+    unit!("mg", "mg", Mass, 0.001, ["milligram", "milligrams"]),
+    unit!("g", "g", Mass, 1.0, ["gr", "gram", "grams", "gramme", "grammes"]),
+    unit!("kg", "kg", Mass, 1000.0, ["kgs", "kilo", "kilos", "kilogram", "kilograms"]),
+    unit!("oz", "oz", Mass, 28.349_523_125, ["ounce", "ounces"]),
+    unit!("lb", "lbs", Mass, 453.592_37, ["lbs", "pound", "pounds"]),
+    unit!("ml", "ml", Volume, 1.0, ["milliliter", "milliliters", "millilitre", "millilitres"]),
+    unit!("cl", "cl", Volume, 10.0, ["centiliter", "centiliters", "centilitre", "centilitres"]),
+    unit!("dl", "dl", Volume, 100.0, ["deciliter", "deciliters", "decilitre", "decilitres"]),
+    unit!("l", "l", Volume, 1000.0, ["liter", "liters", "litre", "litres"]),
+    unit!("tsp", "tsp", Volume, 4.928_921_6, ["teaspoon", "teaspoons", "tsps"]),
+    unit!("tbsp", "tbsp", Volume, 14.786_765, ["tablespoon", "tablespoons", "tbsps", "tbs"]),
+    unit!("fl oz", "fl oz", Volume, 29.573_53, ["floz", "fluid ounce", "fluid ounces"]),
+    unit!("cup", "cups", Volume, 236.588_24, ["cups", "c"]),
+    unit!("pt", "pt", Volume, 473.176_47, ["pint", "pints"]),
+    unit!("qt", "qt", Volume, 946.352_95, ["quart", "quarts"]),
+    unit!("gal", "gal", Volume, 3_785.411_8, ["gallon", "gallons"]),
+    unit!("dozen", "dozen", Count, 12.0, ["doz"]),
+    unit!("can", "cans", Package, 1.0, ["cans", "tin", "tins"]),
+    unit!("bag", "bags", Package, 1.0, ["bags"]),
+    unit!("box", "boxes", Package, 1.0, ["boxes"]),
+    unit!("bottle", "bottles", Package, 1.0, ["bottles"]),
+    unit!("jar", "jars", Package, 1.0, ["jars"]),
+    unit!("pack", "packs", Package, 1.0, ["packs", "package", "packages", "pkg", "pkgs"]),
+    unit!("bunch", "bunches", Package, 1.0, ["bunches"]),
+    unit!("head", "heads", Package, 1.0, ["heads"]),
+    unit!("clove", "cloves", Package, 1.0, ["cloves"]),
+    unit!("slice", "slices", Package, 1.0, ["slices"]),
+    unit!("stick", "sticks", Package, 1.0, ["sticks"]),
+    unit!("loaf", "loaves", Package, 1.0, ["loaves"]),
+    unit!("piece", "pieces", Package, 1.0, ["pieces", "pc", "pcs"]),
+    unit!("pinch", "pinches", Package, 1.0, ["pinches"]),
+];
+
+/// Find a unit by symbol, plural or alias, ignoring case and a trailing period.
+pub fn lookup_unit(word: &str) -> Option<&'static Unit> {
+    let word = word.trim_end_matches('.').to_lowercase();
+    if word.is_empty() {
+        return None;
+    }
+    UNITS
+        .iter()
+        .find(|unit| unit.symbol == word || unit.plural == word || unit.aliases.contains(&word.as_str()))
+}
+
+/// Parse "2", "1.5", "1/2" or "½" (a single word).
+pub fn parse_amount(word: &str) -> Option<f64> {
+    let word = word.trim();
+    if let Some(value) = vulgar_fraction(word) {
+        return Some(value);
+    }
+    // "1½"
+    if let Some(last) = word.chars().last()
+        && let Some(fraction) = vulgar_fraction(&last.to_string())
+    {
+        let whole = &word[..word.len() - last.len_utf8()];
+        if !whole.is_empty() {
+            return whole.parse::<u32>().ok().map(|whole| f64::from(whole) + fraction);
+        }
+    }
+    if let Some((numerator, denominator)) = word.split_once('/') {
+        let numerator: f64 = numerator.parse().ok()?;
+        let denominator: f64 = denominator.parse().ok()?;
+        return (denominator != 0.0).then(|| numerator / denominator);
+    }
+    if !word.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+        return None;
+    }
+    let number = if has_thousands_separators(word) {
+        word.replace(',', "")
+    } else {
+        word.replace(',', ".")
+    };
+    number.parse::<f64>().ok().filter(|value| value.is_finite())
+}
+
+/// "1,000" and "12,500.5" group thousands with commas; "1,5" uses a decimal
+/// comma.
+fn has_thousands_separators(word: &str) -> bool {
+    let whole = word.split('.').next().unwrap_or(word);
+    let mut groups = whole.split(',');
+    let first = groups.next().unwrap_or("");
+    whole.contains(',')
+        && (1..=3).contains(&first.len())
+        && groups.all(|group| group.len() == 3 && group.bytes().all(|byte| byte.is_ascii_digit()))
+}
+
+fn vulgar_fraction(word: &str) -> Option<f64> {
+    Some(match word {
+        "½" => 0.5,
+        "⅓" => 1.0 / 3.0,
+        "⅔" => 2.0 / 3.0,
+        "¼" => 0.25,
+        "¾" => 0.75,
+        "⅛" => 0.125,
+        "⅜" => 0.375,
+        "⅝" => 0.625,
+        "⅞" => 0.875,
+        _ => return None,
+    })
+}
+
+/// Units to convert recipe quantities to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnitSystem {
+    /// g, kg, ml and l
+    Metric,
+    /// oz, lb and US cooking measures (tsp, tbsp, cup)
+    Imperial,
+}
+
+impl UnitSystem {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "metric" => Some(UnitSystem::Metric),
+            "imperial" => Some(UnitSystem::Imperial),
+            _ => None,
+        }
+    }
+
+    /// Whether `unit` already belongs to this system.
+    fn includes(self, unit: &Unit) -> bool {
+        let metric = matches!(unit.symbol, "mg" | "g" | "kg" | "ml" | "cl" | "dl" | "l");
+        match self {
+            UnitSystem::Metric => metric,
+            UnitSystem::Imperial => !metric,
+        }
+    }
+
+    /// Units to convert to, largest first, each with the smallest amount
+    /// worth writing in it ("1/4 cup", but "2 tbsp" rather than "1/8 cup").
+    fn targets(self, dimension: Dimension) -> &'static [(&'static str, f64)] {
+        match (self, dimension) {
+            (UnitSystem::Metric, Dimension::Mass) => &[("kg", 1.0), ("g", 0.0)],
+            (UnitSystem::Metric, Dimension::Volume) => &[("l", 1.0), ("ml", 0.0)],
+            (UnitSystem::Imperial, Dimension::Mass) => &[("lb", 1.0), ("oz", 0.0)],
+            (UnitSystem::Imperial, Dimension::Volume) => &[("cup", 0.25), ("tbsp", 1.0), ("tsp", 0.0)],
+            _ => &[],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quantity {
+    pub amount: f64,
+    pub unit: Option<&'static Unit>,
+}
+
+impl Quantity {
+    pub fn new(amount: f64, unit: Option<&'static Unit>) -> Self {
+        Quantity { amount, unit }
+    }
+
+    /// Parse a whole string such as "500g", "1 1/2 cups" or "2 lbs".
+    pub fn parse(text: &str) -> Option<Quantity> {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        match Self::parse_words(&words, 0) {
+            Some((quantity, used)) if used == words.len() => Some(quantity),
+            _ => None,
+        }
+    }
+
+    /// Parse a quantity from the start of `words`, returning it and how many
+    /// words it used. A unit is only taken when words remain after it, so
+    /// "2 cans" is read as two of an item called "cans" unless more follows.
+    pub fn parse_prefix(words: &[&str]) -> Option<(Quantity, usize)> {
+        Self::parse_words(words, 1)
+    }
+
+    /// Parse leading words, leaving at least `keep` words after a unit.
+    fn parse_words(words: &[&str], keep: usize) -> Option<(Quantity, usize)> {
+        let first = *words.first()?;
+        let (mut amount, mut used) = match parse_amount(first) {
+            Some(amount) => (amount, 1),
+            None => {
+                // "500g", "1.5kg", "2lbs"
+                let split = first.find(|c: char| !(c.is_ascii_digit() || c == '.' || c == ','))?;
+                let amount = parse_amount(&first[..split])?;
+                let unit = lookup_unit(&first[split..])?;
+                return Some((Quantity::new(amount, Some(unit)), 1));
+            }
+        };
+
+        // "1 1/2"
+        if let Some(next) = words.get(used)
+            && (next.contains('/') || vulgar_fraction(next).is_some())
+            && amount.fract() == 0.0
+            && let Some(fraction) = parse_amount(next)
+            && fraction < 1.0
+        {
+            amount += fraction;
+            used += 1;
+        }
+
+        let mut unit = None;
+        // Two-word units first ("fl oz")
+        if words.len() >= used + 2 + keep
+            && let Some(found) = lookup_unit(&format!("{} {}", words[used], words[used + 1]))
+        {
+            unit = Some(found);
+            used += 2;
+        } else if words.len() >= used + 1 + keep
+            && let Some(found) = lookup_unit(words[used])
+        {
+            unit = Some(found);
+            used += 1;
+        }
+
+        Some((Quantity::new(amount, unit), used))
+    }
+
+    pub fn dimension(&self) -> Dimension {
+        self.unit.map_or(Dimension::Count, |unit| unit.dimension)
+    }
+
+    /// Whether the two quantities can be added together.
+    pub fn is_compatible(&self, other: &Quantity) -> bool {
+        match (self.dimension(), other.dimension()) {
+            (Dimension::Package, Dimension::Package) => self.unit == other.unit,
+            (a, b) => a == b,
+        }
+    }
+
+    /// The amount in the dimension's base unit (g, ml or a plain count).
+    pub fn base_amount(&self) -> f64 {
+        self.amount * self.unit.map_or(1.0, |unit| unit.factor)
+    }
+
+    pub fn scaled(&self, factor: f64) -> Quantity {
+        Quantity::new(self.amount * factor, self.unit)
+    }
+
+    /// Switch to the larger metric unit once the amount reaches it, so sums
+    /// read "1.2 kg" instead of "1200 g". Other units are left alone.
+    pub fn normalized(&self) -> Quantity {
+        let Some(unit) = self.unit else {
+            return *self;
+        };
+        let larger = match unit.symbol {
+            "mg" | "g" => "kg",
+            "ml" | "cl" | "dl" => "l",
+            _ => return *self,
+        };
+        let larger = lookup_unit(larger).expect("metric units are in the table");
+        if self.base_amount() >= larger.factor {
+            Quantity::new(self.base_amount() / larger.factor, Some(larger))
+        } else {
+            *self
+        }
+    }
+
+    /// The same amount in `system`'s units. Quantities already in the system,
+    /// counts and packages are left alone.
+    pub fn convert(&self, system: UnitSystem) -> Quantity {
+        let Some(unit) = self.unit else {
+            return *self;
+        };
+        if system.includes(unit) {
+            return *self;
+        }
+        let base = self.base_amount();
+        let Some(target) = system
+            .targets(unit.dimension)
+            .iter()
+            .filter_map(|(symbol, minimum)| lookup_unit(symbol).map(|unit| (unit, minimum)))
+            .find(|(unit, minimum)| base / unit.factor >= **minimum)
+            .map(|(unit, _)| unit)
+        else {
+            return *self;
+        };
+
+        // "237 ml" rather than "236.59 ml"; "3 1/2 oz" rather than "3.53 oz"
+        let amount = base / target.factor;
+        let step = match system {
+            _ if amount >= 10.0 => 1.0,
+            UnitSystem::Metric => 0.1,
+            UnitSystem::Imperial => 0.125,
+        };
+        Quantity::new((amount / step).round() * step, Some(target))
+    }
+
+    /// Format for a recipe: fractions ("1 1/2 cups") for US measures and
+    /// counts, decimals for metric units.
+    pub fn to_recipe_string(self) -> String {
+        match self.unit {
+            Some(unit) if UnitSystem::Metric.includes(unit) => self.to_string(),
+            None => format_fraction(self.amount),
+            Some(unit) if self.amount <= 1.0 => format!("{} {}", format_fraction(self.amount), unit.symbol),
+            Some(unit) => format!("{} {}", format_fraction(self.amount), unit.plural),
+        }
+    }
+
+    /// Add two compatible quantities, keeping this quantity's unit.
+    pub fn checked_add(&self, other: &Quantity) -> Option<Quantity> {
+        if !self.is_compatible(other) {
+            return None;
+        }
+        let factor = self.unit.map_or(1.0, |unit| unit.factor);
+        Some(Quantity::new(self.amount + other.base_amount() / factor, self.unit))
+    }
+}
+
+/// Scale a quantity written as text, formatted like [`adjust_text`]; text
+/// that isn't a quantity is kept as is.
+pub fn scale_text(text: &str, factor: f64) -> String {
+    adjust_text(text, factor, None)
+}
+
+/// The number of servings in text like "4", "Serves 4" or "4-6 servings".
+pub fn parse_servings(text: &str) -> Option<f64> {
+    text.split(|c: char| c.is_whitespace() || c == '-' || c == '–')
+        .find_map(parse_amount)
+        .filter(|servings| *servings > 0.0)
+}
+
+/// Combine two item quantities: "1 l" and "500 ml" give "1.5 l"; quantities
+/// that can't be added are joined as "1 l + 2 bottles".
+pub fn combine_quantities(a: Option<&str>, b: Option<&str>) -> Option<String> {
+    match (a, b) {
+        (None, other) | (other, None) => other.map(str::to_string),
+        (Some(a), Some(b)) => {
+            let sum = Quantity::parse(a)
+                .zip(Quantity::parse(b))
+                .and_then(|(a, b)| a.checked_add(&b));
+            Some(sum.map_or_else(|| format!("{} + {}", a, b), |sum| sum.to_string()))
+        }
+    }
+}
+
+/// Scale and convert a recipe quantity written as text; text that isn't a
+/// quantity ("a pinch", "2-3") is kept as is.
+pub fn adjust_text(text: &str, factor: f64, system: Option<UnitSystem>) -> String {
+    match Quantity::parse(text) {
+        Some(quantity) if factor != 1.0 || system.is_some() => {
+            let quantity = quantity.scaled(factor);
+            system.map_or(quantity, |system| quantity.convert(system)).to_recipe_string()
+        }
+        _ => text.to_string(),
+    }
+}
+
+/// Format an amount as a whole number and a common kitchen fraction
+/// ("1 1/2", "2/3"), falling back to decimals when none is close.
+pub fn format_fraction(amount: f64) -> String {
+    const FRACTIONS: &[(f64, &str)] = &[
+        (1.0 / 8.0, "1/8"),
+        (1.0 / 4.0, "1/4"),
+        (1.0 / 3.0, "1/3"),
+        (3.0 / 8.0, "3/8"),
+        (1.0 / 2.0, "1/2"),
+        (5.0 / 8.0, "5/8"),
+        (2.0 / 3.0, "2/3"),
+        (3.0 / 4.0, "3/4"),
+        (7.0 / 8.0, "7/8"),
+    ];
+
+    let whole = amount.trunc();
+    let fraction = amount - whole;
+    if (fraction < 0.02 && whole > 0.0) || fraction > 0.98 {
+        return format_amount(amount.round());
+    }
+    match FRACTIONS.iter().find(|(value, _)| (fraction - value).abs() < 0.02) {
+        Some((_, text)) if whole == 0.0 => text.to_string(),
+        Some((_, text)) => format!("{} {}", whole as i64, text),
+        None => format_amount(amount),
+    }
+}
+
+/// Format an amount without trailing zeros: 2, 1.5, 0.33.
+pub fn format_amount(amount: f64) -> String {
+    let rounded = (amount * 100.0).round() / 100.0;
+    if rounded.fract() == 0.0 {
+        format!("{}", rounded as i64)
+    } else {
+        format!("{}", rounded)
+    }
+}
+
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let amount = format_amount(self.amount);
+        match self.unit {
+            None => write!(f, "{}", amount),
+            Some(unit) if self.amount <= 1.0 => write!(f, "{} {}", amount, unit.symbol),
+            Some(unit) => write!(f, "{} {}", amount, unit.plural),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quantity(text: &str) -> Quantity {
+        Quantity::parse(text).unwrap_or_else(|| panic!("{:?} should parse", text))
+    }
+
+    #[test]
+    fn parses_amounts() {
+        for (word, amount) in [
+            ("2", 2.0),
+            ("1.5", 1.5),
+            (".5", 0.5),
+            ("1,5", 1.5),
+            ("0,25", 0.25),
+            ("1,000", 1000.0),
+            ("12,500", 12500.0),
+            ("1,000,000", 1_000_000.0),
+            ("1,250.5", 1250.5),
+            ("1/2", 0.5),
+            ("3/4", 0.75),
+            ("½", 0.5),
+            ("1½", 1.5),
+            ("2¾", 2.75),
+        ] {
+            assert_eq!(parse_amount(word), Some(amount), "{:?}", word);
+        }
+        for word in ["", "a", "1/0", "x2", "2%", "1,5,0"] {
+            assert_eq!(parse_amount(word), None, "{:?}", word);
+        }
+    }
+
+    #[test]
+    fn parses_quantities() {
+        for (text, amount, symbol) in [
+            ("2", 2.0, None),
+            ("500g", 500.0, Some("g")),
+            ("500 g", 500.0, Some("g")),
+            ("1,000 g", 1000.0, Some("g")),
+            ("1,000g", 1000.0, Some("g")),
+            ("1,5 kg", 1.5, Some("kg")),
+            ("1 1/2 cups", 1.5, Some("cup")),
+            ("2 Tablespoons", 2.0, Some("tbsp")),
+            ("2 fl oz", 2.0, Some("fl oz")),
+            ("12 oz.", 12.0, Some("oz")),
+            ("2 lbs", 2.0, Some("lb")),
+            ("3 cans", 3.0, Some("can")),
+        ] {
+            let parsed = quantity(text);
+            assert_eq!(parsed.amount, amount, "{:?}", text);
+            assert_eq!(parsed.unit.map(|unit| unit.symbol), symbol, "{:?}", text);
+        }
+        for text in ["", "a pinch", "2-3", "2 cups flour", "500 grams of"] {
+            assert_eq!(Quantity::parse(text), None, "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn adds_compatible_quantities() {
+        assert_eq!(combine_quantities(Some("1 l"), Some("500 ml")).as_deref(), Some("1.5 l"));
+        assert_eq!(combine_quantities(Some("1 kg"), Some("1,000 g")).as_deref(), Some("2 kg"));
+        assert_eq!(combine_quantities(Some("2"), Some("1 dozen")).as_deref(), Some("14"));
+        assert_eq!(combine_quantities(Some("2 cans"), Some("1 can")).as_deref(), Some("3 cans"));
+        assert_eq!(
+            combine_quantities(Some("1 l"), Some("2 bottles")).as_deref(),
+            Some("1 l + 2 bottles")
+        );
+        assert_eq!(combine_quantities(None, Some("2")).as_deref(), Some("2"));
+        assert_eq!(combine_quantities(None, None), None);
+    }
+
+    #[test]
+    fn normalizes_to_larger_metric_units() {
+        assert_eq!(quantity("1200 g").normalized().to_string(), "1.2 kg");
+        assert_eq!(quantity("250 ml").normalized().to_string(), "250 ml");
+        assert_eq!(quantity("24 oz").normalized().to_string(), "24 oz");
+    }
+
+    #[test]
+    fn converts_between_systems() {
+        for (text, system, expected) in [
+            ("1 cup", UnitSystem::Metric, "237 ml"),
+            ("8 oz", UnitSystem::Metric, "227 g"),
+            ("5 cups", UnitSystem::Metric, "1.2 l"),
+            ("1 kg", UnitSystem::Imperial, "2 1/4 lbs"),
+            ("500 ml", UnitSystem::Imperial, "2 1/8 cups"),
+            ("15 ml", UnitSystem::Imperial, "1 tbsp"),
+            ("5 ml", UnitSystem::Imperial, "1 tsp"),
+            ("500 g", UnitSystem::Metric, "500 g"),
+            ("2 cans", UnitSystem::Metric, "2 cans"),
+            ("3", UnitSystem::Imperial, "3"),
+        ] {
+            assert_eq!(quantity(text).convert(system).to_recipe_string(), expected, "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn scales_text() {
+        for (text, factor, expected) in [
+            ("1 cup", 1.5, "1 1/2 cups"),
+            ("1/2 cup", 0.5, "1/4 cup"),
+            ("200 g", 1.5, "300 g"),
+            ("1,000 g", 0.5, "500 g"),
+            ("3", 2.0, "6"),
+            ("1 can", 0.5, "1/2 can"),
+            ("2 cups", 1.0, "2 cups"),
+            ("a pinch", 2.0, "a pinch"),
+            ("2-3", 2.0, "2-3"),
+        ] {
+            assert_eq!(scale_text(text, factor), expected, "{:?} x {}", text, factor);
+            assert_eq!(adjust_text(text, factor, None), expected, "{:?} x {}", text, factor);
+        }
+        assert_eq!(adjust_text("1 cup", 2.0, Some(UnitSystem::Metric)), "473 ml");
+    }
+
+    #[test]
+    fn formats_fractions() {
+        for (amount, expected) in [
+            (0.5, "1/2"),
+            (0.25, "1/4"),
+            (1.0 / 3.0, "1/3"),
+            (2.0 / 3.0, "2/3"),
+            (0.125, "1/8"),
+            (1.5, "1 1/2"),
+            (2.75, "2 3/4"),
+            (2.0, "2"),
+            (1.99, "2"),
+            (0.0, "0"),
+            (0.1, "0.1"),
+            (1.45, "1.45"),
+        ] {
+            assert_eq!(format_fraction(amount), expected, "{}", amount);
+        }
+        assert_eq!(format_amount(1.0 / 3.0), "0.33");
+        assert_eq!(format_amount(2.0), "2");
+    }
+
+    #[test]
+    fn parses_servings() {
+        assert_eq!(parse_servings("4"), Some(4.0));
+        assert_eq!(parse_servings("Serves 4"), Some(4.0));
+        assert_eq!(parse_servings("4-6 servings"), Some(4.0));
+        assert_eq!(parse_servings("a few"), None);
+        assert_eq!(parse_servings("0"), None);
+    }
+}
@@ -0,0 +1,366 @@
+//! Recipes as files for other tools.
+//!
+//! Markdown for wikis and notes, JSON in the same shape as `recipe get -o json`
+//! data, Paprika's YAML import format, and Cooklang. Cooklang marks
+//! ingredients inside the steps, so each ingredient is tagged where a step
+//! first mentions it and the rest are listed in a step of their own.
+
+use anylist_rs::{Ingredient, Recipe};
+use serde_json::json;
+
+use crate::error::CliError;
+use crate::output::{yaml, RecipeRecord};
+use crate::quantity::{format_amount, Quantity};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Markdown,
+    Json,
+    Paprika,
+    Cooklang,
+}
+
+impl ExportFormat {
+    pub const NAMES: [&'static str; 4] = ["markdown", "json", "paprika", "cooklang"];
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "markdown" => Some(ExportFormat::Markdown),
+            "json" => Some(ExportFormat::Json),
+            "paprika" => Some(ExportFormat::Paprika),
+            "cooklang" => Some(ExportFormat::Cooklang),
+            _ => None,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Json => "json",
+            ExportFormat::Paprika => "yml",
+            ExportFormat::Cooklang => "cook",
+        }
+    }
+}
+
+pub fn export(recipe: &Recipe, format: ExportFormat) -> Result<String, CliError> {
+    Ok(match format {
+        ExportFormat::Markdown => to_markdown(recipe),
+        ExportFormat::Json => serde_json::to_string_pretty(&RecipeRecord::from(recipe))? + "\n",
+        ExportFormat::Paprika => to_paprika(recipe),
+        ExportFormat::Cooklang => to_cooklang(recipe),
+    })
+}
+
+/// A file name for the recipe: "Pasta Carbonara!" becomes "pasta-carbonara".
+pub fn file_stem(recipe: &Recipe) -> String {
+    let stem = recipe
+        .name()
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    if stem.is_empty() {
+        recipe.id().to_string()
+    } else {
+        stem
+    }
+}
+
+/// "200 g spaghetti (al dente)"
+fn ingredient_text(ingredient: &Ingredient) -> String {
+    let mut text = match ingredient.quantity().filter(|quantity| !quantity.is_empty()) {
+        Some(quantity) => format!("{} {}", quantity, ingredient.name()),
+        None => ingredient.name().to_string(),
+    };
+    if let Some(note) = ingredient.note().filter(|note| !note.is_empty()) {
+        text.push_str(&format!(" ({})", note));
+    }
+    text
+}
+
+fn minutes(seconds: i32) -> String {
+    format!("{} min", seconds / 60)
+}
+
+fn to_markdown(recipe: &Recipe) -> String {
+    let mut doc = format!("# {}\n\n", recipe.name());
+
+    let mut facts = Vec::new();
+    match (recipe.source_name(), recipe.source_url()) {
+        (Some(name), Some(url)) => facts.push(format!("Source: [{}]({})", name, url)),
+        (None, Some(url)) => facts.push(format!("Source: <{}>", url)),
+        (Some(name), None) => facts.push(format!("Source: {}", name)),
+        (None, None) => {}
+    }
+    if let Some(servings) = recipe.servings() {
+        facts.push(format!("Servings: {}", servings));
+    }
+    if let Some(prep_time) = recipe.prep_time() {
+        facts.push(format!("Prep time: {}", minutes(prep_time)));
+    }
+    if let Some(cook_time) = recipe.cook_time() {
+        facts.push(format!("Cook time: {}", minutes(cook_time)));
+    }
+    if let Some(rating) = recipe.rating() {
+        facts.push(format!("Rating: {}", "★".repeat(rating.max(0) as usize)));
+    }
+    for fact in &facts {
+        doc.push_str(&format!("- {}\n", fact));
+    }
+    if !facts.is_empty() {
+        doc.push('\n');
+    }
+
+    if let Some(note) = recipe.note() {
+        doc.push_str(&format!("{}\n\n", note.trim()));
+    }
+
+    if !recipe.ingredients().is_empty() {
+        doc.push_str("## Ingredients\n\n");
+        for ingredient in recipe.ingredients() {
+            doc.push_str(&format!("- {}\n", ingredient_text(ingredient)));
+        }
+        doc.push('\n');
+    }
+
+    if !recipe.preparation_steps().is_empty() {
+        doc.push_str("## Steps\n\n");
+        for (i, step) in recipe.preparation_steps().iter().enumerate() {
+            doc.push_str(&format!("{}. {}\n", i + 1, step.trim().replace('\n', "\n   ")));
+        }
+        doc.push('\n');
+    }
+
+    doc.truncate(doc.trim_end().len());
+    doc.push('\n');
+    doc
+}
+
+/// Paprika's YAML import format: ingredients and directions are single
+/// strings with one line per entry.
+fn to_paprika(recipe: &Recipe) -> String {
+    let ingredients: Vec<String> = recipe.ingredients().iter().map(ingredient_text).collect();
+    let value = json!({
+        "name": recipe.name(),
+        "servings": recipe.servings().unwrap_or(""),
+        "source": recipe.source_name().unwrap_or(""),
+        "source_url": recipe.source_url().unwrap_or(""),
+        "prep_time": recipe.prep_time().map(minutes).unwrap_or_default(),
+        "cook_time": recipe.cook_time().map(minutes).unwrap_or_default(),
+        "rating": recipe.rating().unwrap_or(0),
+        "notes": recipe.note().unwrap_or(""),
+        "ingredients": ingredients.join("\n"),
+        "directions": recipe.preparation_steps().join("\n"),
+    });
+    yaml::to_string(&value)
+}
+
+/// A Cooklang ingredient tag: `@salt{}`, `@spaghetti{200%g}`.
+fn cooklang_tag(ingredient: &Ingredient) -> String {
+    let amount = match ingredient.quantity().filter(|quantity| !quantity.is_empty()) {
+        None => String::new(),
+        Some(text) => match Quantity::parse(text) {
+            Some(Quantity { amount, unit: Some(unit) }) => {
+                let unit = if amount > 1.0 { unit.plural } else { unit.symbol };
+                format!("{}%{}", format_amount(amount), unit)
+            }
+            Some(Quantity { amount, unit: None }) => format_amount(amount),
+            // Cooklang reserves these inside braces
+            None => text
+                .replace(['{', '}', '%'], " ")
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" "),
+        },
+    };
+    format!("@{}{{{}}}", ingredient.name().replace(['{', '}'], ""), amount)
+}
+
+fn to_cooklang(recipe: &Recipe) -> String {
+    let mut doc = format!(">> title: {}\n", recipe.name());
+    if let Some(servings) = recipe.servings() {
+        doc.push_str(&format!(">> servings: {}\n", servings));
+    }
+    if let Some(url) = recipe.source_url().or(recipe.source_name()) {
+        doc.push_str(&format!(">> source: {}\n", url));
+    }
+    if let Some(prep_time) = recipe.prep_time() {
+        doc.push_str(&format!(">> prep time: {}\n", minutes(prep_time)));
+    }
+    if let Some(cook_time) = recipe.cook_time() {
+        doc.push_str(&format!(">> cook time: {}\n", minutes(cook_time)));
+    }
+    if let Some(note) = recipe.note() {
+        for line in note.lines() {
+            doc.push_str(&format!("-- {}\n", line));
+        }
+    }
+
+    // Longest names first, so "bell pepper" is tagged before "pepper"
+    let mut order: Vec<usize> = (0..recipe.ingredients().len()).collect();
+    order.sort_by_key(|&index| std::cmp::Reverse(recipe.ingredients()[index].name().len()));
+
+    let mut tagged = vec![false; recipe.ingredients().len()];
+    let mut steps = Vec::new();
+    for step in recipe.preparation_steps() {
+        let mut step = step.replace('\n', " ");
+        // ASCII lowercasing keeps byte offsets valid in `step`
+        let lower = step.to_ascii_lowercase();
+        let mut spans: Vec<(usize, usize, usize)> = Vec::new();
+        for &index in &order {
+            let name = recipe.ingredients()[index].name().to_ascii_lowercase();
+            if tagged[index] || name.is_empty() {
+                continue;
+            }
+            let is_word_edge = |c: Option<char>| !c.is_some_and(char::is_alphanumeric);
+            let found = lower
+                .match_indices(&name)
+                .map(|(start, _)| (start, start + name.len()))
+                .find(|&(start, end)| {
+                    is_word_edge(lower[..start].chars().next_back())
+                        && is_word_edge(lower[end..].chars().next())
+                        && spans.iter().all(|&(other_start, other_end, _)| end <= other_start || start >= other_end)
+                });
+            if let Some((start, end)) = found {
+                spans.push((start, end, index));
+                tagged[index] = true;
+            }
+        }
+        spans.sort_by_key(|&(start, _, _)| std::cmp::Reverse(start));
+        for (start, end, index) in spans {
+            step.replace_range(start..end, &cooklang_tag(&recipe.ingredients()[index]));
+        }
+        steps.push(step);
+    }
+
+    let untagged: Vec<String> = recipe
+        .ingredients()
+        .iter()
+        .zip(&tagged)
+        .filter(|(_, tagged)| !**tagged)
+        .map(|(ingredient, _)| cooklang_tag(ingredient))
+        .collect();
+    if !untagged.is_empty() {
+        steps.insert(0, format!("Ingredients: {}.", untagged.join(", ")));
+    }
+
+    for step in steps {
+        doc.push('\n');
+        doc.push_str(&step);
+        doc.push('\n');
+    }
+    doc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recipe() -> Recipe {
+        serde_json::from_value(json!({
+            "id": "r1",
+            "name": "Pasta Carbonara!",
+            "ingredients": [
+                {"name": "spaghetti", "quantity": "200 g", "note": "al dente", "raw_ingredient": "200 g spaghetti"},
+                {"name": "eggs", "quantity": "2", "note": null, "raw_ingredient": null},
+                {"name": "pecorino", "quantity": "", "note": "grated", "raw_ingredient": null},
+                {"name": "black pepper", "quantity": null, "note": null, "raw_ingredient": null}
+            ],
+            "preparation_steps": [
+                "Boil the spaghetti.",
+                "Whisk the eggs with the pecorino.\nToss with the pasta off the heat."
+            ],
+            "note": "Rome's favourite.\n",
+            "source_name": "Example Kitchen",
+            "source_url": "https://example.com/carbonara",
+            "servings": "2",
+            "prep_time": 600,
+            "cook_time": 900,
+            "rating": 4,
+            "photo_urls": []
+        }))
+        .expect("valid recipe")
+    }
+
+    /// Read an exported JSON file back into a recipe.
+    fn import_json(text: &str) -> Recipe {
+        let record: RecipeRecord = serde_json::from_str(text).expect("exported JSON");
+        serde_json::from_value(json!({
+            "id": record.id,
+            "name": record.name,
+            "ingredients": record.ingredients,
+            "preparation_steps": record.preparation_steps,
+            "note": record.note,
+            "source_name": record.source_name,
+            "source_url": record.source_url,
+            "servings": record.servings,
+            "prep_time": record.prep_time_seconds,
+            "cook_time": record.cook_time_seconds,
+            "rating": record.rating,
+            "photo_urls": [],
+        }))
+        .expect("valid recipe")
+    }
+
+    #[test]
+    fn json_round_trips() {
+        let recipe = recipe();
+        let exported = export(&recipe, ExportFormat::Json).unwrap();
+        assert_eq!(import_json(&exported), recipe);
+
+        let bare: Recipe = serde_json::from_value(json!({
+            "id": "r2",
+            "name": "Toast",
+            "ingredients": [],
+            "preparation_steps": [],
+            "note": null,
+            "source_name": null,
+            "source_url": null,
+            "servings": null,
+            "prep_time": null,
+            "cook_time": null,
+            "rating": null,
+            "photo_urls": []
+        }))
+        .unwrap();
+        let exported = export(&bare, ExportFormat::Json).unwrap();
+        assert_eq!(import_json(&exported), bare);
+    }
+
+    #[test]
+    fn writes_markdown() {
+        let expected = "\
+# Pasta Carbonara!
+
+- Source: [Example Kitchen](https://example.com/carbonara)
+- Servings: 2
+- Prep time: 10 min
+- Cook time: 15 min
+- Rating: ★★★★
+
+Rome's favourite.
+
+## Ingredients
+
+- 200 g spaghetti (al dente)
+- 2 eggs
+- pecorino (grated)
+- black pepper
+
+## Steps
+
+1. Boil the spaghetti.
+2. Whisk the eggs with the pecorino.
+   Toss with the pasta off the heat.
+";
+        assert_eq!(export(&recipe(), ExportFormat::Markdown).unwrap(), expected);
+    }
+
+    #[test]
+    fn names_files() {
+        assert_eq!(file_stem(&recipe()), "pasta-carbonara");
+        assert_eq!(ExportFormat::parse("cooklang").map(ExportFormat::extension), Some("cook"));
+    }
+}
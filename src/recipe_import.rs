@@ -0,0 +1,362 @@
+//! Recipes from web pages.
+//!
+//! Most recipe sites embed a schema.org `Recipe` as JSON-LD in a
+//! `<script type="application/ld+json">` tag. [`from_html`] finds it, whether
+//! it stands alone, sits in an array or in an `@graph`, and reads the
+//! ingredients, instructions, yield and ISO 8601 prep and cook times.
+
+use anylist_rs::Ingredient;
+use regex::Regex;
+use serde_json::Value;
+
+use crate::error::CliError;
+use crate::output::{IngredientRecord, RecipeRecord};
+use crate::recipe_text::parse_ingredient;
+
+#[derive(Debug, Clone)]
+pub struct ImportedRecipe {
+    pub name: String,
+    pub ingredients: Vec<Ingredient>,
+    pub steps: Vec<String>,
+    pub servings: Option<String>,
+    /// Seconds, like AnyList's recipe times
+    pub prep_time: Option<i32>,
+    pub cook_time: Option<i32>,
+    pub note: Option<String>,
+    pub source_name: Option<String>,
+    pub source_url: Option<String>,
+}
+
+impl ImportedRecipe {
+    /// The imported recipe as output, under the ID it was saved with.
+    pub fn record(&self, id: &str) -> RecipeRecord {
+        RecipeRecord {
+            id: id.to_string(),
+            name: self.name.clone(),
+            rating: None,
+            source_name: self.source_name.clone(),
+            source_url: self.source_url.clone(),
+            servings: self.servings.clone(),
+            prep_time_seconds: self.prep_time,
+            cook_time_seconds: self.cook_time,
+            note: self.note.clone(),
+            ingredients: self.ingredients.iter().map(IngredientRecord::from).collect(),
+            preparation_steps: self.steps.clone(),
+        }
+    }
+}
+
+/// Extract the first schema.org `Recipe` from a page. `url` is where the page
+/// came from, used when the recipe doesn't name its own source.
+pub fn from_html(html: &str, url: Option<&str>) -> Result<ImportedRecipe, CliError> {
+    let scripts = Regex::new(r#"(?is)<script[^>]*type\s*=\s*["']?application/ld\+json["']?[^>]*>(.*?)</script>"#)
+        .expect("valid regex");
+
+    let recipe = scripts
+        .captures_iter(html)
+        .filter_map(|captures| serde_json::from_str::<Value>(captures[1].trim()).ok())
+        .find_map(|json| find_recipe(&json).cloned())
+        .ok_or_else(|| CliError::ImportFailed("no schema.org Recipe found in the page".to_string()))?;
+
+    let name = recipe
+        .get("name")
+        .and_then(Value::as_str)
+        .map(clean_text)
+        .filter(|name| !name.is_empty())
+        .ok_or_else(|| CliError::ImportFailed("the recipe has no name".to_string()))?;
+
+    let ingredients = recipe
+        .get("recipeIngredient")
+        .or_else(|| recipe.get("ingredients"))
+        .map(strings)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|text| parse_ingredient(&clean_text(text)))
+        .collect();
+
+    let mut steps = Vec::new();
+    if let Some(instructions) = recipe.get("recipeInstructions") {
+        collect_steps(instructions, &mut steps);
+    }
+
+    let source_url = recipe
+        .get("url")
+        .and_then(Value::as_str)
+        .filter(|link| link.starts_with("http"))
+        .or(url)
+        .map(str::to_string);
+    let source_name = recipe
+        .get("publisher")
+        .and_then(|publisher| publisher.get("name").or(Some(publisher)))
+        .and_then(Value::as_str)
+        .map(clean_text)
+        .or_else(|| source_url.as_deref().and_then(host));
+
+    Ok(ImportedRecipe {
+        name,
+        ingredients,
+        steps,
+        servings: recipe.get("recipeYield").and_then(servings),
+        prep_time: recipe.get("prepTime").and_then(Value::as_str).and_then(parse_duration),
+        cook_time: recipe.get("cookTime").and_then(Value::as_str).and_then(parse_duration),
+        note: recipe
+            .get("description")
+            .and_then(Value::as_str)
+            .map(clean_text)
+            .filter(|note| !note.is_empty()),
+        source_name,
+        source_url,
+    })
+}
+
+/// The first object whose `@type` is (or includes) "Recipe".
+fn find_recipe(json: &Value) -> Option<&Value> {
+    match json {
+        Value::Array(values) => values.iter().find_map(find_recipe),
+        Value::Object(object) => {
+            let is_recipe = match object.get("@type") {
+                Some(Value::String(kind)) => kind == "Recipe",
+                Some(Value::Array(kinds)) => kinds.iter().any(|kind| kind == "Recipe"),
+                _ => false,
+            };
+            if is_recipe {
+                return Some(json);
+            }
+            object.get("@graph").and_then(find_recipe)
+        }
+        _ => None,
+    }
+}
+
+/// A string or an array of strings.
+fn strings(value: &Value) -> Vec<String> {
+    match value {
+        Value::String(text) => vec![text.clone()],
+        Value::Array(values) => values.iter().filter_map(Value::as_str).map(str::to_string).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Flatten instructions given as text, `HowToStep`s or `HowToSection`s.
+fn collect_steps(value: &Value, steps: &mut Vec<String>) {
+    match value {
+        Value::String(text) => steps.extend(clean_text_lines(text)),
+        Value::Array(values) => values.iter().for_each(|value| collect_steps(value, steps)),
+        Value::Object(object) => {
+            if let Some(elements) = object.get("itemListElement") {
+                collect_steps(elements, steps);
+            } else if let Some(text) = object.get("text").or_else(|| object.get("name")) {
+                collect_steps(text, steps);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// `recipeYield` as "4", "4 servings" or ["4", "4 servings"].
+fn servings(value: &Value) -> Option<String> {
+    match value {
+        Value::Number(number) => Some(number.to_string()),
+        Value::String(text) => Some(clean_text(text)).filter(|text| !text.is_empty()),
+        Value::Array(values) => values.iter().rev().find_map(servings),
+        _ => None,
+    }
+}
+
+/// Seconds in an ISO 8601 duration such as "PT1H30M" or "P0DT45M".
+fn parse_duration(text: &str) -> Option<i32> {
+    let rest = text.trim().strip_prefix(['P', 'p'])?;
+    let mut seconds = 0.0;
+    let mut number = String::new();
+    let mut in_time = false;
+    for c in rest.chars() {
+        match c.to_ascii_uppercase() {
+            'T' => in_time = true,
+            '0'..='9' | '.' | ',' => number.push(if c == ',' { '.' } else { c }),
+            unit => {
+                let value: f64 = number.parse().ok()?;
+                number.clear();
+                seconds += value
+                    * match (unit, in_time) {
+                        ('W', false) => 604_800.0,
+                        ('D', false) => 86_400.0,
+                        ('H', true) => 3_600.0,
+                        ('M', true) => 60.0,
+                        ('S', true) => 1.0,
+                        _ => return None,
+                    };
+            }
+        }
+    }
+    number.is_empty().then_some(seconds.round() as i32)
+}
+
+fn host(url: &str) -> Option<String> {
+    let rest = url.split_once("://")?.1;
+    let host = rest.split(['/', '?', '#']).next()?;
+    Some(host.trim_start_matches("www.").to_string()).filter(|host| !host.is_empty())
+}
+
+/// Strip tags and decode common entities, collapsing whitespace.
+fn clean_text(text: &str) -> String {
+    clean_text_lines(text).join(" ")
+}
+
+/// Like [`clean_text`], keeping line breaks (`<br>`, `</p>`, newlines).
+fn clean_text_lines(text: &str) -> Vec<String> {
+    let breaks = Regex::new(r"(?i)<br\s*/?>|</p>|</li>").expect("valid regex");
+    let tags = Regex::new(r"<[^>]*>").expect("valid regex");
+    let text = breaks.replace_all(text, "\n");
+    let text = decode_entities(&tags.replace_all(&text, ""));
+    text.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+fn decode_entities(text: &str) -> String {
+    let entity = Regex::new(r"&(#[0-9]+|#[xX][0-9a-fA-F]+|[a-zA-Z][a-zA-Z0-9]*);").expect("valid regex");
+    entity
+        .replace_all(text, |captures: &regex::Captures| {
+            let name = &captures[1];
+            let decoded = match name {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                "deg" => Some('°'),
+                "frac12" => Some('½'),
+                "frac14" => Some('¼'),
+                "frac34" => Some('¾'),
+                _ if name.starts_with("#x") || name.starts_with("#X") => {
+                    u32::from_str_radix(&name[2..], 16).ok().and_then(char::from_u32)
+                }
+                _ if name.starts_with('#') => name[1..].parse().ok().and_then(char::from_u32),
+                _ => None,
+            };
+            decoded.map_or_else(|| captures[0].to_string(), |c| c.to_string())
+        })
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ingredients(recipe: &ImportedRecipe) -> Vec<(&str, Option<&str>, Option<&str>)> {
+        recipe
+            .ingredients
+            .iter()
+            .map(|ingredient| (ingredient.name(), ingredient.quantity(), ingredient.note()))
+            .collect()
+    }
+
+    #[test]
+    fn finds_the_recipe_in_a_graph() {
+        let html = include_str!("../tests/fixtures/recipe_import/graph.html");
+        let recipe = from_html(html, Some("https://www.example.com/weeknight-chili/")).unwrap();
+
+        assert_eq!(recipe.name, "Weeknight Chili & Cornbread");
+        assert_eq!(recipe.note.as_deref(), Some("A quick chili for busy nights."));
+        assert_eq!(recipe.servings.as_deref(), Some("6 servings"));
+        assert_eq!(recipe.prep_time, Some(15 * 60));
+        assert_eq!(recipe.cook_time, Some(65 * 60));
+        assert_eq!(
+            ingredients(&recipe),
+            vec![
+                ("ground beef", Some("1 lb"), None),
+                ("kidney beans", Some("2 cans"), Some("drained")),
+                ("chopped onion", Some("1 1/2 cups"), None),
+                ("chili powder", Some("2 tbsp"), None),
+                ("salt", None, None),
+            ]
+        );
+        assert_eq!(
+            recipe.steps,
+            vec![
+                "Brown the beef.",
+                "Add the onion and cook until soft.",
+                "Stir in the beans and chili powder and simmer for 45 minutes.",
+            ]
+        );
+        // The publisher is only a reference, so the page's host names the source
+        assert_eq!(recipe.source_name.as_deref(), Some("example.com"));
+        assert_eq!(recipe.source_url.as_deref(), Some("https://www.example.com/weeknight-chili/"));
+    }
+
+    #[test]
+    fn reads_a_single_recipe() {
+        let html = include_str!("../tests/fixtures/recipe_import/single.html");
+        let recipe = from_html(html, Some("https://elsewhere.example.net/")).unwrap();
+
+        assert_eq!(recipe.name, "Lemon Bars");
+        assert_eq!(recipe.servings.as_deref(), Some("16"));
+        assert_eq!(recipe.prep_time, Some(20 * 60));
+        assert_eq!(recipe.cook_time, Some(45 * 60));
+        assert_eq!(
+            ingredients(&recipe),
+            vec![
+                ("flour", Some("2 cups"), None),
+                ("powdered sugar", Some("1/2 cup"), None),
+                ("eggs", Some("4"), None),
+                ("lemon juice", Some("1/3 cup"), None),
+            ]
+        );
+        assert_eq!(
+            recipe.steps,
+            vec![
+                "Heat the oven to 350°F.",
+                "Press the crust into the pan and bake.",
+                "Whisk the filling, pour it over and bake again.",
+            ]
+        );
+        // The recipe's own URL and publisher win over the page's
+        assert_eq!(recipe.source_name.as_deref(), Some("Example Bakes"));
+        assert_eq!(recipe.source_url.as_deref(), Some("https://bakes.example.org/lemon-bars"));
+    }
+
+    #[test]
+    fn flattens_instruction_sections() {
+        let html = include_str!("../tests/fixtures/recipe_import/sections.html");
+        let recipe = from_html(html, None).unwrap();
+
+        assert_eq!(recipe.name, "Roast Chicken with Gravy");
+        assert_eq!(recipe.servings.as_deref(), Some("4 servings"));
+        assert_eq!(recipe.prep_time, None);
+        assert_eq!(recipe.cook_time, Some(90 * 60));
+        assert_eq!(recipe.ingredients.len(), 4);
+        assert_eq!(recipe.ingredients[0].note(), Some("about 4 lb"));
+        assert_eq!(
+            recipe.steps,
+            vec![
+                "Heat the oven to 425°F.",
+                "Rub the chicken with butter and roast for 1 1/2 hours.",
+                "Whisk the flour into the pan juices.",
+                "Add the stock and simmer until thick.",
+            ]
+        );
+        assert_eq!(recipe.source_name, None);
+        assert_eq!(recipe.source_url, None);
+    }
+
+    #[test]
+    fn fails_without_a_recipe() {
+        let html = include_str!("../tests/fixtures/recipe_import/no_recipe.html");
+        assert!(matches!(from_html(html, None), Err(CliError::ImportFailed(_))));
+        assert!(matches!(from_html("<html></html>", None), Err(CliError::ImportFailed(_))));
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("PT1H30M"), Some(5400));
+        assert_eq!(parse_duration("P0DT45M"), Some(2700));
+        assert_eq!(parse_duration("pt90s"), Some(90));
+        assert_eq!(parse_duration("PT0.5H"), Some(1800));
+        assert_eq!(parse_duration("P1D"), Some(86_400));
+        assert_eq!(parse_duration("45 minutes"), None);
+        assert_eq!(parse_duration("PT45"), None);
+        assert_eq!(parse_duration("P5M"), None);
+    }
+}
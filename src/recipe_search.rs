@@ -0,0 +1,327 @@
+//! Filtering and ranking recipes.
+//!
+//! Ingredient terms match whole words, ignoring case and plurals (every word
+//! is made singular, so "tomatoes" matches "Cherry tomato, halved"), so
+//! "chicken" matches "Chicken thighs" but "egg" doesn't match "eggplant".
+//! A few terms stand for groups of ingredients (`dairy`, `meat`, ...) so that
+//! "no dairy" excludes milk, butter and cheese alike, but not peanut butter or
+//! coconut milk.
+
+use anylist_rs::Recipe;
+
+use crate::item_parser::normalize_words;
+
+#[derive(Debug, Clone, Default)]
+pub struct RecipeQuery {
+    /// Words to look for in the name, ingredients and steps
+    pub text: Option<String>,
+    /// Ingredients a recipe must use
+    pub include: Vec<String>,
+    /// Ingredients a recipe must not use
+    pub exclude: Vec<String>,
+    pub min_rating: Option<i32>,
+    /// Longest prep plus cook time, in minutes
+    pub max_minutes: Option<i32>,
+    /// Text in the source name or URL
+    pub source: Option<String>,
+}
+
+struct IngredientGroup {
+    name: &'static str,
+    members: &'static [&'static str],
+    /// Ingredients named after a member that don't belong to the group
+    lookalikes: &'static [&'static str],
+}
+
+const INGREDIENT_GROUPS: &[IngredientGroup] = &[
+    IngredientGroup {
+        name: "dairy",
+        members: &[
+            "milk", "buttermilk", "butter", "cream", "cheese", "yogurt", "yoghurt", "ghee", "parmesan",
+            "mozzarella", "cheddar", "ricotta", "feta", "mascarpone", "creme fraiche", "sour cream",
+        ],
+        lookalikes: &[
+            "peanut butter", "almond butter", "cashew butter", "nut butter", "apple butter", "cocoa butter",
+            "vegan butter", "coconut cream", "coconut milk", "almond milk", "oat milk", "soy milk", "rice milk",
+            "cream of tartar", "vegan cheese", "coconut yogurt",
+        ],
+    },
+    IngredientGroup {
+        name: "meat",
+        members: &[
+            "beef", "pork", "chicken", "lamb", "turkey", "bacon", "ham", "sausage", "veal", "duck",
+            "prosciutto", "pancetta", "chorizo", "mince", "steak",
+        ],
+        lookalikes: &["vegan sausage", "vegetarian sausage"],
+    },
+    IngredientGroup {
+        name: "seafood",
+        members: &["fish", "salmon", "tuna", "cod", "shrimp", "prawn", "crab", "lobster", "mussel", "clam", "anchovy"],
+        lookalikes: &[],
+    },
+    IngredientGroup {
+        name: "nuts",
+        members: &["almond", "walnut", "pecan", "cashew", "hazelnut", "pistachio", "peanut", "macadamia", "pine nut"],
+        lookalikes: &[],
+    },
+    IngredientGroup {
+        name: "gluten",
+        members: &["flour", "bread", "pasta", "spaghetti", "noodle", "couscous", "barley", "breadcrumb", "tortilla"],
+        lookalikes: &[
+            "rice flour", "almond flour", "coconut flour", "corn flour", "rice noodle", "corn tortilla",
+            "gluten free bread", "gluten free pasta", "gluten free flour",
+        ],
+    },
+];
+
+/// The words a term stands for, its group's members or just itself, and
+/// names that look like them but don't count.
+fn expand(term: &str) -> (Vec<String>, Vec<String>) {
+    let key = normalize_words(term);
+    let normalize_all = |words: &[&str]| words.iter().map(|word| normalize_words(word)).collect();
+    match INGREDIENT_GROUPS.iter().find(|group| normalize_words(group.name) == key) {
+        Some(group) => (normalize_all(group.members), normalize_all(group.lookalikes)),
+        None => (vec![key], Vec::new()),
+    }
+}
+
+/// Text normalized with [`normalize_words`] and padded with spaces, for
+/// [`has_words`].
+fn padded(text: &str) -> String {
+    format!(" {} ", normalize_words(text))
+}
+
+/// Whether normalized `words` appear as whole words in `padded` text.
+fn has_words(padded: &str, words: &str) -> bool {
+    !words.is_empty() && padded.contains(&format!(" {} ", words))
+}
+
+/// Whether normalized `words` appear as whole words in `text`.
+fn contains_words(text: &str, words: &str) -> bool {
+    has_words(&padded(text), words)
+}
+
+fn uses(recipe: &Recipe, term: &str) -> bool {
+    let (terms, lookalikes) = expand(term);
+    recipe.ingredients().iter().any(|ingredient| {
+        let mut name = padded(ingredient.name());
+        for lookalike in &lookalikes {
+            let lookalike = format!(" {} ", lookalike);
+            // Repeated, as neighbouring matches share a space; two spaces
+            // keep the words on either side apart
+            while name.contains(&lookalike) {
+                name = name.replace(&lookalike, "  ");
+            }
+        }
+        terms.iter().any(|term| has_words(&name, term))
+    })
+}
+
+/// Prep plus cook time in minutes, when the recipe has either.
+pub fn total_minutes(recipe: &Recipe) -> Option<i32> {
+    match (recipe.prep_time(), recipe.cook_time()) {
+        (None, None) => None,
+        (prep, cook) => Some((prep.unwrap_or(0) + cook.unwrap_or(0)) / 60),
+    }
+}
+
+impl RecipeQuery {
+    pub fn matches(&self, recipe: &Recipe) -> bool {
+        self.include.iter().all(|term| uses(recipe, term))
+            && !self.exclude.iter().any(|term| uses(recipe, term))
+            && self
+                .min_rating
+                .is_none_or(|min| recipe.rating().is_some_and(|rating| rating >= min))
+            // Recipes without times can't be shown to fit
+            && self
+                .max_minutes
+                .is_none_or(|max| total_minutes(recipe).is_some_and(|minutes| minutes <= max))
+            && self.source.as_deref().is_none_or(|source| {
+                let source = source.to_lowercase();
+                [recipe.source_name(), recipe.source_url()]
+                    .into_iter()
+                    .flatten()
+                    .any(|field| field.to_lowercase().contains(&source))
+            })
+    }
+
+    /// How well the recipe matches the search text, or None when a word
+    /// can't be found anywhere in it. Name matches count most.
+    fn text_score(&self, recipe: &Recipe) -> Option<u32> {
+        let Some(text) = self.text.as_deref().map(str::trim).filter(|text| !text.is_empty()) else {
+            return Some(0);
+        };
+
+        let name = recipe.name().to_lowercase();
+        let query = text.to_lowercase();
+        let mut score = if name == query {
+            100
+        } else if name.starts_with(&query) {
+            60
+        } else if name.contains(&query) {
+            30
+        } else {
+            0
+        };
+
+        for word in normalize_words(text).split_whitespace() {
+            if contains_words(recipe.name(), word) {
+                score += 20;
+            } else if recipe.ingredients().iter().any(|i| contains_words(i.name(), word)) {
+                score += 8;
+            } else if recipe.preparation_steps().iter().any(|step| contains_words(step, word))
+                || recipe.note().is_some_and(|note| contains_words(note, word))
+            {
+                score += 2;
+            } else if !name.contains(word) {
+                return None;
+            }
+        }
+        Some(score)
+    }
+}
+
+/// Recipes matching the query, best first: by how well the text matches,
+/// then by rating, then by name.
+pub fn search<'a>(recipes: &'a [Recipe], query: &RecipeQuery) -> Vec<&'a Recipe> {
+    let mut found: Vec<(u32, &Recipe)> = recipes
+        .iter()
+        .filter(|recipe| query.matches(recipe))
+        .filter_map(|recipe| query.text_score(recipe).map(|score| (score, recipe)))
+        .collect();
+    found.sort_by(|(score_a, a), (score_b, b)| {
+        score_b
+            .cmp(score_a)
+            .then(b.rating().unwrap_or(0).cmp(&a.rating().unwrap_or(0)))
+            .then_with(|| a.name().to_lowercase().cmp(&b.name().to_lowercase()))
+    });
+    found.into_iter().map(|(_, recipe)| recipe).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn recipe(id: &str, name: &str, ingredients: &[&str], rating: Option<i32>, minutes: Option<i32>) -> Recipe {
+        let ingredients: Vec<_> = ingredients
+            .iter()
+            .map(|name| json!({"name": name, "quantity": null, "note": null, "raw_ingredient": null}))
+            .collect();
+        serde_json::from_value(json!({
+            "id": id,
+            "name": name,
+            "ingredients": ingredients,
+            "preparation_steps": ["Cook everything until done."],
+            "note": null,
+            "source_name": "Example Kitchen",
+            "source_url": null,
+            "servings": null,
+            "prep_time": null,
+            "cook_time": minutes.map(|minutes| minutes * 60),
+            "rating": rating,
+            "photo_urls": []
+        }))
+        .expect("valid recipe")
+    }
+
+    fn query(include: &[&str], exclude: &[&str]) -> RecipeQuery {
+        RecipeQuery {
+            include: include.iter().map(|term| term.to_string()).collect(),
+            exclude: exclude.iter().map(|term| term.to_string()).collect(),
+            ..RecipeQuery::default()
+        }
+    }
+
+    #[test]
+    fn matches_whole_words_ignoring_plurals() {
+        let salad = recipe("1", "Salad", &["Cherry tomatoes, halved", "Baby potatoes", "Eggplant"], None, None);
+        assert!(uses(&salad, "tomato"));
+        assert!(uses(&salad, "tomatoes"));
+        assert!(uses(&salad, "Cherry Tomato"));
+        assert!(uses(&salad, "potato"));
+        assert!(uses(&salad, "baby potatoes"));
+        assert!(!uses(&salad, "egg"));
+        assert!(!uses(&salad, "plant"));
+    }
+
+    #[test]
+    fn groups_cover_their_members() {
+        let pasta = recipe("1", "Pasta", &["spaghetti", "unsalted butter", "Parmesan cheese"], None, None);
+        assert!(uses(&pasta, "dairy"));
+        assert!(uses(&pasta, "gluten"));
+        assert!(!uses(&pasta, "meat"));
+    }
+
+    #[test]
+    fn lookalikes_are_not_group_members() {
+        let satay = recipe("1", "Satay", &["Peanut butter", "Coconut cream", "coconut milk", "rice noodles"], None, None);
+        assert!(!uses(&satay, "dairy"));
+        assert!(!uses(&satay, "gluten"));
+        assert!(uses(&satay, "nuts"));
+        // The lookalikes still match when asked for by name
+        assert!(uses(&satay, "peanut butter"));
+        assert!(uses(&satay, "cream"));
+
+        let both = recipe("2", "Cookies", &["peanut butter and butter"], None, None);
+        assert!(uses(&both, "dairy"));
+        let twice = recipe("3", "Curry", &["coconut milk or coconut milk"], None, None);
+        assert!(!uses(&twice, "dairy"));
+    }
+
+    #[test]
+    fn filters_recipes() {
+        let curry = recipe("1", "Chicken Curry", &["chicken thighs", "coconut milk"], Some(5), Some(30));
+        let soup = recipe("2", "Chicken Soup", &["chicken", "egg noodles", "cream"], Some(3), None);
+        let toast = recipe("3", "Toast", &["bread", "butter"], None, Some(5));
+
+        let dairy_free = query(&["chicken"], &["dairy"]);
+        assert!(dairy_free.matches(&curry));
+        assert!(!dairy_free.matches(&soup));
+
+        let quick = RecipeQuery {
+            max_minutes: Some(20),
+            ..RecipeQuery::default()
+        };
+        assert!(quick.matches(&toast));
+        assert!(!quick.matches(&curry));
+        assert!(!quick.matches(&soup), "no time means it can't be shown to fit");
+
+        let rated = RecipeQuery {
+            min_rating: Some(4),
+            ..RecipeQuery::default()
+        };
+        assert!(rated.matches(&curry));
+        assert!(!rated.matches(&soup));
+        assert!(!rated.matches(&toast));
+
+        let source = RecipeQuery {
+            source: Some("example".to_string()),
+            ..RecipeQuery::default()
+        };
+        assert!(source.matches(&toast));
+    }
+
+    #[test]
+    fn ranks_by_text_then_rating() {
+        let recipes = [
+            recipe("1", "Chicken Soup", &["chicken", "carrots"], Some(3), None),
+            recipe("2", "Creamy Chicken Pasta", &["chicken", "pasta"], Some(4), None),
+            recipe("3", "Carrot Cake", &["carrots", "flour"], Some(5), None),
+            recipe("4", "Toast", &["bread"], Some(5), None),
+        ];
+        let names = |text: &str| -> Vec<&str> {
+            let query = RecipeQuery {
+                text: Some(text.to_string()),
+                ..RecipeQuery::default()
+            };
+            search(&recipes, &query).into_iter().map(|recipe| recipe.name()).collect()
+        };
+
+        assert_eq!(names("chicken soup"), vec!["Chicken Soup"]);
+        assert_eq!(names("chicken"), vec!["Chicken Soup", "Creamy Chicken Pasta"]);
+        assert_eq!(names("carrots"), vec!["Carrot Cake", "Chicken Soup"]);
+        assert_eq!(names("curry"), Vec::<&str>::new());
+        assert_eq!(names(""), vec!["Carrot Cake", "Toast", "Creamy Chicken Pasta", "Chicken Soup"]);
+    }
+}
@@ -0,0 +1,405 @@
+//! Recipes as plain text documents, for authoring them in an editor.
+//!
+//! [`to_document`] writes a recipe as a `Name:` line followed by
+//! `[Ingredients]` and `[Steps]` sections; [`parse_document`] reads it back
+//! into a [`RecipeDraft`]. Ingredients are written `name | quantity | note`,
+//! but a line without `|` such as "2 cups flour (sifted)" is parsed like a
+//! shopping list item. Lines starting with `#` are comments unless escaped as
+//! `\#`, and indented lines under a step continue it.
+
+use anylist_rs::{Ingredient, Recipe};
+
+use crate::error::CliError;
+use crate::item_parser::parse_item;
+
+/// The parts of a recipe that can be saved through the API.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecipeDraft {
+    pub name: String,
+    pub ingredients: Vec<Ingredient>,
+    pub steps: Vec<String>,
+}
+
+impl From<&Recipe> for RecipeDraft {
+    fn from(recipe: &Recipe) -> Self {
+        RecipeDraft {
+            name: recipe.name().to_string(),
+            ingredients: recipe.ingredients().to_vec(),
+            steps: recipe.preparation_steps().to_vec(),
+        }
+    }
+}
+
+impl RecipeDraft {
+    /// Swap in `before`'s ingredients where they were left as they were, so
+    /// their original raw text survives a round trip through the document.
+    pub fn keep_unchanged_ingredients(&mut self, before: &RecipeDraft) {
+        for ingredient in &mut self.ingredients {
+            if let Some(original) = before.ingredients.iter().find(|original| same_ingredient(original, ingredient)) {
+                *ingredient = original.clone();
+            }
+        }
+    }
+
+    /// Swap in `before`'s steps where they read the same apart from
+    /// whitespace, so blank lines and spacing inside a step survive a round
+    /// trip through the document.
+    pub fn keep_unchanged_steps(&mut self, before: &RecipeDraft) {
+        for step in &mut self.steps {
+            if let Some(original) = before.steps.iter().find(|original| step_words(original) == step_words(step)) {
+                *step = original.clone();
+            }
+        }
+    }
+
+    /// What changed from `before`, one line per kind of change.
+    pub fn changes_from(&self, before: &RecipeDraft) -> Vec<String> {
+        let mut changes = Vec::new();
+        if self.name != before.name {
+            changes.push(format!("Renamed '{}' to '{}'", before.name, self.name));
+        }
+
+        let contains = |list: &[Ingredient], ingredient: &Ingredient| list.iter().any(|i| same_ingredient(i, ingredient));
+        let added = self.ingredients.iter().filter(|i| !contains(&before.ingredients, i)).count();
+        let removed = before.ingredients.iter().filter(|i| !contains(&self.ingredients, i)).count();
+        if added > 0 || removed > 0 {
+            changes.push(format!("Ingredients: {} added, {} removed", added, removed));
+        } else if self.ingredients != before.ingredients {
+            changes.push("Ingredients reordered".to_string());
+        }
+
+        if self.steps != before.steps {
+            changes.push(format!("Steps: {} before, {} now", before.steps.len(), self.steps.len()));
+        }
+        changes
+    }
+}
+
+/// What saving an edit would wipe: anylist_rs replaces the whole recipe with
+/// just its name, ingredients and steps.
+pub fn cleared_on_save(recipe: &Recipe) -> Vec<&'static str> {
+    let fields = [
+        ("servings", recipe.servings().is_some()),
+        ("prep time", recipe.prep_time().is_some()),
+        ("cook time", recipe.cook_time().is_some()),
+        ("note", recipe.note().is_some()),
+        ("rating", recipe.rating().is_some()),
+        ("source name", recipe.source_name().is_some()),
+        ("source URL", recipe.source_url().is_some()),
+        ("photos", !recipe.photo_urls().is_empty()),
+    ];
+    fields.into_iter().filter(|(_, set)| *set).map(|(field, _)| field).collect()
+}
+
+fn step_words(step: &str) -> Vec<&str> {
+    step.split_whitespace().collect()
+}
+
+/// Whether two ingredients read the same in a document, ignoring raw text.
+fn same_ingredient(a: &Ingredient, b: &Ingredient) -> bool {
+    let quantity = |i: &Ingredient| i.quantity().filter(|quantity| !quantity.is_empty()).map(str::to_string);
+    let note = |i: &Ingredient| i.note().filter(|note| !note.is_empty()).map(str::to_string);
+    a.name() == b.name() && quantity(a) == quantity(b) && note(a) == note(b)
+}
+
+/// Build an ingredient; `anylist_rs` only creates them by deserializing.
+pub fn new_ingredient(name: &str, quantity: Option<&str>, note: Option<&str>) -> Ingredient {
+    let raw = match quantity {
+        Some(quantity) => format!("{} {}", quantity, name),
+        None => name.to_string(),
+    };
+    serde_json::from_value(serde_json::json!({
+        "name": name,
+        "quantity": quantity,
+        "note": note,
+        "raw_ingredient": raw,
+    }))
+    .expect("ingredient fields match anylist_rs")
+}
+
+/// Parse "name | quantity | note", or free text like "200 g spaghetti (al dente)".
+pub fn parse_ingredient(line: &str) -> Option<Ingredient> {
+    let line = line.trim();
+    if line.is_empty() {
+        return None;
+    }
+    if line.contains('|') {
+        let mut parts = line.splitn(3, '|').map(str::trim).map(|part| (!part.is_empty()).then_some(part));
+        let name = parts.next().flatten()?;
+        let quantity = parts.next().flatten();
+        let note = parts.next().flatten();
+        return Some(new_ingredient(name, quantity, note));
+    }
+    let parsed = parse_item(line);
+    Some(new_ingredient(
+        &parsed.name,
+        parsed.quantity_text().as_deref(),
+        parsed.details.as_deref(),
+    ))
+}
+
+fn ingredient_line(ingredient: &Ingredient) -> String {
+    let quantity = ingredient.quantity().unwrap_or("");
+    // A bare name is read like a shopping list item, so a name such as
+    // "2 eggs" or "salt (to taste)" needs the "|" form to come back as is
+    let reads_back = || parse_ingredient(ingredient.name()).is_some_and(|read| same_ingredient(&read, ingredient));
+    let line = match ingredient.note().filter(|note| !note.is_empty()) {
+        Some(note) => format!("{} | {} | {}", ingredient.name(), quantity, note),
+        None if !quantity.is_empty() => format!("{} | {}", ingredient.name(), quantity),
+        None if reads_back() => ingredient.name().to_string(),
+        None => format!("{} |", ingredient.name()),
+    };
+    // "#10 can tomatoes" would read as a comment
+    if line.starts_with(['#', '\\']) {
+        format!("\\{}", line)
+    } else {
+        line
+    }
+}
+
+pub fn to_document(recipe: &Recipe) -> String {
+    let mut doc = String::from(
+        "# Edit the recipe, then save and close the editor. Lines starting with '#'\n\
+         # are ignored. Closing the editor without changes cancels the edit.\n\
+         #\n\
+         # Ingredients go one per line as \"name | quantity | note\", for example\n\
+         # \"spaghetti | 200 g\" or \"parmesan | 50 g | finely grated\".\n\
+         # Steps go one per line; indented lines continue the step above.\n",
+    );
+
+    let mut read_only = Vec::new();
+    if let Some(servings) = recipe.servings() {
+        read_only.push(format!("Servings: {}", servings));
+    }
+    if let Some(prep_time) = recipe.prep_time() {
+        read_only.push(format!("Prep time: {} minutes", prep_time / 60));
+    }
+    if let Some(cook_time) = recipe.cook_time() {
+        read_only.push(format!("Cook time: {} minutes", cook_time / 60));
+    }
+    if let Some(note) = recipe.note() {
+        read_only.push(format!("Note: {}", note.replace('\n', "\n#   ")));
+    }
+    if let Some(rating) = recipe.rating() {
+        read_only.push(format!("Rating: {}", rating));
+    }
+    if let Some(source_name) = recipe.source_name() {
+        read_only.push(format!("Source: {}", source_name));
+    }
+    if let Some(source_url) = recipe.source_url() {
+        read_only.push(format!("Source URL: {}", source_url));
+    }
+    if !recipe.photo_urls().is_empty() {
+        read_only.push(format!("Photos: {}", recipe.photo_urls().len()));
+    }
+    if !read_only.is_empty() {
+        doc.push_str("#\n# Not editable here:\n");
+        for line in read_only {
+            doc.push_str(&format!("#   {}\n", line));
+        }
+    }
+
+    doc.push_str(&format!("\nName: {}\n\n[Ingredients]\n", recipe.name()));
+    for ingredient in recipe.ingredients() {
+        doc.push_str(&ingredient_line(ingredient));
+        doc.push('\n');
+    }
+    doc.push_str("\n[Steps]\n");
+    for (i, step) in recipe.preparation_steps().iter().enumerate() {
+        let mut lines = step.lines().map(str::trim).filter(|line| !line.is_empty());
+        doc.push_str(&format!("{}. {}\n", i + 1, lines.next().unwrap_or_default()));
+        for line in lines {
+            doc.push_str(&format!("   {}\n", line));
+        }
+    }
+    doc
+}
+
+pub fn parse_document(text: &str) -> Result<RecipeDraft, CliError> {
+    enum Section {
+        Header,
+        Ingredients,
+        Steps,
+    }
+
+    let mut section = Section::Header;
+    let mut name = None;
+    let mut ingredients = Vec::new();
+    let mut steps: Vec<String> = Vec::new();
+
+    for (number, raw) in text.lines().enumerate() {
+        let line = raw.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if matches!(section, Section::Steps)
+            && raw.starts_with(char::is_whitespace)
+            && let Some(step) = steps.last_mut()
+        {
+            step.push('\n');
+            step.push_str(line);
+            continue;
+        }
+        if line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix('\\').unwrap_or(line);
+        match line.to_lowercase().as_str() {
+            "[ingredients]" => {
+                section = Section::Ingredients;
+                continue;
+            }
+            "[steps]" => {
+                section = Section::Steps;
+                continue;
+            }
+            _ => {}
+        }
+
+        match section {
+            Section::Header => match line.split_once(':') {
+                Some((key, value)) if key.trim().eq_ignore_ascii_case("name") => {
+                    name = Some(value.trim().to_string());
+                }
+                _ => {
+                    return Err(CliError::InvalidInput(format!(
+                        "line {}: expected 'Name:' or a section header, found '{}'",
+                        number + 1,
+                        line
+                    )));
+                }
+            },
+            Section::Ingredients => ingredients.extend(parse_ingredient(line)),
+            Section::Steps => steps.push(strip_step_number(line).to_string()),
+        }
+    }
+
+    let name = name.unwrap_or_default();
+    if name.is_empty() {
+        return Err(CliError::InvalidInput("the recipe needs a name".to_string()));
+    }
+    Ok(RecipeDraft { name, ingredients, steps })
+}
+
+/// "3. Stir" and "3) Stir" become "Stir". Only the number the document adds
+/// is removed, so "1. 2) Stir" keeps the step's own "2)".
+fn strip_step_number(line: &str) -> &str {
+    let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    if digits > 0
+        && let Some(rest) = line[digits..].strip_prefix(['.', ')'])
+    {
+        return rest.trim_start();
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn recipe(extra: serde_json::Value) -> Recipe {
+        let mut fields = json!({
+            "id": "r1",
+            "name": "Chili",
+            "ingredients": [],
+            "preparation_steps": [],
+            "note": null,
+            "source_name": null,
+            "source_url": null,
+            "servings": null,
+            "prep_time": null,
+            "cook_time": null,
+            "rating": null,
+            "photo_urls": []
+        });
+        for (key, value) in extra.as_object().unwrap() {
+            fields[key] = value.clone();
+        }
+        serde_json::from_value(fields).unwrap()
+    }
+
+    #[test]
+    fn lists_every_field_saving_clears() {
+        assert!(cleared_on_save(&recipe(json!({}))).is_empty());
+
+        let full = recipe(json!({
+            "servings": "4",
+            "prep_time": 600,
+            "cook_time": 1800,
+            "note": "Better the next day",
+            "rating": 5,
+            "source_name": "Grandma",
+            "source_url": "https://example.com/chili",
+            "photo_urls": ["https://example.com/chili.jpg"]
+        }));
+        assert_eq!(
+            cleared_on_save(&full),
+            ["servings", "prep time", "cook time", "note", "rating", "source name", "source URL", "photos"]
+        );
+
+        let rated = recipe(json!({ "rating": 4, "source_url": "https://example.com" }));
+        assert_eq!(cleared_on_save(&rated), ["rating", "source URL"]);
+    }
+
+    #[test]
+    fn unedited_documents_round_trip() {
+        let original = recipe(json!({
+            "ingredients": [
+                { "name": "beans", "quantity": "2 cans", "note": "drained", "raw_ingredient": "2 cans beans, drained" },
+                { "name": "#10 can tomatoes", "quantity": null, "note": null, "raw_ingredient": "#10 can tomatoes" },
+                { "name": "\\salt", "quantity": "1 tsp", "note": null, "raw_ingredient": null },
+                { "name": "onion", "quantity": "1/2", "note": null, "raw_ingredient": "1/2 onion" }
+            ],
+            "preparation_steps": [
+                "Brown the onion.\n\nThen add the beans.",
+                "2) Simmer for an hour",
+                "  # Serve hot  ",
+                "3. Season"
+            ],
+            "note": "Line one\n# not a comment",
+            "rating": 5
+        }));
+        let before = RecipeDraft::from(&original);
+
+        let mut draft = parse_document(&to_document(&original)).unwrap();
+        draft.keep_unchanged_ingredients(&before);
+        draft.keep_unchanged_steps(&before);
+
+        assert_eq!(draft, before);
+        assert!(draft.changes_from(&before).is_empty());
+    }
+
+    #[test]
+    fn writes_multi_line_steps_as_continuations() {
+        let original = recipe(json!({ "preparation_steps": ["Mix.\nRest for an hour.", "Bake"] }));
+        let document = to_document(&original);
+        assert!(document.contains("1. Mix.\n   Rest for an hour.\n2. Bake\n"), "{}", document);
+
+        let draft = parse_document(&document).unwrap();
+        assert_eq!(draft.steps, ["Mix.\nRest for an hour.", "Bake"]);
+    }
+
+    #[test]
+    fn reads_edits() {
+        let document = "# A comment\n\
+                        Name: White chili\n\
+                        [Ingredients]\n\
+                        chicken | 500 g\n\
+                        2 cups stock (hot)\n\
+                        \\#1 spice mix | 1 tbsp\n\
+                        [Steps]\n\
+                        1. Simmer\n\
+                        \x20  until thick\n\
+                        Serve\n";
+        let draft = parse_document(document).unwrap();
+        assert_eq!(draft.name, "White chili");
+        let names: Vec<&str> = draft.ingredients.iter().map(Ingredient::name).collect();
+        assert_eq!(names, ["chicken", "stock", "#1 spice mix"]);
+        assert_eq!(draft.ingredients[1].note(), Some("hot"));
+        assert_eq!(draft.steps, ["Simmer\nuntil thick", "Serve"]);
+
+        assert!(parse_document("[Steps]\n1. Stir\n").is_err());
+        assert!(parse_document("Servings: 4\n").is_err());
+    }
+}
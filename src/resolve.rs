@@ -0,0 +1,353 @@
+//! Resolve user-typed names to AnyList objects.
+//!
+//! Commands accept either a name or an ID. Names are matched exactly, then
+//! case-insensitively, then against IDs, then as the start of a name, and
+//! finally fuzzily (part of a name or a small edit distance). When a stage
+//! produces several matches the user picks one with an `inquire::Select`, or
+//! gets an error when the command isn't interactive. Commands pass
+//! `interactive` (usually whether stdin is a terminal) so tests and scripts
+//! never prompt.
+//!
+//! Commands that change or remove what they find use [`resolve_to_change`],
+//! which also asks before acting on a single prefix or fuzzy match, so
+//! "eggs" can't silently delete "Eggplant".
+
+use anylist_rs::{ListItem, Recipe};
+use inquire::{Confirm, Select};
+use std::fmt;
+
+use crate::error::CliError;
+use crate::meal_calendar::MealLabel;
+
+/// Something that can be looked up by name or ID.
+pub trait Named {
+    /// Singular noun used in prompts and errors, e.g. "item"
+    const KIND: &'static str;
+
+    fn name(&self) -> &str;
+
+    fn id(&self) -> &str;
+
+    /// One-line description shown when asking the user to choose.
+    fn describe(&self) -> String {
+        format!("{} ({})", self.name(), self.id())
+    }
+}
+
+impl Named for ListItem {
+    const KIND: &'static str = "item";
+
+    fn name(&self) -> &str {
+        ListItem::name(self)
+    }
+
+    fn id(&self) -> &str {
+        ListItem::id(self)
+    }
+
+    fn describe(&self) -> String {
+        let mut description = ListItem::name(self).to_string();
+        if let Some(qty) = self.quantity() {
+            description.push_str(&format!(" ({})", qty));
+        }
+        if let Some(cat) = self.category() {
+            description.push_str(&format!(" [{}]", cat));
+        }
+        if self.is_checked() {
+            description.push_str(" ✓");
+        }
+        format!("{} — {}", description, ListItem::id(self))
+    }
+}
+
+impl Named for Recipe {
+    const KIND: &'static str = "recipe";
+
+    fn name(&self) -> &str {
+        Recipe::name(self)
+    }
+
+    fn id(&self) -> &str {
+        Recipe::id(self)
+    }
+
+    fn describe(&self) -> String {
+        let mut description = Recipe::name(self).to_string();
+        if let Some(servings) = self.servings() {
+            description.push_str(&format!(" (serves {})", servings));
+        }
+        description
+    }
+}
+
+impl Named for MealLabel {
+    const KIND: &'static str = "meal label";
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+/// Find the candidate the user means by `query`.
+pub fn resolve<'a, T: Named>(query: &str, candidates: &'a [T], interactive: bool) -> Result<&'a T, CliError> {
+    resolve_preferring(query, candidates, |_| true, interactive)
+}
+
+/// Like [`resolve`], but matches among candidates accepted by `prefer` win
+/// over the rest (e.g. `item check` prefers unchecked items).
+pub fn resolve_preferring<'a, T: Named>(
+    query: &str,
+    candidates: &'a [T],
+    prefer: impl Fn(&T) -> bool,
+    interactive: bool,
+) -> Result<&'a T, CliError> {
+    find(query, candidates, prefer, interactive).map(|(found, _)| found)
+}
+
+/// Like [`resolve_preferring`], for commands that change or delete what they
+/// find. A lone prefix or fuzzy match is confirmed first, unless `yes` is set;
+/// when not interactive and not `yes`, it's an error.
+pub fn resolve_to_change<'a, T: Named>(
+    query: &str,
+    candidates: &'a [T],
+    prefer: impl Fn(&T) -> bool,
+    action: &str,
+    yes: bool,
+    interactive: bool,
+) -> Result<&'a T, CliError> {
+    let (found, stage) = find(query, candidates, prefer, interactive)?;
+    if yes || !stage.is_loose() {
+        return Ok(found);
+    }
+    if !interactive {
+        return Err(CliError::InvalidInput(format!(
+            "'{}' only partly matches {} '{}'; use its full name or ID",
+            query,
+            T::KIND,
+            found.name()
+        )));
+    }
+    let prompt = format!("{} {} '{}'?", action, T::KIND, found.name());
+    if Confirm::new(&prompt).with_default(false).prompt()? {
+        Ok(found)
+    } else {
+        Err(CliError::PromptCancelled)
+    }
+}
+
+/// How a query matched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stage {
+    Exact,
+    CaseInsensitive,
+    Id,
+    Prefix,
+    Fuzzy,
+    /// Picked by the user from several matches
+    Chosen,
+}
+
+impl Stage {
+    /// Whether the match might not be what the user meant.
+    fn is_loose(self) -> bool {
+        matches!(self, Stage::Prefix | Stage::Fuzzy)
+    }
+
+    /// Whether `candidate` matches `query` at this stage; `query_lower` is
+    /// the query in lowercase.
+    fn matches(self, query: &str, query_lower: &str, candidate: &impl Named) -> bool {
+        match self {
+            Stage::Exact => candidate.name() == query,
+            Stage::CaseInsensitive => candidate.name().to_lowercase() == query_lower,
+            Stage::Id => candidate.id() == query,
+            Stage::Prefix => !query.is_empty() && candidate.name().to_lowercase().starts_with(query_lower),
+            Stage::Fuzzy => is_fuzzy_match(query_lower, &candidate.name().to_lowercase()),
+            Stage::Chosen => false,
+        }
+    }
+}
+
+fn find<'a, T: Named>(
+    query: &str,
+    candidates: &'a [T],
+    prefer: impl Fn(&T) -> bool,
+    interactive: bool,
+) -> Result<(&'a T, Stage), CliError> {
+    let query_lower = query.to_lowercase();
+
+    let stages = [Stage::Exact, Stage::CaseInsensitive, Stage::Id, Stage::Prefix, Stage::Fuzzy];
+
+    for stage in stages {
+        let matches: Vec<&T> = candidates
+            .iter()
+            .filter(|c| stage.matches(query, &query_lower, *c))
+            .collect();
+        if matches.is_empty() {
+            continue;
+        }
+
+        let preferred: Vec<&T> = matches.iter().copied().filter(|c| prefer(c)).collect();
+        let matches = if preferred.is_empty() { matches } else { preferred };
+
+        return match matches.as_slice() {
+            [only] => Ok((only, stage)),
+            _ => choose(query, matches, interactive).map(|chosen| (chosen, Stage::Chosen)),
+        };
+    }
+
+    Err(CliError::NoMatch(T::KIND, query.to_string()))
+}
+
+struct Choice<'a, T: Named>(&'a T);
+
+impl<T: Named> fmt::Display for Choice<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.describe())
+    }
+}
+
+fn choose<'a, T: Named>(query: &str, matches: Vec<&'a T>, interactive: bool) -> Result<&'a T, CliError> {
+    if !interactive {
+        let names: Vec<String> = matches.iter().map(|m| m.describe()).collect();
+        return Err(CliError::AmbiguousMatch(
+            T::KIND,
+            format!("'{}' could be any of: {}", query, names.join(", ")),
+        ));
+    }
+
+    let options: Vec<Choice<T>> = matches.into_iter().map(Choice).collect();
+    let choice = Select::new(
+        &format!("Several {}s match '{}'. Which one?", T::KIND, query),
+        options,
+    )
+    .prompt()?;
+
+    Ok(choice.0)
+}
+
+/// The query within the name, or a typo within a small edit distance. Both
+/// are lowercase.
+fn is_fuzzy_match(query: &str, name: &str) -> bool {
+    if query.is_empty() {
+        return false;
+    }
+    if name.contains(query) {
+        return true;
+    }
+
+    // Allow one typo for short words, two for longer ones
+    let allowed = if query.chars().count() <= 5 { 1 } else { 2 };
+    edit_distance(query, name) <= allowed
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct Thing(&'static str, &'static str);
+
+    impl Named for Thing {
+        const KIND: &'static str = "thing";
+
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn id(&self) -> &str {
+            self.1
+        }
+    }
+
+    const THINGS: &[Thing] = &[
+        Thing("Eggs", "id-1"),
+        Thing("eggs", "id-2"),
+        Thing("Eggplant", "id-3"),
+        Thing("Greek yogurt", "id-4"),
+        Thing("Plain yogurt", "id-5"),
+        Thing("Bananas", "id-6"),
+        Thing("Apples", "id-7"),
+    ];
+
+    fn found<'a>(query: &str, candidates: &'a [Thing]) -> (&'a str, Stage) {
+        find(query, candidates, |_| true, false)
+            .map(|(thing, stage)| (thing.1, stage))
+            .unwrap_or_else(|err| panic!("{:?}: {}", query, err))
+    }
+
+    #[test]
+    fn matches_in_stages() {
+        assert_eq!(found("Eggs", THINGS), ("id-1", Stage::Exact));
+        assert_eq!(found("eggs", THINGS), ("id-2", Stage::Exact));
+        assert_eq!(found("APPLES", THINGS), ("id-7", Stage::CaseInsensitive));
+        assert_eq!(found("id-6", THINGS), ("id-6", Stage::Id));
+        assert_eq!(found("eggp", THINGS), ("id-3", Stage::Prefix));
+        assert_eq!(found("greek", THINGS), ("id-4", Stage::Prefix));
+        assert_eq!(found("bannas", THINGS), ("id-6", Stage::Fuzzy));
+        assert_eq!(found("plant", THINGS), ("id-3", Stage::Fuzzy));
+    }
+
+    #[test]
+    fn earlier_stages_win() {
+        let things = [Thing("Apple", "a"), Thing("Apples", "b")];
+        assert_eq!(found("apple", &things), ("a", Stage::CaseInsensitive));
+    }
+
+    #[test]
+    fn names_inside_the_query_do_not_match() {
+        // "Eggs" used to match "eggs benedict" because the query contained it
+        let things = [Thing("Eggs", "a")];
+        assert!(matches!(
+            find("eggs benedict", &things, |_| true, false),
+            Err(CliError::NoMatch("thing", _))
+        ));
+        assert!(matches!(find("", &things, |_| true, false), Err(CliError::NoMatch(..))));
+    }
+
+    #[test]
+    fn several_matches_are_ambiguous() {
+        // Without prompting, the choice becomes an error
+        for query in ["EGGS", "yogurt", "egg"] {
+            assert!(
+                matches!(find(query, THINGS, |_| true, false), Err(CliError::AmbiguousMatch("thing", _))),
+                "{:?}",
+                query
+            );
+        }
+    }
+
+    #[test]
+    fn preferred_matches_win() {
+        let (thing, stage) = find("EGGS", THINGS, |thing| thing.1 == "id-2", false).unwrap();
+        assert_eq!((thing.1, stage), ("id-2", Stage::CaseInsensitive));
+    }
+
+    #[test]
+    fn loose_matches_need_confirmation() {
+        let change = |query: &str, yes: bool| resolve_to_change(query, THINGS, |_| true, "Delete", yes, false);
+        assert!(matches!(change("eggp", false), Err(CliError::InvalidInput(_))));
+        assert_eq!(change("eggp", true).unwrap().1, "id-3");
+        assert_eq!(change("Eggplant", false).unwrap().1, "id-3");
+        assert_eq!(change("id-3", false).unwrap().1, "id-3");
+    }
+}
@@ -0,0 +1,423 @@
+//! Adding items to a list without creating duplicates.
+//!
+//! Used by `item add` and by the commands that shop for recipes and meal
+//! plans: an item whose normalized name is already on the list is merged into
+//! the existing item (adding up quantities) instead of added again. Shopping
+//! for several recipes first combines their ingredients with
+//! [`aggregate_ingredients`].
+
+use anylist_rs::{AnyListClient, Ingredient, List, Recipe};
+use clap::{Arg, ArgAction, ArgMatches};
+use inquire::{Confirm, MultiSelect};
+use std::io::{self, IsTerminal};
+
+use crate::error::CliError;
+use crate::item_parser::{normalize_name, parse_item};
+use crate::output::{BatchItemResult, BatchRecord, ListItemRecord};
+use crate::quantity::{combine_quantities, scale_text, Quantity};
+
+/// An item to add to a list.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewItem {
+    pub name: String,
+    pub quantity: Option<String>,
+    pub details: Option<String>,
+    pub category: Option<String>,
+}
+
+/// A recipe ingredient as a list item, with its quantity scaled by `ratio`
+/// and the recipe's name in the details.
+pub fn ingredient_item(ingredient: &Ingredient, ratio: f64, recipe_name: &str) -> NewItem {
+    let (name, quantity) = match ingredient.quantity().filter(|quantity| !quantity.trim().is_empty()) {
+        Some(quantity) => (ingredient.name().to_string(), Some(quantity.to_string())),
+        // Some recipes keep the whole line ("2 cups flour") in the name
+        None => {
+            let parsed = parse_item(ingredient.name());
+            let quantity = parsed.quantity_text();
+            (parsed.name, quantity)
+        }
+    };
+    let details = match ingredient.note().filter(|note| !note.trim().is_empty()) {
+        Some(note) => format!("{}; for {}", note, recipe_name),
+        None => format!("for {}", recipe_name),
+    };
+    NewItem {
+        name,
+        quantity: quantity.map(|quantity| scale_text(&quantity, ratio)),
+        details: Some(details),
+        category: None,
+    }
+}
+
+/// The ingredients of several recipes with one item per ingredient: the
+/// quantities of each are added up where their units allow and the details
+/// list the recipes that need it.
+pub fn aggregate_ingredients<'a>(recipes: impl IntoIterator<Item = &'a Recipe>) -> Vec<NewItem> {
+    struct Total {
+        key: String,
+        name: String,
+        quantities: Vec<Quantity>,
+        other_quantities: Vec<String>,
+        notes: Vec<String>,
+        recipes: Vec<String>,
+    }
+
+    let mut totals: Vec<Total> = Vec::new();
+    for recipe in recipes {
+        for ingredient in recipe.ingredients() {
+            let item = ingredient_item(ingredient, 1.0, recipe.name());
+            let key = normalize_name(&item.name);
+            let index = match totals.iter().position(|total| total.key == key) {
+                Some(index) => index,
+                None => {
+                    totals.push(Total {
+                        key,
+                        name: item.name.clone(),
+                        quantities: Vec::new(),
+                        other_quantities: Vec::new(),
+                        notes: Vec::new(),
+                        recipes: Vec::new(),
+                    });
+                    totals.len() - 1
+                }
+            };
+            let total = &mut totals[index];
+
+            match item.quantity.as_deref().map(|text| (text, Quantity::parse(text))) {
+                Some((_, Some(quantity))) => {
+                    match total.quantities.iter_mut().find(|sum| sum.is_compatible(&quantity)) {
+                        Some(sum) => *sum = sum.checked_add(&quantity).expect("compatible"),
+                        None => total.quantities.push(quantity),
+                    }
+                }
+                Some((text, None)) => total.other_quantities.push(text.to_string()),
+                None => {}
+            }
+            if let Some(note) = ingredient.note().filter(|note| !note.trim().is_empty())
+                && !total.notes.iter().any(|known| known == note)
+            {
+                total.notes.push(note.to_string());
+            }
+            if !total.recipes.iter().any(|known| known == recipe.name()) {
+                total.recipes.push(recipe.name().to_string());
+            }
+        }
+    }
+
+    totals
+        .into_iter()
+        .map(|total| {
+            let quantities: Vec<String> = total
+                .quantities
+                .iter()
+                .map(|quantity| quantity.normalized().to_string())
+                .chain(total.other_quantities)
+                .collect();
+            let mut details = total.notes;
+            details.push(format!("for {}", total.recipes.join(", ")));
+            NewItem {
+                name: total.name,
+                quantity: (!quantities.is_empty()).then(|| quantities.join(" + ")),
+                details: Some(details.join("; ")),
+                category: None,
+            }
+        })
+        .collect()
+}
+
+/// Let the user deselect items they already have. Without a terminal, every
+/// item is kept.
+pub fn choose_items(items: Vec<NewItem>, prompt: &str) -> Result<Vec<NewItem>, CliError> {
+    if !io::stdin().is_terminal() {
+        return Ok(items);
+    }
+
+    let options: Vec<String> = items
+        .iter()
+        .map(|item| match &item.quantity {
+            Some(quantity) => format!("{} ({})", item.name, quantity),
+            None => item.name.clone(),
+        })
+        .collect();
+    let all: Vec<usize> = (0..options.len()).collect();
+    let chosen = MultiSelect::new(prompt, options)
+        .with_default(&all)
+        .raw_prompt()?;
+
+    let keep: Vec<usize> = chosen.iter().map(|option| option.index).collect();
+    Ok(items
+        .into_iter()
+        .enumerate()
+        .filter(|(index, _)| keep.contains(index))
+        .map(|(_, item)| item)
+        .collect())
+}
+
+/// What to do when an added item is already on the list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeMode {
+    /// Ask on a terminal, merge otherwise
+    Ask,
+    Always,
+    Never,
+}
+
+impl MergeMode {
+    /// The `--merge` and `--no-merge` flags that pick the mode.
+    pub fn args() -> [Arg; 2] {
+        [
+            Arg::new("merge")
+                .long("merge")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("no_merge")
+                .help("Merge into items already on the list without asking"),
+            Arg::new("no_merge")
+                .long("no-merge")
+                .action(ArgAction::SetTrue)
+                .help("Always add a new item, even if one with the same name exists"),
+        ]
+    }
+
+    pub fn from_matches(matches: &ArgMatches) -> Self {
+        if matches.get_flag("merge") {
+            MergeMode::Always
+        } else if matches.get_flag("no_merge") {
+            MergeMode::Never
+        } else {
+            MergeMode::Ask
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddOutcome {
+    Added,
+    /// Merged into an unchecked item with the same name
+    Merged,
+    /// A checked-off item with the same name was put back on the list
+    Unchecked,
+}
+
+/// Add an item, or merge it into an item with the same normalized name.
+///
+/// `on_list` holds the list's items and is kept up to date, so adding the
+/// same item twice in one batch merges too.
+pub async fn add_or_merge(
+    client: &AnyListClient,
+    list_id: &str,
+    on_list: &mut Vec<ListItemRecord>,
+    new_item: &NewItem,
+    merge: MergeMode,
+) -> Result<(AddOutcome, ListItemRecord), CliError> {
+    let key = normalize_name(&new_item.name);
+    let same = |item: &&ListItemRecord| normalize_name(&item.name) == key;
+    let found = on_list
+        .iter()
+        .filter(same)
+        .find(|item| !item.checked)
+        .or_else(|| on_list.iter().find(same))
+        .map(|item| item.id.clone());
+
+    if merge != MergeMode::Never
+        && let Some(index) = found.and_then(|id| on_list.iter().position(|item| item.id == id))
+    {
+        let current = &on_list[index];
+        // A checked-off item was already bought, so its old quantity doesn't add up
+        let quantity = if current.checked {
+            new_item.quantity.clone().or_else(|| current.quantity.clone())
+        } else {
+            combine_quantities(current.quantity.as_deref(), new_item.quantity.as_deref())
+        };
+        let merged = ListItemRecord {
+            quantity,
+            details: combine_details(current.details.as_deref(), new_item.details.as_deref()),
+            category: new_item.category.clone().or_else(|| current.category.clone()),
+            checked: false,
+            ..current.clone()
+        };
+        let outcome = if current.checked {
+            AddOutcome::Unchecked
+        } else {
+            AddOutcome::Merged
+        };
+
+        if outcome == AddOutcome::Unchecked || merge == MergeMode::Always || confirm_merge(current, &merged)? {
+            // update_item also clears the checked state
+            client
+                .update_item(
+                    list_id,
+                    &merged.id,
+                    &merged.name,
+                    merged.quantity.as_deref(),
+                    merged.details.as_deref(),
+                    merged.category.as_deref(),
+                )
+                .await?;
+            on_list[index] = merged.clone();
+            return Ok((outcome, merged));
+        }
+    }
+
+    let item = client
+        .add_item_with_details(
+            list_id,
+            &new_item.name,
+            new_item.quantity.as_deref(),
+            new_item.details.as_deref(),
+            new_item.category.as_deref(),
+        )
+        .await?;
+    let record = ListItemRecord::from(&item);
+    on_list.push(record.clone());
+    Ok((AddOutcome::Added, record))
+}
+
+fn confirm_merge(current: &ListItemRecord, merged: &ListItemRecord) -> Result<bool, CliError> {
+    if !io::stdin().is_terminal() {
+        return Ok(true);
+    }
+    let mut prompt = format!("'{}' is already on the list", current.name);
+    if let Some(quantity) = &current.quantity {
+        prompt.push_str(&format!(" ({})", quantity));
+    }
+    prompt.push_str(". Merge");
+    if let Some(quantity) = merged.quantity.as_ref().filter(|q| Some(*q) != current.quantity.as_ref()) {
+        prompt.push_str(&format!(" to {}", quantity));
+    }
+    prompt.push('?');
+    Ok(Confirm::new(&prompt).with_default(true).prompt()?)
+}
+
+/// Keep both sets of details when merging, unless one already contains the other.
+pub fn combine_details(current: Option<&str>, new: Option<&str>) -> Option<String> {
+    match (current, new) {
+        (Some(current), Some(new)) if current.contains(new) => Some(current.to_string()),
+        (Some(current), Some(new)) if new.contains(current) => Some(new.to_string()),
+        (Some(current), Some(new)) => Some(format!("{}; {}", current, new)),
+        (current, new) => current.or(new).map(str::to_string),
+    }
+}
+
+/// Add items one by one, merging with the list's items, and report each one.
+pub async fn add_items(
+    client: &AnyListClient,
+    list: &List,
+    items: Vec<NewItem>,
+    merge: MergeMode,
+) -> BatchRecord {
+    let mut on_list: Vec<ListItemRecord> = list.items().iter().map(ListItemRecord::from).collect();
+    let mut results = Vec::new();
+    for new_item in items {
+        let result = add_or_merge(client, list.id(), &mut on_list, &new_item, merge).await;
+        results.push(match result {
+            Ok((AddOutcome::Added, item)) => BatchItemResult::ok(&new_item.name, item),
+            Ok((AddOutcome::Merged, item)) => {
+                BatchItemResult::ok(&new_item.name, item).with_note("merged into the existing item")
+            }
+            Ok((AddOutcome::Unchecked, item)) => {
+                BatchItemResult::ok(&new_item.name, item).with_note("unchecked the existing item")
+            }
+            Err(err) => BatchItemResult::failed(&new_item.name, err.to_string()),
+        });
+    }
+    BatchRecord::new("item.added", list.name(), results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// A recipe from (name, quantity, note) ingredients.
+    fn recipe(name: &str, ingredients: &[(&str, Option<&str>, Option<&str>)]) -> Recipe {
+        let ingredients: Vec<serde_json::Value> = ingredients
+            .iter()
+            .map(|(name, quantity, note)| {
+                json!({ "name": name, "quantity": quantity, "note": note, "raw_ingredient": null })
+            })
+            .collect();
+        serde_json::from_value(json!({
+            "id": name,
+            "name": name,
+            "ingredients": ingredients,
+            "preparation_steps": [],
+            "note": null,
+            "source_name": null,
+            "source_url": null,
+            "servings": null,
+            "prep_time": null,
+            "cook_time": null,
+            "rating": null,
+            "photo_urls": []
+        }))
+        .unwrap()
+    }
+
+    fn summary(items: &[NewItem]) -> Vec<(String, Option<String>, Option<String>)> {
+        items
+            .iter()
+            .map(|item| (item.name.clone(), item.quantity.clone(), item.details.clone()))
+            .collect()
+    }
+
+    fn owned(name: &str, quantity: &str, details: &str) -> (String, Option<String>, Option<String>) {
+        (name.to_string(), Some(quantity.to_string()), Some(details.to_string()))
+    }
+
+    #[test]
+    fn aggregates_across_recipes() {
+        let chili = recipe(
+            "Chili",
+            &[
+                ("beans", Some("400 g"), None),
+                ("onion", Some("1"), Some("chopped")),
+                ("salt", Some("a pinch"), None),
+                ("2 cups stock", None, None),
+                ("tomatoes", Some("2 cans"), None),
+            ],
+        );
+        let salad = recipe(
+            "Salad",
+            &[
+                ("Beans", Some("1 kg"), None),
+                ("Onions", Some("2"), Some("chopped")),
+                ("salt", Some("to taste"), None),
+                ("stock", Some("250 ml"), None),
+                ("tomatoes", Some("300 g"), None),
+            ],
+        );
+        // Chili is planned twice, so its quantities count twice but it is named once.
+        let items = aggregate_ingredients([&chili, &salad, &chili]);
+        assert_eq!(
+            summary(&items),
+            vec![
+                owned("beans", "1.8 kg", "for Chili, Salad"),
+                owned("onion", "4", "chopped; for Chili, Salad"),
+                owned("salt", "a pinch + to taste + a pinch", "for Chili, Salad"),
+                owned("stock", "5.06 cups", "for Chili, Salad"),
+                owned("tomatoes", "4 cans + 300 g", "for Chili, Salad"),
+            ]
+        );
+    }
+
+    #[test]
+    fn scales_a_single_ingredient() {
+        let chili = recipe(
+            "Chili",
+            &[("beans", Some("400 g"), Some("drained")), ("1 onion", None, None), ("salt", None, None)],
+        );
+        let items: Vec<NewItem> = chili
+            .ingredients()
+            .iter()
+            .map(|ingredient| ingredient_item(ingredient, 2.0, "Chili"))
+            .collect();
+        assert_eq!(
+            summary(&items),
+            vec![
+                owned("beans", "800 g", "drained; for Chili"),
+                owned("onion", "2", "for Chili"),
+                ("salt".to_string(), None, Some("for Chili".to_string())),
+            ]
+        );
+    }
+}